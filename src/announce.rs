@@ -0,0 +1,123 @@
+//! 找到结果时的提示模板：控制台提示和通知中的文本由用户给出的模板生成，
+//! 例如 `找到 {pattern}: {pubkey}（用时 {elapsed}，{eta_beaten}）`。`{{` 和 `}}` 表示字面的花括号
+
+use crate::error::GrindError;
+use crate::units;
+
+/// 模板中可用的占位符及其说明
+pub const PLACEHOLDERS: [(&str, &str); 10] = [
+    ("pattern", "目标，例如 abc、...xyz"),
+    ("kind", "匹配类型：前缀、后缀、前后缀、包含或正则"),
+    ("pubkey", "公钥"),
+    ("secret", "私钥（只在控制台提示中出现，通知中为空）"),
+    ("counter", "该密钥对的序号"),
+    ("found", "该目标已找到的数量"),
+    ("count", "该目标需要的数量"),
+    ("time", "找到的时间"),
+    ("elapsed", "本次运行已用的时间"),
+    ("eta_beaten", "按平均运气和当前速度，比预计提前（或推迟）了多久"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Field(&'static str),
+}
+
+/// 解析后的提示模板
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnnounceTemplate {
+    parts: Vec<Part>,
+}
+
+/// 生成一条提示所需的数据
+#[derive(Debug, Clone)]
+pub struct Announcement<'a> {
+    pub pattern: &'a str,
+    pub kind: &'a str,
+    pub public_key: &'a str,
+    /// 为空时 `{secret}` 替换为空字符串
+    pub secret: Option<&'a str>,
+    pub counter: u64,
+    pub found: u64,
+    pub count: u64,
+    pub time: &'a str,
+    pub elapsed_secs: f64,
+    /// 预计找到这个结果的时间减去实际用时，正数表示提前；速度未知时为空
+    pub eta_beaten_secs: Option<f64>,
+}
+
+impl AnnounceTemplate {
+    pub fn parse(template: &str) -> Result<Self, GrindError> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(GrindError::Config("提示模板中有不成对的 '{'（字面的花括号写作 '{{'）".to_string())),
+                        }
+                    }
+                    let field = PLACEHOLDERS.iter().find(|(n, _)| *n == name).map(|(n, _)| *n).ok_or_else(|| {
+                        let names: Vec<&str> = PLACEHOLDERS.iter().map(|(n, _)| *n).collect();
+                        GrindError::Config(format!("提示模板中有未知的占位符 '{{{}}}'（可用: {}）", name, names.join(", ")))
+                    })?;
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Field(field));
+                }
+                '}' => return Err(GrindError::Config("提示模板中有不成对的 '}'（字面的花括号写作 '}}'）".to_string())),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(AnnounceTemplate { parts })
+    }
+
+    /// 模板是否包含私钥
+    pub fn uses_secret(&self) -> bool {
+        self.parts.contains(&Part::Field("secret"))
+    }
+
+    pub fn render(&self, a: &Announcement<'_>) -> String {
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => out.push_str(text),
+                Part::Field(name) => out.push_str(&match *name {
+                    "pattern" => a.pattern.to_string(),
+                    "kind" => a.kind.to_string(),
+                    "pubkey" => a.public_key.to_string(),
+                    "secret" => a.secret.unwrap_or_default().to_string(),
+                    "counter" => a.counter.to_string(),
+                    "found" => a.found.to_string(),
+                    "count" => a.count.to_string(),
+                    "time" => a.time.to_string(),
+                    "elapsed" => units::duration(a.elapsed_secs),
+                    _ => match a.eta_beaten_secs {
+                        Some(secs) if secs >= 0.0 => format!("比预计提前 {}", units::duration(secs)),
+                        Some(secs) => format!("比预计推迟 {}", units::duration(-secs)),
+                        None => "预计时间未知".to_string(),
+                    },
+                }),
+            }
+        }
+        out
+    }
+}
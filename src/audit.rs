@@ -0,0 +1,296 @@
+use crate::error::GrindError;
+use crate::net::HttpClient;
+use crate::records;
+use crate::watch;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+// getMultipleAccounts 单次请求最多查询的账户数
+const RPC_BATCH_SIZE: usize = 100;
+
+/// 从输入中读到的一个待检查的密钥对
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    /// 来源位置，例如 `keypairs_0000.log:12`
+    pub source: String,
+    /// 来源文件
+    pub path: PathBuf,
+    /// 记录中给出的公钥；solana-keygen 密钥文件中没有单独的公钥字段
+    pub public_key: Option<String>,
+    /// base58 编码的私钥（加密的私钥以 `enc1:` 开头）
+    pub secret_key: String,
+}
+
+impl AuditEntry {
+    /// 记录中的公钥；没有时取私钥后 32 字节（无法解码时为空）
+    pub fn resolved_public_key(&self) -> Option<String> {
+        if let Some(public_key) = &self.public_key {
+            return Some(public_key.clone());
+        }
+        let bytes = bs58::decode(&self.secret_key).into_vec().ok().filter(|b| b.len() == 64)?;
+        Some(bs58::encode(&bytes[32..]).into_string())
+    }
+}
+
+/// 检查发现的问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditIssue {
+    /// 私钥无法解码或长度不是 64 字节
+    InvalidLength(String),
+    /// 私钥前 32 字节推导出的公钥与后 32 字节或记录中的公钥不一致
+    Mismatch(String),
+    /// 公钥不在 ed25519 曲线上
+    OffCurve(String),
+    /// 同一公钥在输入中出现多次，附带首次出现的位置
+    Duplicate(String),
+}
+
+impl fmt::Display for AuditIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditIssue::InvalidLength(detail) => write!(f, "长度错误: {}", detail),
+            AuditIssue::Mismatch(detail) => write!(f, "公私钥不一致: {}", detail),
+            AuditIssue::OffCurve(public_key) => write!(f, "公钥 {} 不在 ed25519 曲线上", public_key),
+            AuditIssue::Duplicate(detail) => write!(f, "重复: {}", detail),
+        }
+    }
+}
+
+/// `audit` 的检查结果
+#[derive(Debug, Clone, Default)]
+pub struct AuditReport {
+    /// 读到的密钥对数量
+    pub checked: u64,
+    /// 私钥已加密、无法检查一致性的数量
+    pub encrypted: u64,
+    /// 每个有问题的条目及问题
+    pub issues: Vec<(String, AuditIssue)>,
+    /// 查询了余额时，余额不为 0 的公钥（lamports）
+    pub funded: Vec<(String, u64)>,
+    /// 查询了余额的公钥数量
+    pub balance_checked: u64,
+}
+
+/// 收集 `input`（文件或目录）中的所有密钥对。目录只扫描第一层，支持：
+/// solana-keygen 的 JSON 数组密钥文件，以及本工具的文本、CSV、JSONL 日志和私钥文件。
+pub fn collect_entries(input: &Path) -> Result<Vec<AuditEntry>, GrindError> {
+    let files: Vec<PathBuf> = if input.is_dir() {
+        let mut files: Vec<PathBuf> = fs::read_dir(input)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file())
+            .filter(|path| matches!(path.extension().and_then(|e| e.to_str()), Some("json" | "log" | "csv" | "jsonl")))
+            .collect();
+        files.sort();
+        files
+    } else {
+        vec![input.to_path_buf()]
+    };
+
+    let mut entries = Vec::new();
+    for path in files {
+        if path.extension().and_then(|e| e.to_str()) == Some("json") {
+            if let Some(entry) = parse_keypair_file(&path)? {
+                entries.push(entry);
+            }
+            continue;
+        }
+        let reader = BufReader::new(File::open(&path)?);
+        let mut csv_columns = None;
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            // CSV 首行是表头，记下公钥和私钥所在的列
+            if index == 0 && line.contains("public_key") && !line.starts_with('{') {
+                let columns: Vec<&str> = line.split(',').collect();
+                csv_columns = columns.iter().position(|c| *c == "public_key")
+                    .zip(columns.iter().position(|c| *c == "secret_key"));
+                continue;
+            }
+            let parsed = match csv_columns {
+                Some((pubkey_col, secret_col)) => {
+                    let fields: Vec<&str> = line.split(',').collect();
+                    fields.get(pubkey_col).zip(fields.get(secret_col))
+                        .map(|(p, s)| (p.to_string(), s.to_string()))
+                }
+                None => parse_line(&line),
+            };
+            if let Some((public_key, secret_key)) = parsed {
+                if !secret_key.is_empty() {
+                    entries.push(AuditEntry {
+                        source: format!("{}:{}", path.display(), index + 1),
+                        path: path.clone(),
+                        public_key: Some(public_key),
+                        secret_key,
+                    });
+                }
+            }
+        }
+    }
+    Ok(entries)
+}
+
+// solana-keygen 格式：64 个字节组成的 JSON 数组；其他 JSON 文件忽略
+fn parse_keypair_file(path: &Path) -> Result<Option<AuditEntry>, GrindError> {
+    let text = fs::read_to_string(path)?;
+    let Ok(bytes) = serde_json::from_str::<Vec<u8>>(&text) else { return Ok(None) };
+    Ok(Some(AuditEntry {
+        source: path.display().to_string(),
+        path: path.to_path_buf(),
+        public_key: None,
+        secret_key: bs58::encode(bytes).into_string(),
+    }))
+}
+
+// 从一行日志中取出（公钥，私钥）
+fn parse_line(line: &str) -> Option<(String, String)> {
+    if line.starts_with('{') {
+        let value: serde_json::Value = serde_json::from_str(line).ok()?;
+        return Some((
+            value.get("public_key")?.as_str()?.to_string(),
+            value.get("secret_key")?.as_str()?.to_string(),
+        ));
+    }
+    if let Some(record) = records::parse_keypair_line(line) {
+        return Some((record.public_key, record.private_key));
+    }
+    if let Some(event) = watch::parse_result_entry(line) {
+        return Some((event.public_key, event.secret_key?));
+    }
+    // 私钥文件：`[时间] 公钥: xxx | 私钥: xxx`
+    let (_, rest) = line.split_once("公钥: ")?;
+    let (public_key, secret_key) = rest.split_once(" | 私钥: ")?;
+    Some((public_key.to_string(), secret_key.to_string()))
+}
+
+/// 检查所有条目：长度、公私钥一致性、公钥是否在曲线上，以及重复公钥
+pub fn audit_entries(entries: &[AuditEntry]) -> AuditReport {
+    let mut report = AuditReport::default();
+    let mut seen: HashMap<String, &AuditEntry> = HashMap::new();
+    for entry in entries {
+        report.checked += 1;
+        if entry.secret_key.starts_with("enc1:") {
+            report.encrypted += 1;
+            if let Some(public_key) = &entry.public_key {
+                check_duplicate(&mut report, &mut seen, entry, public_key);
+            }
+            continue;
+        }
+
+        let issue = |report: &mut AuditReport, issue| report.issues.push((entry.source.clone(), issue));
+        let bytes = match bs58::decode(&entry.secret_key).into_vec() {
+            Ok(bytes) if bytes.len() == 64 => bytes,
+            Ok(bytes) => {
+                issue(&mut report, AuditIssue::InvalidLength(format!("私钥为 {} 字节，应为 64 字节", bytes.len())));
+                continue;
+            }
+            Err(e) => {
+                issue(&mut report, AuditIssue::InvalidLength(format!("私钥不是有效的 base58: {}", e)));
+                continue;
+            }
+        };
+
+        // 私钥后 32 字节即公钥；它应当在曲线上，并且与前 32 字节推导出的公钥一致
+        let stored = Pubkey::new_from_array(bytes[32..].try_into().unwrap());
+        if !stored.is_on_curve() {
+            issue(&mut report, AuditIssue::OffCurve(stored.to_string()));
+        }
+        let derived = Keypair::new_from_array(bytes[..32].try_into().unwrap()).pubkey();
+        if derived != stored {
+            issue(&mut report, AuditIssue::Mismatch(format!("私钥推导出的公钥为 {}，私钥中记录的为 {}", derived, stored)));
+        }
+        if let Some(public_key) = &entry.public_key {
+            if *public_key != derived.to_string() {
+                issue(&mut report, AuditIssue::Mismatch(format!("记录的公钥 {} 与私钥推导出的 {} 不一致", public_key, derived)));
+            }
+        }
+        check_duplicate(&mut report, &mut seen, entry, &derived.to_string());
+    }
+    report
+}
+
+// 同一份记录的不同副本不算重复：migrate 生成的同名不同格式文件（keypairs_0000.log 与 .jsonl），
+// 以及结果文件与按公钥命名的 <公钥>.json 密钥文件
+fn is_copy(first: &Path, second: &Path, public_key: &str) -> bool {
+    if first == second {
+        return false;
+    }
+    let stem = |path: &Path| path.file_stem().and_then(|s| s.to_str()).map(str::to_string);
+    stem(first) == stem(second) || stem(first).as_deref() == Some(public_key) || stem(second).as_deref() == Some(public_key)
+}
+
+fn check_duplicate<'a>(report: &mut AuditReport, seen: &mut HashMap<String, &'a AuditEntry>, entry: &'a AuditEntry, public_key: &str) {
+    match seen.get(public_key) {
+        Some(first) if !is_copy(&first.path, &entry.path, public_key) => report.issues.push((
+            entry.source.clone(),
+            AuditIssue::Duplicate(format!("公钥 {} 首次出现于 {}", public_key, first.source)),
+        )),
+        Some(_) => {}
+        None => {
+            seen.insert(public_key.to_string(), entry);
+        }
+    }
+}
+
+/// 通过 Solana JSON-RPC（getMultipleAccounts）查询公钥余额，返回余额不为 0 的公钥
+pub fn lookup_balances(client: &HttpClient, rpc_url: &str, public_keys: &[String]) -> Result<Vec<(String, u64)>, GrindError> {
+    let mut funded = Vec::new();
+    for chunk in public_keys.chunks(RPC_BATCH_SIZE) {
+        for key in chunk {
+            Pubkey::from_str(key).map_err(|_| GrindError::Rpc(format!("无效的公钥 {}", key)))?;
+        }
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getMultipleAccounts",
+            "params": [chunk, { "encoding": "base64", "dataSlice": { "offset": 0, "length": 0 } }],
+        });
+        let response: serde_json::Value = client.post(rpc_url)
+            .send_json(request)
+            .map_err(|e| GrindError::Rpc(e.to_string()))?
+            .into_json()
+            .map_err(|e| GrindError::Rpc(format!("无法解析 RPC 响应: {}", e)))?;
+        if let Some(error) = response.get("error") {
+            return Err(GrindError::Rpc(error.to_string()));
+        }
+        let accounts = response.pointer("/result/value").and_then(|v| v.as_array())
+            .ok_or_else(|| GrindError::Rpc("RPC 响应缺少 result.value".to_string()))?;
+        for (key, account) in chunk.iter().zip(accounts) {
+            let lamports = account.get("lamports").and_then(|v| v.as_u64()).unwrap_or(0);
+            if lamports > 0 {
+                funded.push((key.clone(), lamports));
+            }
+        }
+    }
+    Ok(funded)
+}
+
+/// 通过 Solana JSON-RPC（getSignaturesForAddress）查询公钥最近的一笔交易，返回（签名，区块时间的 Unix 秒数）；
+/// 没有任何交易时为空
+pub fn latest_signature(client: &HttpClient, rpc_url: &str, public_key: &str) -> Result<Option<(String, Option<i64>)>, GrindError> {
+    Pubkey::from_str(public_key).map_err(|_| GrindError::Rpc(format!("无效的公钥 {}", public_key)))?;
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "getSignaturesForAddress",
+        "params": [public_key, { "limit": 1 }],
+    });
+    let response: serde_json::Value = client.post(rpc_url)
+        .send_json(request)
+        .map_err(|e| GrindError::Rpc(e.to_string()))?
+        .into_json()
+        .map_err(|e| GrindError::Rpc(format!("无法解析 RPC 响应: {}", e)))?;
+    if let Some(error) = response.get("error") {
+        return Err(GrindError::Rpc(error.to_string()));
+    }
+    let signatures = response.get("result").and_then(|v| v.as_array())
+        .ok_or_else(|| GrindError::Rpc("RPC 响应缺少 result".to_string()))?;
+    Ok(signatures.first().and_then(|latest| {
+        let signature = latest.get("signature")?.as_str()?.to_string();
+        Some((signature, latest.get("blockTime").and_then(|v| v.as_i64())))
+    }))
+}
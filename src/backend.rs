@@ -0,0 +1,385 @@
+use crate::base58;
+use crate::clock::SharedClock;
+use crate::error::GrindError;
+use crate::target::{self, Target};
+use chacha20::cipher::{KeyIvInit, StreamCipher};
+use chacha20::ChaCha20;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+use serde::{Deserialize, Serialize};
+use solana_sdk::signature::{Keypair, Signer};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 密钥生成后端：成批产生（种子，公钥）对，由调度器（工作线程）负责匹配、计数和记录。
+///
+/// 每个工作线程通过 [`BackendFactory`] 各自创建一个实例，实例内可以保存线程私有的状态
+/// （随机数生成器、设备队列等）。私钥即 `种子 || 公钥`，只在公钥命中或需要写日志时才拼出。
+pub trait KeygenBackend: Send {
+    /// 后端名称，用于启动信息
+    fn name(&self) -> &str;
+
+    /// 填满 `seeds` 和 `public_keys`（两者长度相同），`public_keys[i]` 必须是 `seeds[i]` 推导出的 ed25519 公钥
+    fn generate(&mut self, seeds: &mut [[u8; 32]], public_keys: &mut [[u8; 32]]);
+
+    /// 私钥种子的随机数来源，记录在会话日志、运行报告和暂停状态中，事后可以核对密钥是怎样产生的。
+    /// 默认为每个种子直接取自操作系统熵源
+    fn rng_policy(&self) -> RngPolicy {
+        RngPolicy::Os
+    }
+}
+
+/// 为编号为 `thread_id` 的工作线程创建后端实例
+pub type BackendFactory = Arc<dyn Fn(usize) -> Box<dyn KeygenBackend> + Send + Sync>;
+
+/// 默认的 CPU 后端：逐个调用 `Keypair::new()`
+#[derive(Debug, Default)]
+pub struct CpuBackend;
+
+impl KeygenBackend for CpuBackend {
+    fn name(&self) -> &str {
+        "cpu"
+    }
+
+    fn generate(&mut self, seeds: &mut [[u8; 32]], public_keys: &mut [[u8; 32]]) {
+        for (seed, public_key) in seeds.iter_mut().zip(public_keys.iter_mut()) {
+            let keypair = Keypair::new();
+            seed.copy_from_slice(&keypair.to_bytes()[..32]);
+            *public_key = keypair.pubkey().to_bytes();
+        }
+    }
+}
+
+/// 默认后端的工厂
+pub fn cpu() -> BackendFactory {
+    Arc::new(|_| Box::new(CpuBackend))
+}
+
+/// 用户态随机数生成器重新从操作系统熵源取种子的默认间隔
+pub const DEFAULT_RESEED: Reseed = Reseed::Keys(1_000_000);
+// 一个 ChaCha20 密钥最多产生的种子数：块计数器为 32 位，每块 64 字节可产生两个种子，留出一半余量
+const CHACHA_MAX_KEYS: u64 = 1 << 32;
+
+/// 私钥种子的随机数来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum RngPolicy {
+    /// 每个种子都直接取自操作系统熵源（`Keypair::new`）
+    #[default]
+    Os,
+    /// 每个工作线程一个 ChaCha20 密钥流，密钥取自操作系统熵源，并按 `reseed` 定期换用新的密钥
+    Chacha20 { reseed: Reseed },
+    /// 模拟后端的非密码学随机数，产生的私钥不能使用
+    Simulated,
+}
+
+impl fmt::Display for RngPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RngPolicy::Os => write!(f, "操作系统熵源（每个密钥单独读取）"),
+            RngPolicy::Chacha20 { reseed } => write!(f, "ChaCha20（{}）", reseed),
+            RngPolicy::Simulated => write!(f, "模拟（非密码学随机数）"),
+        }
+    }
+}
+
+/// 用户态随机数生成器重新从操作系统熵源取种子的间隔：按每个工作线程生成的密钥数或经过的时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reseed {
+    Keys(u64),
+    Secs(u64),
+}
+
+impl Reseed {
+    /// 解析 `--reseed-every` 的值：数量（可带 K、M、G 后缀），或带 `s`、`min`、`h` 后缀的时间
+    pub fn parse(value: &str) -> Result<Self, GrindError> {
+        let invalid = || GrindError::Config(format!(
+            "无效的重新播种间隔 '{}'（数量如 10M，或时间如 30s、10min、1h）", value
+        ));
+        for (suffix, unit) in [("min", 60), ("h", 3600), ("s", 1)] {
+            if let Some(number) = value.strip_suffix(suffix) {
+                let secs = number.parse::<u64>().ok().and_then(|n| n.checked_mul(unit)).filter(|&n| n > 0);
+                return secs.map(Reseed::Secs).ok_or_else(invalid);
+            }
+        }
+        match parse_quantity(value).ok_or_else(invalid)? {
+            keys if keys > CHACHA_MAX_KEYS => Err(GrindError::Config(format!(
+                "重新播种间隔不能超过 {} 个密钥（ChaCha20 一个密钥能安全产生的数量）", CHACHA_MAX_KEYS
+            ))),
+            keys => Ok(Reseed::Keys(keys)),
+        }
+    }
+}
+
+impl fmt::Display for Reseed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reseed::Keys(keys) => write!(f, "每个线程每 {} 个密钥重新播种", keys),
+            Reseed::Secs(secs) => write!(f, "每个线程每 {} 秒重新播种", secs),
+        }
+    }
+}
+
+/// 使用用户态随机数生成器的 CPU 后端：种子来自 ChaCha20 密钥流，省去每个密钥一次的系统调用
+pub struct ChaChaBackend {
+    reseed: Reseed,
+    cipher: ChaCha20,
+    keys_since_reseed: u64,
+    clock: SharedClock,
+    reseeded_at: Instant,
+}
+
+impl ChaChaBackend {
+    /// 按时间重新播种时从 `clock` 读取时间
+    pub fn new(reseed: Reseed, clock: SharedClock) -> Self {
+        let reseeded_at = clock.now();
+        ChaChaBackend { reseed, cipher: fresh_cipher(), keys_since_reseed: 0, clock, reseeded_at }
+    }
+
+    fn reseed_due(&self) -> bool {
+        self.keys_since_reseed >= CHACHA_MAX_KEYS || match self.reseed {
+            Reseed::Keys(keys) => self.keys_since_reseed >= keys,
+            Reseed::Secs(secs) => self.clock.now().duration_since(self.reseeded_at) >= Duration::from_secs(secs),
+        }
+    }
+}
+
+// 以操作系统熵源取出的新密钥开始一个密钥流；每个密钥只用一次，nonce 可以固定为零
+fn fresh_cipher() -> ChaCha20 {
+    let mut key = [0u8; 32];
+    OsRng.fill_bytes(&mut key);
+    ChaCha20::new(&key.into(), &[0u8; 12].into())
+}
+
+impl KeygenBackend for ChaChaBackend {
+    fn name(&self) -> &str {
+        "cpu"
+    }
+
+    fn generate(&mut self, seeds: &mut [[u8; 32]], public_keys: &mut [[u8; 32]]) {
+        for (seed, public_key) in seeds.iter_mut().zip(public_keys.iter_mut()) {
+            if self.reseed_due() {
+                self.cipher = fresh_cipher();
+                self.keys_since_reseed = 0;
+                self.reseeded_at = self.clock.now();
+            }
+            *seed = [0u8; 32];
+            self.cipher.apply_keystream(seed);
+            self.keys_since_reseed += 1;
+            *public_key = Keypair::new_from_array(*seed).pubkey().to_bytes();
+        }
+    }
+
+    fn rng_policy(&self) -> RngPolicy {
+        RngPolicy::Chacha20 { reseed: self.reseed }
+    }
+}
+
+/// 使用 ChaCha20 用户态随机数生成器的 CPU 后端的工厂，每个工作线程各自取种子
+pub fn cpu_chacha20(reseed: Reseed, clock: SharedClock) -> BackendFactory {
+    Arc::new(move |_| Box::new(ChaChaBackend::new(reseed, Arc::clone(&clock))))
+}
+
+/// 拼出 64 字节的私钥（与 `Keypair::to_bytes` 的布局相同）
+pub fn secret_key(seed: &[u8; 32], public_key: &[u8; 32]) -> [u8; 64] {
+    let mut secret = [0u8; 64];
+    secret[..32].copy_from_slice(seed);
+    secret[32..].copy_from_slice(public_key);
+    secret
+}
+
+/// `--simulate` 的参数：合成公钥的总速度上限，以及每隔多少个公钥注入一个命中
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimulationSpec {
+    /// 每秒生成的公钥总数上限；为空时不限速
+    pub rate: Option<u64>,
+    pub match_every: u64,
+}
+
+impl SimulationSpec {
+    /// 解析 `rate=1M,match-every=5M`，数量可带 K、M、G 后缀
+    pub fn parse(value: &str) -> Result<Self, GrindError> {
+        let mut spec = SimulationSpec { rate: None, match_every: 1_000_000 };
+        for part in value.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, number) = part.split_once('=')
+                .ok_or_else(|| GrindError::Config(format!("模拟参数 '{}' 应为 key=value 形式", part)))?;
+            let number = parse_quantity(number)
+                .ok_or_else(|| GrindError::Config(format!("模拟参数 '{}' 中的数量无效", part)))?;
+            match key {
+                "rate" => spec.rate = Some(number),
+                "match-every" => spec.match_every = number,
+                _ => return Err(GrindError::Config(format!("未知的模拟参数 '{}'（可用: rate、match-every）", key))),
+            }
+        }
+        Ok(spec)
+    }
+}
+
+/// 解析可带 K、M、G 后缀的正整数数量，例如 `5M`
+pub fn parse_quantity(value: &str) -> Option<u64> {
+    let (digits, multiplier) = match value.as_bytes().last()? {
+        b'K' | b'k' => (&value[..value.len() - 1], 1_000),
+        b'M' | b'm' => (&value[..value.len() - 1], 1_000_000),
+        b'G' | b'g' => (&value[..value.len() - 1], 1_000_000_000),
+        _ => (value, 1),
+    };
+    digits.parse::<u64>().ok()?.checked_mul(multiplier).filter(|&n| n > 0)
+}
+
+// 模拟后端为正则目标随机抽取命中公钥的次数上限
+const SIMULATED_REGEX_TRIES: usize = 1_000_000;
+// 模拟后端为其他目标随机补齐编码的次数上限；一般几次之内就能得到 32 字节的公钥
+const SIMULATED_FILL_TRIES: usize = 10_000;
+
+/// 模拟后端：不做椭圆曲线运算，直接产生随机的"公钥"，并按固定间隔注入命中目标的公钥，
+/// 用于在几分钟内检验输出、轮转和通知等整条流水线。产生的私钥与公钥不对应，不能使用。
+pub struct SimulatedBackend {
+    spec: SimulationSpec,
+    targets: Vec<Target>,
+    ignore_case: bool,
+    // 所有线程共享的已生成数量，决定何时注入命中
+    produced: Arc<AtomicU64>,
+    threads: u64,
+    clock: SharedClock,
+    started: Instant,
+    local_produced: u64,
+    rng: u64,
+}
+
+/// 模拟后端的工厂；速度上限由 `threads` 个工作线程平分，限速按 `clock` 计时
+pub fn simulated(spec: SimulationSpec, targets: Vec<Target>, ignore_case: bool, threads: usize, clock: SharedClock) -> BackendFactory {
+    let produced = Arc::new(AtomicU64::new(0));
+    Arc::new(move |thread_id| {
+        let mut seed = [0u8; 8];
+        OsRng.fill_bytes(&mut seed);
+        Box::new(SimulatedBackend {
+            spec,
+            targets: targets.clone(),
+            ignore_case,
+            produced: Arc::clone(&produced),
+            threads: threads.max(1) as u64,
+            clock: Arc::clone(&clock),
+            started: clock.now(),
+            local_produced: 0,
+            rng: u64::from_le_bytes(seed) ^ thread_id as u64,
+        })
+    })
+}
+
+impl SimulatedBackend {
+    // splitmix64：模拟数据不需要密码学强度的随机数
+    fn next_u64(&mut self) -> u64 {
+        self.rng = self.rng.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn fill_random(&mut self, out: &mut [u8; 32]) {
+        for chunk in out.chunks_mut(8) {
+            chunk.copy_from_slice(&self.next_u64().to_le_bytes());
+        }
+    }
+
+    // 目标字符串的一种具体写法：忽略大小写和 leet 目标的每个字符随机取一种能匹配的 base58 字符；
+    // 有字符没有任何 base58 写法时返回 None
+    fn concrete(&mut self, pattern: &str, leet: bool) -> Option<String> {
+        pattern.bytes()
+            .map(|c| {
+                let forms = target::char_forms(c, self.ignore_case, leet);
+                (!forms.is_empty()).then(|| forms[(self.next_u64() % forms.len() as u64) as usize] as char)
+            })
+            .collect()
+    }
+
+    // 用随机的 base58 字符补齐目标的前缀、包含的字符串和后缀，直到解码结果恰好是 32 字节；
+    // 编码长度随机取 32..=44，前缀以多个 1（前导零字节）开头时编码会更短。
+    // 正则目标无法直接构造，改为随机抽取，最多 SIMULATED_REGEX_TRIES 次仍未命中时这一次不注入；
+    // 其他目标最多补齐 SIMULATED_FILL_TRIES 次，同样不会卡住工作线程
+    fn matching_key(&mut self, target: &Target) -> [u8; 32] {
+        if let Some(regex) = &target.regex {
+            let mut key = [0u8; 32];
+            for _ in 0..SIMULATED_REGEX_TRIES {
+                self.fill_random(&mut key);
+                if regex.is_match(&bs58::encode(key).into_string()) {
+                    break;
+                }
+            }
+            return key;
+        }
+        let mut key = [0u8; 32];
+        for _ in 0..SIMULATED_FILL_TRIES {
+            let (Some(prefix), Some(contains), Some(suffix)) = (
+                self.concrete(&target.prefix, target.leet),
+                self.concrete(&target.contains, target.leet),
+                self.concrete(&target.suffix, target.leet),
+            ) else {
+                break;
+            };
+            let len = 32 + (self.next_u64() % 13) as usize;
+            let fill = len.saturating_sub(prefix.len() + contains.len() + suffix.len());
+            // 包含的字符串放在补齐字符中的随机位置
+            let insert_at = (self.next_u64() % (fill as u64 + 1)) as usize;
+            let mut encoded = prefix;
+            for i in 0..fill {
+                if i == insert_at {
+                    encoded.push_str(&contains);
+                }
+                encoded.push(base58::ALPHABET[(self.next_u64() % 58) as usize] as char);
+            }
+            if insert_at == fill {
+                encoded.push_str(&contains);
+            }
+            encoded.push_str(&suffix);
+            if let Ok(bytes) = bs58::decode(&encoded).into_vec() {
+                if let Ok(key) = <[u8; 32]>::try_from(bytes.as_slice()) {
+                    if bs58::encode(key).into_string() == encoded {
+                        return key;
+                    }
+                }
+            }
+        }
+        self.fill_random(&mut key);
+        key
+    }
+}
+
+impl KeygenBackend for SimulatedBackend {
+    fn name(&self) -> &str {
+        "simulate"
+    }
+
+    fn generate(&mut self, seeds: &mut [[u8; 32]], public_keys: &mut [[u8; 32]]) {
+        let batch_start = self.produced.fetch_add(public_keys.len() as u64, Ordering::Relaxed);
+        for (i, (seed, public_key)) in seeds.iter_mut().zip(public_keys.iter_mut()).enumerate() {
+            self.fill_random(seed);
+            let n = batch_start + i as u64 + 1;
+            if n.is_multiple_of(self.spec.match_every) && !self.targets.is_empty() {
+                let idx = ((n / self.spec.match_every - 1) % self.targets.len() as u64) as usize;
+                let target = self.targets[idx].clone();
+                *public_key = self.matching_key(&target);
+            } else {
+                self.fill_random(public_key);
+            }
+        }
+
+        // 限速：本线程按平分后的速度，生成得太快就休眠
+        self.local_produced += public_keys.len() as u64;
+        if let Some(rate) = self.spec.rate {
+            let per_thread = (rate as f64 / self.threads as f64).max(1.0);
+            let due = Duration::from_secs_f64(self.local_produced as f64 / per_thread);
+            if let Some(ahead) = due.checked_sub(self.clock.now().duration_since(self.started)) {
+                thread::sleep(ahead);
+            }
+        }
+    }
+
+    fn rng_policy(&self) -> RngPolicy {
+        RngPolicy::Simulated
+    }
+}
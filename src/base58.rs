@@ -0,0 +1,508 @@
+/// Base58 字母表（不含 0、O、I、l）
+pub const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// 32 字节公钥编码后的最大长度
+pub const MAX_ENCODED_LEN: usize = 44;
+
+/// 返回 base58 字符对应的数值
+pub fn digit_value(c: u8) -> Option<u64> {
+    ALPHABET.iter().position(|&a| a == c).map(|v| v as u64)
+}
+
+/// 字母表中没有的字符最接近的替代字符：`0`、`O` 换成 `o`，`I` 换成 `i`，`l` 换成 `L`；其他字符没有替代
+pub fn substitute(c: char) -> Option<char> {
+    match c {
+        '0' | 'O' => Some('o'),
+        'I' => Some('i'),
+        'l' => Some('L'),
+        _ => None,
+    }
+}
+
+/// 320 位无符号整数（小端 u64 数组），足以容纳 58^44 > 2^256
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct U320([u64; 5]);
+
+impl U320 {
+    pub const ZERO: U320 = U320([0; 5]);
+
+    pub fn from_u64(v: u64) -> Self {
+        U320([v, 0, 0, 0, 0])
+    }
+
+    /// 2^exp（exp < 320）
+    pub fn pow2(exp: u32) -> Self {
+        let mut limbs = [0u64; 5];
+        limbs[(exp / 64) as usize] = 1 << (exp % 64);
+        U320(limbs)
+    }
+
+    pub fn pow58(exp: usize) -> Self {
+        (0..exp).fold(U320::from_u64(1), |acc, _| acc.mul_small(58))
+    }
+
+    pub fn mul_small(self, m: u64) -> Self {
+        let mut out = [0u64; 5];
+        let mut carry = 0u128;
+        for (o, &limb) in out.iter_mut().zip(self.0.iter()) {
+            let v = limb as u128 * m as u128 + carry;
+            *o = v as u64;
+            carry = v >> 64;
+        }
+        U320(out)
+    }
+
+    pub fn add_small(self, a: u64) -> Self {
+        let mut out = self.0;
+        let mut carry = a;
+        for limb in out.iter_mut() {
+            let (v, overflow) = limb.overflowing_add(carry);
+            *limb = v;
+            carry = overflow as u64;
+            if carry == 0 {
+                break;
+            }
+        }
+        U320(out)
+    }
+
+    /// 减一（调用方保证非零）
+    pub fn sub_one(self) -> Self {
+        let mut out = self.0;
+        for limb in out.iter_mut() {
+            let (v, borrow) = limb.overflowing_sub(1);
+            *limb = v;
+            if !borrow {
+                break;
+            }
+        }
+        U320(out)
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0.iter().rev().fold(0.0, |acc, &limb| acc * 18_446_744_073_709_551_616.0 + limb as f64)
+    }
+
+    pub fn from_be_bytes32(bytes: &[u8; 32]) -> Self {
+        let mut limbs = [0u64; 5];
+        for (i, chunk) in bytes.chunks_exact(8).enumerate() {
+            limbs[3 - i] = u64::from_be_bytes(chunk.try_into().unwrap());
+        }
+        U320(limbs)
+    }
+
+    /// 有效位数
+    pub fn bits(&self) -> u32 {
+        match self.0.iter().rposition(|&limb| limb != 0) {
+            Some(i) => i as u32 * 64 + (64 - self.0[i].leading_zeros()),
+            None => 0,
+        }
+    }
+
+    pub fn shr_bits(self, shift: u32) -> Self {
+        let (limb_shift, bit_shift) = ((shift / 64) as usize, shift % 64);
+        let mut out = [0u64; 5];
+        for (i, o) in out.iter_mut().enumerate() {
+            let src = i + limb_shift;
+            if src < 5 {
+                *o = self.0[src] >> bit_shift;
+                if bit_shift > 0 && src + 1 < 5 {
+                    *o |= self.0[src + 1] << (64 - bit_shift);
+                }
+            }
+        }
+        U320(out)
+    }
+
+    /// 低 128 位
+    pub fn low_u128(&self) -> u128 {
+        self.0[0] as u128 | (self.0[1] as u128) << 64
+    }
+
+    /// 按 256 位大端整数解释时的最高 64 位；超出 256 位时饱和为 u64::MAX
+    pub fn top_u64_of_256(self) -> u64 {
+        if self.0[4] != 0 {
+            u64::MAX
+        } else {
+            self.0[3]
+        }
+    }
+}
+
+impl PartialOrd for U320 {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for U320 {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+/// 计算编码结果以 `prefix` 开头的 32 字节公钥，其最高 8 字节（大端 u64）可能落入的区间。
+/// 返回的区间是充分的（超集），精确判断仍需编码后比较；前缀含非法字符或过长时返回空。
+pub fn prefix_top_ranges(prefix: &str) -> Vec<(u64, u64)> {
+    let bytes = prefix.as_bytes();
+    if bytes.is_empty() {
+        return vec![(0, u64::MAX)];
+    }
+    if bytes.len() > MAX_ENCODED_LEN || bytes.iter().any(|&c| digit_value(c).is_none()) {
+        return Vec::new();
+    }
+
+    // 开头的 '1' 对应值为 0 的前导字节：只能粗略限制最高若干字节为 0
+    let leading_ones = bytes.iter().take_while(|&&c| c == b'1').count();
+    if leading_ones > 0 {
+        let hi = if leading_ones >= 8 { 0 } else { u64::MAX >> (8 * leading_ones) };
+        return vec![(0, hi)];
+    }
+
+    // 无前导 '1' 说明首字节非零：值域为 [2^248, 2^256)
+    prefix_intervals(bytes, U320::pow2(248), U320::pow2(256).sub_one())
+        .into_iter()
+        .map(|(lo, hi)| (lo.top_u64_of_256(), hi.top_u64_of_256()))
+        .collect()
+}
+
+/// 随机 32 字节公钥的编码以 `prefix` 开头的精确概率（按值域中满足条件的整数个数计算）；
+/// 前缀含非法字符或不可能出现在任何公钥中时为 0
+pub fn prefix_probability(prefix: &str) -> f64 {
+    let bytes = prefix.as_bytes();
+    if bytes.len() > MAX_ENCODED_LEN || bytes.iter().any(|&c| digit_value(c).is_none()) {
+        return 0.0;
+    }
+    // 开头的每个 '1' 是一个值为 0 的字节，概率 1/256
+    let leading_ones = bytes.iter().take_while(|&&c| c == b'1').count();
+    let rest = &bytes[leading_ones..];
+    if leading_ones > 32 || (leading_ones == 32 && !rest.is_empty()) {
+        return 0.0;
+    }
+    let zero_bytes = 2f64.powi(-8 * leading_ones as i32);
+    if rest.is_empty() {
+        return zero_bytes;
+    }
+    // 恰好 leading_ones 个前导零字节：其余部分首字节非零，值域为 [2^(bits-8), 2^bits)
+    let bits = 8 * (32 - leading_ones) as u32;
+    let count: f64 = prefix_intervals(rest, U320::pow2(bits - 8), U320::pow2(bits).sub_one())
+        .into_iter()
+        .map(|(lo, hi)| sub_u320(hi, lo).add_small(1).to_f64())
+        .sum();
+    count / 2f64.powi(256)
+}
+
+// 数值编码（不含前导 '1'）以 `digits` 开头、且落在 [min_value, max_value] 中的所有整数区间。
+// 编码长度为 len 时，值域为 [p * 58^(len-n), (p+1) * 58^(len-n) - 1]，不同长度的区间互不相交
+fn prefix_intervals(digits: &[u8], min_value: U320, max_value: U320) -> Vec<(U320, U320)> {
+    let p = digits.iter().fold(U320::ZERO, |acc, &c| acc.mul_small(58).add_small(digit_value(c).unwrap()));
+    let mut scale = U320::from_u64(1);
+    let mut intervals = Vec::new();
+    for _ in digits.len()..=MAX_ENCODED_LEN {
+        let lo = mul_u320(p, scale).max(min_value);
+        let hi = mul_u320(p.add_small(1), scale).sub_one().min(max_value);
+        if lo <= hi {
+            intervals.push((lo, hi));
+        }
+        scale = scale.mul_small(58);
+    }
+    intervals
+}
+
+fn mul_u320(a: U320, b: U320) -> U320 {
+    let mut out = [0u64; 5];
+    for i in 0..5 {
+        let mut carry = 0u128;
+        for j in 0..(5 - i) {
+            let v = out[i + j] as u128 + a.0[i] as u128 * b.0[j] as u128 + carry;
+            out[i + j] = v as u64;
+            carry = v >> 64;
+        }
+    }
+    U320(out)
+}
+
+// a - b（调用方保证 a >= b）
+fn sub_u320(a: U320, b: U320) -> U320 {
+    let mut out = [0u64; 5];
+    let mut borrow = false;
+    for (o, (&x, &y)) in out.iter_mut().zip(a.0.iter().zip(b.0.iter())) {
+        let (v, b1) = x.overflowing_sub(y);
+        let (v, b2) = v.overflowing_sub(borrow as u64);
+        *o = v;
+        borrow = b1 || b2;
+    }
+    U320(out)
+}
+
+// 58^5 可放入 u32，编码时以它为基数分段做除法
+const BASE_58_5: u64 = 656_356_768;
+// 2^256 < 58^45，9 段足够
+const LIMBS: usize = 9;
+
+/// 预分配的编码结果缓冲区，避免每个公钥一次堆分配
+#[derive(Clone, Copy)]
+pub struct EncodedKey {
+    buf: [u8; MAX_ENCODED_LEN],
+    len: u8,
+}
+
+impl Default for EncodedKey {
+    fn default() -> Self {
+        EncodedKey { buf: [b'1'; MAX_ENCODED_LEN], len: 0 }
+    }
+}
+
+impl EncodedKey {
+    pub fn as_str(&self) -> &str {
+        // 缓冲区中只会写入 base58 字母表中的 ASCII 字符
+        std::str::from_utf8(&self.buf[..self.len as usize]).unwrap()
+    }
+}
+
+/// 针对 32 字节定长输入的 base58 编码，结果与 `Pubkey::to_string()` 一致
+pub fn encode_32(input: &[u8; 32], out: &mut EncodedKey) {
+    // 以大端 u32 为单位的被除数
+    let mut words = [0u32; 8];
+    for (word, chunk) in words.iter_mut().zip(input.chunks_exact(4)) {
+        *word = u32::from_be_bytes(chunk.try_into().unwrap());
+    }
+
+    // 反复除以 58^5，得到低位在前的 58^5 进制各段
+    let mut limbs = [0u32; LIMBS];
+    let mut first_nonzero = words.iter().position(|&w| w != 0).unwrap_or(words.len());
+    for limb in limbs.iter_mut() {
+        if first_nonzero == words.len() {
+            break;
+        }
+        let mut rem = 0u64;
+        for word in words[first_nonzero..].iter_mut() {
+            let acc = (rem << 32) | *word as u64;
+            *word = (acc / BASE_58_5) as u32;
+            rem = acc % BASE_58_5;
+        }
+        *limb = rem as u32;
+        while first_nonzero < words.len() && words[first_nonzero] == 0 {
+            first_nonzero += 1;
+        }
+    }
+
+    // 展开为 45 位 base58 数字（高位在前）
+    let mut digits = [0u8; LIMBS * 5];
+    for (i, &limb) in limbs.iter().enumerate() {
+        let mut v = limb;
+        for j in 0..5 {
+            digits[LIMBS * 5 - 1 - (i * 5 + j)] = (v % 58) as u8;
+            v /= 58;
+        }
+    }
+
+    // 前导零字节编码为 '1'，其余去掉数值上的前导零
+    let zero_bytes = input.iter().take_while(|&&b| b == 0).count();
+    let skip = digits.iter().position(|&d| d != 0).unwrap_or(digits.len());
+    let mut len = 0;
+    for _ in 0..zero_bytes {
+        out.buf[len] = b'1';
+        len += 1;
+    }
+    for &d in &digits[skip..] {
+        out.buf[len] = ALPHABET[d as usize];
+        len += 1;
+    }
+    out.len = len as u8;
+}
+
+/// 批量编码，`outputs` 由调用方预先分配并在批次之间复用
+pub fn encode_batch(inputs: &[[u8; 32]], outputs: &mut [EncodedKey]) {
+    for (input, out) in inputs.iter().zip(outputs.iter_mut()) {
+        encode_32(input, out);
+    }
+}
+
+/// 只计算编码结果前 `len` 个字符的编码器，供前缀匹配使用。
+/// 前缀检查只关心最高位的若干个 base58 数字，无需把 44 个字符全部算出来。
+pub struct PrefixEncoder {
+    len: usize,
+    // pow58[i] = 58^i
+    pow58: Vec<U320>,
+}
+
+impl PrefixEncoder {
+    pub fn new(len: usize) -> Self {
+        let pow58 = (0..=MAX_ENCODED_LEN).map(U320::pow58).collect();
+        PrefixEncoder { len, pow58 }
+    }
+
+    /// 将公钥编码结果的前 `len` 个字符写入 `out`（编码结果更短时写入全部）
+    pub fn encode(&self, input: &[u8; 32], out: &mut EncodedKey) {
+        // 前导零字节很少见（1/256），直接走完整编码
+        if input[0] == 0 {
+            encode_32(input, out);
+            out.len = out.len.min(self.len as u8);
+            return;
+        }
+
+        // 首字节非零时 value >= 2^248，编码长度为满足 value >= 58^(total-1) 的最大 total
+        let value = U320::from_be_bytes32(input);
+        let total = (1..=MAX_ENCODED_LEN).rev()
+            .find(|&n| value >= self.pow58[n - 1])
+            .unwrap_or(1);
+        // 商超过 64 位时估算不再可靠，退回完整编码
+        if self.len >= total || self.len > 10 {
+            encode_32(input, out);
+            out.len = out.len.min(self.len as u8);
+            return;
+        }
+
+        // quotient = value / 58^(total-len) 即前 len 位数字。
+        // 用截断到 64 位的除数估算商（只会偏大），再逐次修正。
+        let divisor = self.pow58[total - self.len];
+        let shift = divisor.bits().saturating_sub(64);
+        let top_divisor = divisor.shr_bits(shift).low_u128();
+        let mut quotient = (value.shr_bits(shift).low_u128() / top_divisor) as u64;
+        while divisor.mul_small(quotient) > value {
+            quotient -= 1;
+        }
+
+        for i in (0..self.len).rev() {
+            out.buf[i] = ALPHABET[(quotient % 58) as usize];
+            quotient /= 58;
+        }
+        out.len = self.len as u8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    // 测试用的确定性随机数（splitmix64）
+    struct Rng(u64);
+
+    impl Rng {
+        fn next_u64(&mut self) -> u64 {
+            self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = self.0;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        }
+
+        fn key(&mut self) -> [u8; 32] {
+            let mut key = [0u8; 32];
+            for chunk in key.chunks_mut(8) {
+                chunk.copy_from_slice(&self.next_u64().to_le_bytes());
+            }
+            key
+        }
+    }
+
+    // 数值编码为 `digits`（不含前导 '1'）的 32 字节公钥
+    fn key_from_digits(digits: &str) -> [u8; 32] {
+        let bytes = bs58::decode(digits).into_vec().unwrap();
+        let mut key = [0u8; 32];
+        key[32 - bytes.len()..].copy_from_slice(&bytes);
+        key
+    }
+
+    // 随机公钥、0 到 32 个前导零字节的公钥，以及编码长度变化处的边界值
+    fn sample_keys() -> Vec<[u8; 32]> {
+        let mut rng = Rng(0x5eed);
+        let mut keys: Vec<[u8; 32]> = (0..2000).map(|_| rng.key()).collect();
+        for zeros in 0..=32 {
+            let mut key = rng.key();
+            key[..zeros].fill(0);
+            keys.push(key);
+        }
+        keys.push([0xff; 32]);
+        for len in 40..=44 {
+            keys.push(key_from_digits(&format!("2{}", "1".repeat(len - 1))));
+            keys.push(key_from_digits(&"z".repeat(len - 1)));
+        }
+        keys
+    }
+
+    fn encode(key: &[u8; 32]) -> String {
+        let mut out = EncodedKey::default();
+        encode_32(key, &mut out);
+        out.as_str().to_string()
+    }
+
+    #[test]
+    fn encode_32_matches_reference() {
+        for key in sample_keys() {
+            let expected = bs58::encode(key).into_string();
+            assert_eq!(encode(&key), expected);
+            assert_eq!(Pubkey::new_from_array(key).to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn prefix_encoder_matches_full_encoding() {
+        let keys = sample_keys();
+        for len in 1..=MAX_ENCODED_LEN {
+            let encoder = PrefixEncoder::new(len);
+            let mut out = EncodedKey::default();
+            for key in &keys {
+                let full = encode(key);
+                encoder.encode(key, &mut out);
+                assert_eq!(out.as_str(), &full[..len.min(full.len())], "len {} key {:?}", len, key);
+            }
+        }
+    }
+
+    #[test]
+    fn prefix_intervals_are_exact() {
+        // 首字节非零的公钥：数值落在某个区间中当且仅当编码以该前缀开头
+        let keys: Vec<[u8; 32]> = sample_keys().into_iter().filter(|key| key[0] != 0).collect();
+        let mut rng = Rng(0xfee1);
+        for len in 1..=10 {
+            for key in keys.iter().step_by(50) {
+                let own = encode(key)[..len].to_string();
+                let other: String = (0..len).map(|_| ALPHABET[(rng.next_u64() % 57 + 1) as usize] as char).collect();
+                for prefix in [own, other] {
+                    let value = U320::from_be_bytes32(key);
+                    let intervals = prefix_intervals(prefix.as_bytes(), U320::pow2(248), U320::pow2(256).sub_one());
+                    let inside = intervals.iter().any(|&(lo, hi)| lo <= value && value <= hi);
+                    assert_eq!(inside, encode(key).starts_with(&prefix), "prefix {} key {:?}", prefix, key);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn prefix_top_ranges_cover_matching_keys() {
+        for key in sample_keys() {
+            let full = encode(&key);
+            let top = u64::from_be_bytes(key[..8].try_into().unwrap());
+            for len in 1..=10.min(full.len()) {
+                let ranges = prefix_top_ranges(&full[..len]);
+                assert!(ranges.iter().any(|&(lo, hi)| lo <= top && top <= hi), "prefix {} key {:?}", &full[..len], key);
+            }
+        }
+        assert!(prefix_top_ranges("0").is_empty());
+    }
+
+    #[test]
+    fn first_character_probabilities_sum_to_one() {
+        let total: f64 = ALPHABET.iter().map(|&c| prefix_probability(&(c as char).to_string())).sum();
+        assert!((total - 1.0).abs() < 1e-12, "{}", total);
+        assert_eq!(prefix_probability("1"), 1.0 / 256.0);
+    }
+
+    #[test]
+    fn u320_arithmetic() {
+        let mut rng = Rng(42);
+        for _ in 0..1000 {
+            let (a, b) = (rng.next_u64(), rng.next_u64());
+            let product = mul_u320(U320::from_u64(a), U320::from_u64(b));
+            assert_eq!(product.low_u128(), a as u128 * b as u128);
+            assert_eq!(sub_u320(product, U320::from_u64(a)).low_u128(), a as u128 * b as u128 - a as u128);
+            assert_eq!(U320::from_u64(a).mul_small(b).low_u128(), a as u128 * b as u128);
+        }
+        assert_eq!(U320::pow58(44), (0..44).fold(U320::from_u64(1), |acc, _| acc.mul_small(58)));
+        assert_eq!(U320::pow2(256).sub_one().bits(), 256);
+        assert_eq!(U320::from_be_bytes32(&[0xff; 32]).add_small(1), U320::pow2(256));
+    }
+}
@@ -0,0 +1,127 @@
+use crate::error::GrindError;
+use crate::grinder::{self, GrindConfig, GrindSummary};
+use crate::target::{self, Target};
+use chrono::{Local, SecondsFormat};
+use serde::Deserialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::time::Instant;
+
+/// 阶段切换记录追加写入的文件
+pub const CAMPAIGN_LOG: &str = "campaign.log";
+
+/// 按顺序执行的一组搜索阶段，前一阶段的目标全部找到后自动开始下一阶段
+#[derive(Debug, Clone, Deserialize)]
+pub struct Campaign {
+    pub stages: Vec<Stage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Stage {
+    #[serde(default)]
+    pub name: Option<String>,
+    /// 覆盖命令行指定的线程数
+    #[serde(default)]
+    pub threads: Option<usize>,
+    /// 覆盖命令行的 --ignore-case
+    #[serde(default)]
+    pub ignore_case: Option<bool>,
+    pub targets: Vec<StageTarget>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StageTarget {
+    /// 目标的显示形式（[`crate::target::Target`] 的 `Display`），用 [`crate::target::parse_label`] 还原
+    pub pattern: String,
+    #[serde(default = "default_count")]
+    pub count: u64,
+}
+
+fn default_count() -> u64 {
+    1
+}
+
+/// 一个阶段完成后的记录
+#[derive(Debug, Clone)]
+pub struct StageReport {
+    pub name: String,
+    pub elapsed_secs: f64,
+    pub summary: GrindSummary,
+}
+
+impl Campaign {
+    /// 读取 JSON 格式的阶段定义，并校验所有目标
+    pub fn load(path: &Path) -> Result<Self, GrindError> {
+        let text = std::fs::read_to_string(path)?;
+        let campaign: Campaign = serde_json::from_str(&text)
+            .map_err(|e| GrindError::Config(format!("无法解析阶段定义 {}: {}", path.display(), e)))?;
+        if campaign.stages.is_empty() {
+            return Err(GrindError::Config(format!("{} 中没有定义任何阶段", path.display())));
+        }
+        for (idx, stage) in campaign.stages.iter().enumerate() {
+            if stage.targets.is_empty() {
+                return Err(GrindError::Config(format!("第 {} 个阶段没有目标", idx + 1)));
+            }
+            stage.targets()?;
+        }
+        Ok(campaign)
+    }
+}
+
+impl Stage {
+    pub fn targets(&self) -> Result<Vec<Target>, GrindError> {
+        self.targets.iter()
+            .map(|t| {
+                if t.count == 0 {
+                    return Err(GrindError::Pattern(format!("目标 '{}' 的数量必须是正整数", t.pattern)));
+                }
+                Ok(Target { count: t.count, ..target::parse_label(&t.pattern)? })
+            })
+            .collect()
+    }
+}
+
+fn record(line: &str) -> Result<(), GrindError> {
+    let mut file = OpenOptions::new().create(true).append(true).open(CAMPAIGN_LOG)?;
+    writeln!(file, "[{}] {}", Local::now().to_rfc3339_opts(SecondsFormat::Secs, false), line)?;
+    println!("{}", line);
+    Ok(())
+}
+
+/// 依次运行所有阶段。`base` 提供各阶段未覆盖的设置（线程数、输出方式等），
+/// 阶段的开始和结束都会记录到 `campaign.log`。
+pub fn run_campaign(campaign: &Campaign, base: &GrindConfig) -> Result<Vec<StageReport>, GrindError> {
+    let total = campaign.stages.len();
+    let mut reports = Vec::with_capacity(total);
+    for (idx, stage) in campaign.stages.iter().enumerate() {
+        let name = stage.name.clone().unwrap_or_else(|| format!("阶段 {}", idx + 1));
+        let mut config = base.clone();
+        config.targets = stage.targets()?;
+        if let Some(threads) = stage.threads {
+            config.num_threads = threads;
+        }
+        if let Some(ignore_case) = stage.ignore_case {
+            config.ignore_case = ignore_case;
+        }
+        config.validate()?;
+
+        let labels: Vec<String> = config.targets.iter()
+            .map(|t| if t.count > 1 { format!("{} (x{})", t, t.count) } else { t.to_string() })
+            .collect();
+        record(&format!(
+            "[{}/{}] 开始 '{}'：{} 个线程，目标 {:?}",
+            idx + 1, total, name, config.num_threads, labels
+        ))?;
+
+        let started = Instant::now();
+        let summary = grinder::run(&config)?;
+        let elapsed_secs = started.elapsed().as_secs_f64();
+        record(&format!(
+            "[{}/{}] 完成 '{}'：共生成 {} 个密钥对，平均 {} 个/秒，用时 {:.1} 秒",
+            idx + 1, total, name, config.count_format.count(summary.attempts), config.count_format.rate(summary.keys_per_sec), elapsed_secs
+        ))?;
+        reports.push(StageReport { name, elapsed_secs, summary });
+    }
+    Ok(reports)
+}
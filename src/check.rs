@@ -0,0 +1,132 @@
+use crate::error::GrindError;
+use crate::matcher::Matcher;
+use crate::net;
+use crate::status;
+use crate::target::{self, Target};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+
+/// `serve-check` 的默认监听地址
+pub const DEFAULT_CHECK_ADDR: &str = "127.0.0.1:7879";
+
+/// 一次检查请求：公钥和一组目标（`abc`、`...xyz`、`abc...xyz` 形式，与 `query --pattern` 相同）
+#[derive(Debug, Clone, Deserialize)]
+pub struct CheckRequest {
+    pub pubkey: String,
+    pub patterns: Vec<String>,
+    #[serde(default)]
+    pub ignore_case: bool,
+}
+
+/// 检查结果：`matches` 列出命中的目标及命中位置；请求不合法时 `matched` 为 false 并带有 `error`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CheckResponse {
+    pub pubkey: String,
+    pub matched: bool,
+    pub matches: Vec<CheckMatch>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckMatch {
+    pub pattern: String,
+    /// `prefix`、`suffix`、`prefix_suffix`、`contains` 或 `regex`
+    pub match_type: String,
+    /// 前缀在公钥中占据的字符区间 `[start, end)`；没有前缀时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix_span: Option<(usize, usize)>,
+    /// 后缀在公钥中占据的字符区间 `[start, end)`；没有后缀时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suffix_span: Option<(usize, usize)>,
+    /// 包含的字符串在公钥中占据的字符区间 `[start, end)`；不是包含目标时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contains_span: Option<(usize, usize)>,
+}
+
+/// 用与搜索相同的目标解析和匹配器检查一个公钥，外部生成的地址因此与本工具的结果语义一致
+pub fn check(request: &CheckRequest) -> Result<Vec<CheckMatch>, GrindError> {
+    let decoded = bs58::decode(&request.pubkey).into_vec()
+        .map_err(|e| GrindError::Pattern(format!("公钥 '{}' 不是合法的 base58: {}", request.pubkey, e)))?;
+    if decoded.len() != 32 {
+        return Err(GrindError::Pattern(format!("公钥 '{}' 解码后为 {} 字节，应为 32 字节", request.pubkey, decoded.len())));
+    }
+    if request.patterns.is_empty() {
+        return Err(GrindError::Pattern("至少需要指定一个目标".to_string()));
+    }
+    let mut targets: Vec<Target> = request.patterns.iter()
+        .map(|p| target::parse_label(p).and_then(|t| t.check_alphabet(request.ignore_case).map(|_| t)))
+        .collect::<Result<_, _>>()?;
+    // 与 --ignore-case 一致：目标和公钥都转为小写后比较
+    let candidate = if request.ignore_case {
+        targets = targets.iter().map(Target::to_lowercase).collect();
+        request.pubkey.to_lowercase()
+    } else {
+        request.pubkey.clone()
+    };
+
+    let len = candidate.len();
+    let matcher = Matcher::new(&targets, request.ignore_case);
+    Ok(matcher.find(&candidate).into_iter()
+        .map(|idx| {
+            let target = &targets[idx];
+            CheckMatch {
+                pattern: request.patterns[idx].clone(),
+                match_type: target.match_type().to_string(),
+                prefix_span: (!target.prefix.is_empty()).then_some((0, target.prefix.len())),
+                suffix_span: (!target.suffix.is_empty()).then(|| (len - target.suffix.len(), len)),
+                contains_span: target.match_position(&candidate)
+                    .filter(|_| !target.contains.is_empty())
+                    .map(|start| (start, start + target.contains.len())),
+            }
+        })
+        .collect())
+}
+
+/// 处理一行 JSON 请求，返回一行 JSON 响应；解析或检查失败时响应中带有 `error`
+pub fn handle_line(line: &str) -> String {
+    let response = match serde_json::from_str::<CheckRequest>(line) {
+        Ok(request) => match check(&request) {
+            Ok(matches) => CheckResponse { pubkey: request.pubkey, matched: !matches.is_empty(), matches, error: None },
+            Err(e) => CheckResponse { pubkey: request.pubkey, error: Some(e.to_string()), ..Default::default() },
+        },
+        Err(e) => CheckResponse { error: Some(format!("无法解析请求: {}", e)), ..Default::default() },
+    };
+    serde_json::to_string(&response).expect("CheckResponse 序列化不会失败")
+}
+
+/// 在 `addr` 上长期提供检查服务：每个连接可以发送任意多行 JSON 请求，每行得到一行 JSON 响应。
+/// `allow_remote` 为假时只监听本机回环地址
+pub fn serve(addr: &str, allow_remote: bool) -> Result<(), GrindError> {
+    let listener = net::listen(addr, allow_remote, "--allow-remote")?;
+    status!("检查服务: {}（每行一个 JSON 请求，Ctrl+C 退出）", listener.local_addr()?);
+
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                thread::spawn(move || answer_requests(stream));
+            }
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => {
+                eprintln!("警告: 检查服务接受连接失败: {}", e);
+                thread::sleep(Duration::from_millis(100));
+            }
+        }
+    }
+}
+
+fn answer_requests(stream: TcpStream) {
+    let Ok(mut writer) = stream.try_clone() else { return };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if writeln!(writer, "{}", handle_line(&line)).is_err() {
+            break;
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use crate::error::GrindError;
+use chrono::{DateTime, Local, TimeZone};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// 搜索中的时间来源：工作线程的日志时间戳和批次计时、速度和停滞检测、截止时间的解析和预测、统计快照、
+/// ChaCha20 按时间重新播种和模拟后端的限速都从这里读取时间。
+/// 测试时可以换成手动推进的 [`MockClock`]，让依赖时间的功能得到确定的结果；
+/// 生成速度很高时可以换成 [`CoarseClock`]，降低每个密钥读一次时间的开销
+pub trait Clock: Send + Sync + fmt::Debug {
+    /// 单调时间，用于计时和计算速度
+    fn now(&self) -> Instant;
+    /// 墙上时间，Unix 毫秒时间戳
+    fn unix_millis(&self) -> i64;
+    /// 显示在配置转储中的名称
+    fn name(&self) -> &'static str;
+
+    /// 墙上时间对应的本地时间
+    fn local(&self) -> DateTime<Local> {
+        Local.timestamp_millis_opt(self.unix_millis()).single().unwrap_or_else(Local::now)
+    }
+}
+
+/// 工作线程、写入线程和监控线程共享的时间来源
+pub type SharedClock = Arc<dyn Clock>;
+
+/// 标准库的 `Instant` 和 `SystemTime`（默认）
+pub fn system() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+/// 命令行 `--clock` 可选的时间来源；[`MockClock`] 只供嵌入使用
+pub fn parse(value: &str) -> Result<SharedClock, GrindError> {
+    match value {
+        "system" => Ok(system()),
+        "coarse" => Ok(Arc::new(CoarseClock::new())),
+        _ => Err(GrindError::Config(format!("不支持的时间来源 '{}'（可选: system, coarse）", value))),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn unix_millis(&self) -> i64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as i64)
+    }
+
+    fn name(&self) -> &'static str {
+        "system"
+    }
+}
+
+/// Linux 上的 `CLOCK_MONOTONIC_COARSE` 和 `CLOCK_REALTIME_COARSE`：读取时不进入内核也不读硬件计时器，
+/// 精度为一个时钟节拍（通常 1–4 毫秒），对日志时间戳和批次计时已经足够。其他平台等同于 [`SystemClock`]
+#[derive(Debug, Clone, Copy)]
+pub struct CoarseClock {
+    #[cfg(target_os = "linux")]
+    origin: Instant,
+    #[cfg(target_os = "linux")]
+    origin_coarse: Duration,
+}
+
+impl CoarseClock {
+    pub fn new() -> Self {
+        CoarseClock {
+            #[cfg(target_os = "linux")]
+            origin: Instant::now(),
+            #[cfg(target_os = "linux")]
+            origin_coarse: read_clock(libc::CLOCK_MONOTONIC_COARSE),
+        }
+    }
+}
+
+impl Default for CoarseClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_clock(id: libc::clockid_t) -> Duration {
+    let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    // SAFETY: clock_gettime 只写入传入的 timespec；粗粒度时钟在 Linux 2.6.32 之后总是可用
+    unsafe { libc::clock_gettime(id, &mut ts) };
+    Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+}
+
+impl Clock for CoarseClock {
+    #[cfg(target_os = "linux")]
+    fn now(&self) -> Instant {
+        // 粗粒度的单调时间换算到创建时的 Instant 上，与其他 Instant 一样可以相减
+        self.origin + read_clock(libc::CLOCK_MONOTONIC_COARSE).saturating_sub(self.origin_coarse)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    #[cfg(target_os = "linux")]
+    fn unix_millis(&self) -> i64 {
+        read_clock(libc::CLOCK_REALTIME_COARSE).as_millis() as i64
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn unix_millis(&self) -> i64 {
+        SystemClock.unix_millis()
+    }
+
+    fn name(&self) -> &'static str {
+        "coarse"
+    }
+}
+
+/// 只在调用 [`MockClock::advance`] 时前进的时间，单调时间和墙上时间同步推进。
+/// 监控线程仍按真实时间轮询，读到的时间则完全由调用方控制
+#[derive(Debug)]
+pub struct MockClock {
+    origin: Instant,
+    unix_origin_ms: i64,
+    elapsed_nanos: AtomicU64,
+}
+
+impl MockClock {
+    /// 墙上时间从 `unix_millis` 开始
+    pub fn new(unix_millis: i64) -> Self {
+        MockClock { origin: Instant::now(), unix_origin_ms: unix_millis, elapsed_nanos: AtomicU64::new(0) }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.elapsed_nanos.fetch_add(by.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos(self.elapsed_nanos.load(Ordering::Relaxed))
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.origin + self.elapsed()
+    }
+
+    fn unix_millis(&self) -> i64 {
+        self.unix_origin_ms + self.elapsed().as_millis() as i64
+    }
+
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+}
@@ -0,0 +1,116 @@
+use crate::error::GrindError;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const MAGIC: &[u8; 8] = b"VGCIDX01";
+// 每个公钥占 64 位、16 个哈希位置：在设计容量内误报率约 3e-11，
+// 这样命中几乎一定意味着真的重复，而不是布隆过滤器的误报
+const BITS_PER_KEY: u64 = 64;
+const HASHES: u64 = 16;
+
+/// 默认可容纳的公钥数量（占用约 80 MB）
+pub const DEFAULT_CAPACITY: u64 = 10_000_000;
+
+/// 记录所有已生成公钥的布隆过滤器，可持久化到磁盘，跨多次运行检测重复公钥。
+/// 位数组使用原子操作，工作线程可以并发插入。
+pub struct CollisionIndex {
+    bits: Vec<AtomicU64>,
+    mask: u64,
+    inserted: AtomicU64,
+    suspected: AtomicU64,
+}
+
+impl CollisionIndex {
+    pub fn new(capacity: u64) -> Self {
+        let num_bits = (capacity.max(1) * BITS_PER_KEY).next_power_of_two();
+        CollisionIndex {
+            bits: (0..num_bits / 64).map(|_| AtomicU64::new(0)).collect(),
+            mask: num_bits - 1,
+            inserted: AtomicU64::new(0),
+            suspected: AtomicU64::new(0),
+        }
+    }
+
+    /// 读取已有的索引文件；文件不存在时按给定容量新建
+    pub fn load_or_create(path: &Path, capacity: u64) -> Result<Self, GrindError> {
+        if !path.exists() {
+            return Ok(Self::new(capacity));
+        }
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut header = [0u8; 24];
+        reader.read_exact(&mut header)?;
+        if &header[..8] != MAGIC {
+            return Err(GrindError::Config(format!("{} 不是有效的碰撞索引文件", path.display())));
+        }
+        let num_bits = u64::from_le_bytes(header[8..16].try_into().unwrap());
+        let inserted = u64::from_le_bytes(header[16..24].try_into().unwrap());
+        if !num_bits.is_power_of_two() || num_bits < 64 {
+            return Err(GrindError::Config(format!("{} 中的位数组大小无效", path.display())));
+        }
+        let mut bits = Vec::with_capacity((num_bits / 64) as usize);
+        let mut word = [0u8; 8];
+        for _ in 0..num_bits / 64 {
+            reader.read_exact(&mut word)?;
+            bits.push(AtomicU64::new(u64::from_le_bytes(word)));
+        }
+        Ok(CollisionIndex {
+            bits,
+            mask: num_bits - 1,
+            inserted: AtomicU64::new(inserted),
+            suspected: AtomicU64::new(0),
+        })
+    }
+
+    /// 写入临时文件后再替换，避免中途崩溃留下损坏的索引
+    pub fn save(&self, path: &Path) -> Result<(), GrindError> {
+        let tmp = path.with_extension("tmp");
+        {
+            let mut writer = BufWriter::new(File::create(&tmp)?);
+            writer.write_all(MAGIC)?;
+            writer.write_all(&(self.mask + 1).to_le_bytes())?;
+            writer.write_all(&self.inserted().to_le_bytes())?;
+            for word in &self.bits {
+                writer.write_all(&word.load(Ordering::Relaxed).to_le_bytes())?;
+            }
+            writer.flush()?;
+        }
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// 插入公钥；如果它的所有位此前都已置位（疑似重复）则返回 true
+    pub fn check_and_insert(&self, public_key: &[u8; 32]) -> bool {
+        // 公钥本身是均匀随机的，直接取其中的字节作为两个哈希值
+        let h1 = u64::from_le_bytes(public_key[8..16].try_into().unwrap());
+        let h2 = u64::from_le_bytes(public_key[16..24].try_into().unwrap()) | 1;
+        let mut all_set = true;
+        for i in 0..HASHES {
+            let bit = h1.wrapping_add(i.wrapping_mul(h2)) & self.mask;
+            let mask = 1u64 << (bit % 64);
+            let previous = self.bits[(bit / 64) as usize].fetch_or(mask, Ordering::Relaxed);
+            all_set &= previous & mask != 0;
+        }
+        self.inserted.fetch_add(1, Ordering::Relaxed);
+        if all_set {
+            self.suspected.fetch_add(1, Ordering::Relaxed);
+        }
+        all_set
+    }
+
+    /// 累计插入过的公钥数量（包括以前的运行）
+    pub fn inserted(&self) -> u64 {
+        self.inserted.load(Ordering::Relaxed)
+    }
+
+    /// 本次运行中发现的疑似重复数量
+    pub fn suspected(&self) -> u64 {
+        self.suspected.load(Ordering::Relaxed)
+    }
+
+    /// 设计容量；超出后误报率会明显上升
+    pub fn capacity(&self) -> u64 {
+        (self.mask + 1) / BITS_PER_KEY
+    }
+}
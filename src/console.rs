@@ -0,0 +1,68 @@
+//! 控制台输出的分流和平台差异。周期性的进度（[`progress!`](crate::progress)）总是写到标准错误，
+//! 重定向标准输出时只得到启动信息、找到的结果和汇总；结果镜像到标准输出时（`--results-to-stdout`），
+//! 标准输出只留给结果行，其余提示也改写到标准错误，管道下游读到的都是可以解析的结果
+
+use std::env;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static STATUS_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// 之后的提示信息（[`status!`](crate::status)）写到标准错误
+pub fn set_status_to_stderr(enabled: bool) {
+    STATUS_TO_STDERR.store(enabled, Ordering::Relaxed);
+}
+
+pub fn status_to_stderr() -> bool {
+    STATUS_TO_STDERR.load(Ordering::Relaxed)
+}
+
+/// 控制台能否正常显示 emoji。Windows 上只有 Windows Terminal、VS Code、ConEmu 等终端可以，
+/// 传统的控制台窗口（conhost）会显示成方框，此时应自动改用 `--plain` 的输出；其他平台总是可以。
+/// 中文本身不受影响：标准库在 Windows 控制台上按 UTF-16 输出，与代码页无关
+pub fn supports_emoji() -> bool {
+    if cfg!(windows) {
+        ["WT_SESSION", "TERM_PROGRAM", "ConEmuANSI"].iter().any(|var| env::var_os(var).is_some())
+    } else {
+        true
+    }
+}
+
+/// 调试构建（未开启优化）比发布构建慢的大致倍数；单线程实测约 8 倍，随 CPU 和运行模式不同
+pub const DEBUG_SLOWDOWN: &str = "5 到 10 倍";
+
+/// 程序是否为调试构建，即编译时没有加 `--release`
+pub fn debug_build() -> bool {
+    cfg!(debug_assertions)
+}
+
+/// 调试构建时在标准错误输出醒目的警告，说明会慢多少以及如何重新编译
+pub fn warn_debug_build() {
+    if !debug_build() {
+        return;
+    }
+    let banner = "!".repeat(72);
+    eprintln!("{}", banner);
+    eprintln!("警告: 当前程序是调试构建（未开启优化），生成速度通常比发布构建慢 {}，", DEBUG_SLOWDOWN);
+    eprintln!("同样的目标要多花这么多倍的时间。请用 cargo run --release 或 cargo build --release 重新编译");
+    eprintln!("{}\n", banner);
+}
+
+/// 输出一行进度信息（生成数量、速度、日志轮转），总是写到标准错误
+#[macro_export]
+macro_rules! progress {
+    ($($arg:tt)*) => {
+        eprintln!($($arg)*)
+    };
+}
+
+/// 输出一行提示信息：平时与 `println!` 相同，结果镜像到标准输出时改用 `eprintln!`
+#[macro_export]
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if $crate::console::status_to_stderr() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
@@ -0,0 +1,179 @@
+//! `--debug-dump` 的内部状态转储：运行中收到请求时把配置、编译后的目标集合、计数器、队列深度、
+//! 写入线程和工作线程的状态写成一个 JSON 文件，附在停滞或计数不对的问题报告中。
+//! 转储中不含任何私钥、加密口令和钩子命令，可以直接公开
+
+use crate::backend::RngPolicy;
+use crate::grinder::{Attribution, GrindConfig, OnComplete, ResultFormat};
+use crate::matcher::MatcherLayout;
+use crate::policy::SecretPolicy;
+use chrono::Local;
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 输出目录中出现这个文件时写出一次转储（随后删除该文件）
+pub const DUMP_REQUEST: &str = "debug_dump.request";
+
+/// 一次转储的全部内容
+#[derive(Debug, Serialize)]
+pub struct DebugDump {
+    pub timestamp: String,
+    pub run_id: String,
+    pub version: String,
+    pub elapsed_secs: f64,
+    pub config: ConfigDump,
+    pub patterns: Vec<PatternDump>,
+    /// 当前使用的编译后目标集合，以及它被重新编译的次数
+    pub matcher: MatcherLayout,
+    pub matcher_version: u64,
+    pub counters: CounterDump,
+    pub log_queue: LogQueueDump,
+    pub results: ResultsDump,
+    pub workers: Vec<WorkerDump>,
+}
+
+/// 配置中与排查有关的部分；口令只记录是否设置，钩子只记录数量
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigDump {
+    pub threads: usize,
+    pub ignore_case: bool,
+    pub split_output: bool,
+    pub secrets_encrypted: bool,
+    pub secret_policy: Option<SecretPolicy>,
+    pub write_keypair_files: bool,
+    pub hooks: usize,
+    pub session_log: bool,
+    pub raw_speed: bool,
+    pub low_memory: bool,
+    pub shard_logs: bool,
+    pub log_sink: &'static str,
+    pub io_priority: &'static str,
+    pub result_format: ResultFormat,
+    pub attribution: Attribution,
+    pub backend: String,
+    pub clock: &'static str,
+    pub rng: RngPolicy,
+    pub simulation: bool,
+    pub output_dir: PathBuf,
+    pub state_file: Option<PathBuf>,
+    pub dedup_db: Option<PathBuf>,
+    pub collision_index: Option<PathBuf>,
+    pub stats_addr: Option<String>,
+    pub progress_file: Option<PathBuf>,
+    pub rate_window_secs: Option<f64>,
+    pub shutdown_grace_secs: f64,
+    pub max_result_delay_ms: u128,
+    pub keep_counting: Option<u64>,
+    pub max_attempts: Option<u64>,
+    pub max_time_secs: Option<u64>,
+    pub on_complete: OnComplete,
+    pub milestone_every: Option<u64>,
+}
+
+impl ConfigDump {
+    pub fn new(config: &GrindConfig) -> Self {
+        let backend = (config.backend)(0);
+        ConfigDump {
+            threads: config.num_threads,
+            ignore_case: config.ignore_case,
+            split_output: config.split_output,
+            secrets_encrypted: config.secret_passphrase.is_some(),
+            secret_policy: config.secret_policy,
+            write_keypair_files: config.write_keypair_files,
+            hooks: config.hooks.len(),
+            session_log: config.session_log,
+            raw_speed: config.raw_speed,
+            low_memory: config.low_memory,
+            shard_logs: config.shard_logs,
+            log_sink: config.log_sink.name(),
+            io_priority: config.io_priority.name(),
+            result_format: config.result_format,
+            attribution: config.attribution,
+            backend: backend.name().to_string(),
+            clock: config.clock.name(),
+            rng: backend.rng_policy(),
+            simulation: config.simulation,
+            output_dir: config.output_dir.clone(),
+            state_file: config.state_file.clone(),
+            dedup_db: config.dedup_db.clone(),
+            collision_index: config.collision_index.clone(),
+            stats_addr: config.stats_addr.clone(),
+            progress_file: config.progress_file.clone(),
+            rate_window_secs: config.rate_window.map(|w| w.as_secs_f64()),
+            shutdown_grace_secs: config.shutdown_grace.as_secs_f64(),
+            max_result_delay_ms: config.max_result_delay.as_millis(),
+            keep_counting: config.keep_counting,
+            max_attempts: config.max_attempts,
+            max_time_secs: config.max_time.map(|t| t.as_secs()),
+            on_complete: config.on_complete,
+            milestone_every: config.milestone_every,
+        }
+    }
+}
+
+/// 一个目标的进度（下标与 `matcher.active` 中的一致）
+#[derive(Debug, Clone, Serialize)]
+pub struct PatternDump {
+    pub index: usize,
+    pub pattern: String,
+    pub found: u64,
+    pub count: u64,
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CounterDump {
+    /// 全局序号计数器
+    pub attempts: u64,
+    /// 各工作线程自己数的生成数量之和；与 `attempts` 的差应当只是继续前的数量和正在处理的批次
+    pub worker_attempts: u64,
+    pub resumed_attempts: u64,
+    pub duplicate_results: u64,
+    pub paused: bool,
+    /// 所有目标都已找到，按 `--on-complete idle` 等待追加目标
+    pub idle: bool,
+    pub suspended: bool,
+    pub all_found: bool,
+}
+
+/// 常规日志的共享队列和写入线程
+#[derive(Debug, Clone, Serialize)]
+pub struct LogQueueDump {
+    pub depth: usize,
+    pub capacity: usize,
+    /// 已写入并 flush 的常规日志条数
+    pub written: u64,
+    pub closed: bool,
+    /// 写入线程已退出（正常结束、出错或不写常规日志）
+    pub writer_exited: bool,
+    /// 停止宽限期已过，写入线程不再取新的消息
+    pub abandoned: bool,
+}
+
+/// 结果 channel 和结果写入线程
+#[derive(Debug, Clone, Serialize)]
+pub struct ResultsDump {
+    pub channel_bound: usize,
+    /// 已发送但结果写入线程尚未处理完的结果数
+    pub pending: u64,
+    /// 已计入各目标的结果数之和
+    pub written: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerDump {
+    pub thread: usize,
+    pub generated: u64,
+    pub batch_size: usize,
+}
+
+/// 写出转储：先写临时文件再改名，返回文件路径
+pub fn write(dir: &Path, dump: &DebugDump) -> io::Result<PathBuf> {
+    let path = dir.join(format!("debug_dump_{}.json", Local::now().format("%Y%m%dT%H%M%S%.3f")));
+    let tmp = path.with_extension("json.tmp");
+    let json = serde_json::to_string_pretty(dump).map_err(io::Error::other)?;
+    fs::write(&tmp, json + "\n")?;
+    fs::rename(&tmp, &path)?;
+    Ok(path)
+}
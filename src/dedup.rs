@@ -0,0 +1,90 @@
+use crate::error::GrindError;
+use crate::grinder::ResultFormat;
+use crate::watch;
+use rusqlite::{params, Connection, Transaction, TransactionBehavior};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::time::Duration;
+
+/// 已记录结果数据库的默认文件名（位于输出目录）
+pub const DEDUP_DB: &str = "recorded_results.db";
+
+// 多个进程共用同一个数据库时，等待其他进程的写事务结束的最长时间
+const BUSY_TIMEOUT: Duration = Duration::from_secs(30);
+
+// 各版本的数据库结构变更，按顺序执行；已执行到的版本记录在 SQLite 的 user_version 中
+const MIGRATIONS: &[&str] = &["
+CREATE TABLE IF NOT EXISTS recorded (
+    pattern TEXT NOT NULL,
+    public_key TEXT NOT NULL,
+    run_id TEXT,
+    recorded_at TEXT NOT NULL,
+    PRIMARY KEY (pattern, public_key)
+);
+"];
+
+/// 已写入结果文件的（目标，公钥）组合，保存在 SQLite 中，跨重启和多个进程共享。
+/// 写结果前先在写事务中登记，结果写完后才提交：登记过的组合不会再写入，
+/// 写入中途崩溃时登记随事务回滚，不会把没写出的结果当作已记录
+pub struct RecordedResults {
+    conn: Connection,
+}
+
+impl RecordedResults {
+    /// 打开（不存在时创建）数据库。新建时先登记 `seed_dir` 中已有结果文件里的结果，
+    /// 使启用去重之前写出的结果同样不会重复
+    pub fn open(path: &Path, seed_dir: &Path) -> Result<Self, GrindError> {
+        let mut conn = Connection::open(path)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        // 建表和登记已有结果在同一个写事务中完成，同时启动的其他进程会等它结束
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let version: usize = tx.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version > MIGRATIONS.len() {
+            return Err(GrindError::Config(format!(
+                "已记录结果数据库 {} 的版本为 {}，当前只支持到版本 {}",
+                path.display(), version, MIGRATIONS.len()
+            )));
+        }
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", index + 1)?;
+        }
+        if version == 0 {
+            seed(&tx, seed_dir)?;
+        }
+        tx.commit()?;
+        Ok(RecordedResults { conn })
+    }
+
+    /// 在写事务中登记一个组合；已经记录过时返回 `None`。
+    /// 返回的事务在结果写入文件后提交，提交前其他进程的登记会等待
+    pub(crate) fn claim(&mut self, pattern: &str, public_key: &str, run_id: &str, timestamp: &str) -> rusqlite::Result<Option<Transaction<'_>>> {
+        let tx = self.conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let inserted = tx.execute(
+            "INSERT OR IGNORE INTO recorded (pattern, public_key, run_id, recorded_at) VALUES (?1, ?2, ?3, ?4)",
+            params![pattern, public_key, run_id, timestamp],
+        )?;
+        Ok((inserted > 0).then_some(tx))
+    }
+}
+
+// 登记目录中已有结果文件（文本和 JSONL 格式）里的结果
+fn seed(tx: &Transaction<'_>, dir: &Path) -> Result<(), GrindError> {
+    let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    let mut insert = tx.prepare(
+        "INSERT OR IGNORE INTO recorded (pattern, public_key, run_id, recorded_at) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for format in [ResultFormat::Text, ResultFormat::Jsonl] {
+        let path = dir.join(format.file_name());
+        if !path.exists() {
+            continue;
+        }
+        for line in BufReader::new(File::open(&path)?).lines() {
+            if let Some(event) = watch::parse_result_entry(&line?) {
+                insert.execute(params![event.pattern, event.public_key, event.run_id, event.timestamp])?;
+            }
+        }
+    }
+    Ok(())
+}
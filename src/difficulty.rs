@@ -0,0 +1,208 @@
+use crate::base58;
+use crate::error::GrindError;
+use crate::target::{self, Target};
+use std::collections::BTreeMap;
+use std::fmt;
+
+// 同一个字符连续出现这么多次时提示
+const RUN_WARNING_LEN: usize = 4;
+// 精确概率与粗略估算相差这么多倍时提示
+const SKEW_WARNING_FACTOR: f64 = 2.0;
+// 忽略大小写或 leet 目标时逐一计算各种写法的字符数（前导 '1' 之外）；之后的字符在值域中均匀分布，按个数计算
+const CASE_EXACT_CHARS: usize = 6;
+
+/// 目标中值得提醒的统计特征
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatternWarning {
+    /// 前缀到第 `position` 个字符（从 1 计）为止已不可能出现在任何公钥中
+    Unreachable { position: usize, ch: char },
+    /// 开头连续 `count` 个 '1'：每个代表一个值为 0 的字节，概率是 1/256 而不是 1/58
+    LeadingOnes { count: usize },
+    /// 从第 `position` 个字符（从 1 计）起同一个字符连续出现 `len` 次
+    Run { part: &'static str, position: usize, ch: char, len: usize },
+    /// 精确概率与按每个字符 1/58 的粗略估算之比
+    Skewed { ratio: f64 },
+    /// 正则在只含 base58 字符的公钥中不可能匹配
+    RegexUnreachable,
+}
+
+impl fmt::Display for PatternWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternWarning::Unreachable { position, ch } => write!(
+                f, "前缀第 {} 个字符 '{}' 不可能出现：32 字节公钥的编码在这个位置取不到该字符，搜索永远不会结束",
+                position, ch
+            ),
+            PatternWarning::LeadingOnes { count } => write!(
+                f, "开头的 {} 个 '1' 各代表一个值为 0 的字节，每个的概率是 1/256 而不是 1/58",
+                count
+            ),
+            PatternWarning::Run { part, position, ch, len } => write!(
+                f, "{}从第 {} 个字符起连续 {} 个 '{}'：每个位置仍然独立，并不比其他同长度的目标容易",
+                part, position, len, ch
+            ),
+            PatternWarning::Skewed { ratio } if *ratio < 1.0 => write!(
+                f, "开头字符在编码中分布不均，实际比按每个字符 1/58 的估算难 {:.1} 倍",
+                1.0 / ratio
+            ),
+            PatternWarning::Skewed { ratio } => write!(
+                f, "开头字符在编码中分布不均，实际比按每个字符 1/58 的估算容易 {:.1} 倍",
+                ratio
+            ),
+            PatternWarning::RegexUnreachable => write!(f, "正则在只含 base58 字符的公钥中不可能匹配，搜索永远不会结束"),
+        }
+    }
+}
+
+/// 一个目标的命中概率和统计特征
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternAnalysis {
+    pub pattern: String,
+    /// 一个随机公钥满足该目标的概率：前缀按 32 字节公钥编码的实际值域精确计算，后缀按每个字符 1/58
+    /// （末尾字符是数值除以 58 的余数，与均匀分布的偏差小于 2^-190）
+    pub probability: f64,
+    /// 按每个字符 1/58 的粗略估算（[`Target::expected_attempts`] 的倒数）
+    pub estimated_probability: f64,
+    pub warnings: Vec<PatternWarning>,
+}
+
+impl PatternAnalysis {
+    /// 平均需要生成多少个密钥对才能命中一次；不可能命中时为无穷大
+    pub fn expected_attempts(&self) -> f64 {
+        1.0 / self.probability
+    }
+
+    /// 再命中 `remaining` 次需要生成的密钥对数的中位数（p50）：一次时为 ln 2 / p，
+    /// 多次时按伽马分布中位数的近似 (n - 1/3) / p；中位数比平均值更接近“一半的运行在这之前结束”
+    pub fn median_attempts(&self, remaining: u64) -> f64 {
+        match remaining {
+            0 => 0.0,
+            1 => std::f64::consts::LN_2 / self.probability,
+            n => (n as f64 - 1.0 / 3.0) / self.probability,
+        }
+    }
+
+    pub fn reachable(&self) -> bool {
+        self.probability > 0.0
+    }
+}
+
+/// 计算目标的精确命中概率，并检查不可达的字符、前导 '1'、长串重复字符和开头字符的分布偏差。
+/// 包含和正则目标的位置不固定，只有按每个字符 1/58 的估算，只检查正则是否可能匹配
+pub fn analyze(target: &Target, ignore_case: bool) -> PatternAnalysis {
+    if target.regex.is_some() || !target.contains.is_empty() {
+        let probability = 1.0 / target.expected_attempts(ignore_case);
+        let warnings = if probability == 0.0 { vec![PatternWarning::RegexUnreachable] } else { Vec::new() };
+        return PatternAnalysis { pattern: target.to_string(), probability, estimated_probability: probability, warnings };
+    }
+    let suffix_probability: f64 = target.suffix.bytes().map(|c| target::char_forms(c, ignore_case, target.leet).len() as f64 / 58.0).product();
+    let probability = prefix_probability(&target.prefix, ignore_case, target.leet) * suffix_probability;
+    let estimated_probability = 1.0 / target.expected_attempts(ignore_case);
+
+    let mut warnings = Vec::new();
+    if probability == 0.0 {
+        // 找出从哪个字符起前缀变得不可能
+        let position = (1..=target.prefix.len())
+            .find(|&len| prefix_probability(&target.prefix[..len], ignore_case, target.leet) == 0.0)
+            .unwrap_or(target.prefix.len());
+        let ch = target.prefix[position - 1..].chars().next().unwrap_or('?');
+        warnings.push(PatternWarning::Unreachable { position, ch });
+    }
+    let leading_ones = target.prefix.bytes().take_while(|&c| c == b'1').count();
+    if leading_ones > 0 {
+        warnings.push(PatternWarning::LeadingOnes { count: leading_ones });
+    }
+    for (part, text, skip) in [("前缀", &target.prefix, leading_ones), ("后缀", &target.suffix, 0)] {
+        for (position, ch, len) in runs(&text[skip..], ignore_case) {
+            if len >= RUN_WARNING_LEN {
+                warnings.push(PatternWarning::Run { part, position: position + skip + 1, ch, len });
+            }
+        }
+    }
+    if probability > 0.0 {
+        let ratio = probability / estimated_probability;
+        if !(1.0 / SKEW_WARNING_FACTOR..=SKEW_WARNING_FACTOR).contains(&ratio) && leading_ones == 0 {
+            warnings.push(PatternWarning::Skewed { ratio });
+        }
+    }
+
+    PatternAnalysis { pattern: target.to_string(), probability, estimated_probability, warnings }
+}
+
+/// 按目标长度（前缀、后缀和包含的字符数；正则目标按难度折算为同样难度的字符数）分组的难度统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct LengthBucket {
+    pub len: usize,
+    pub patterns: u64,
+    /// 找够各自数量平均需要的尝试次数，取组内平均
+    pub mean_attempts: f64,
+    /// 期望尝试次数超过 `infeasible_attempts`（或不可能命中）的目标数
+    pub infeasible: u64,
+}
+
+/// 把目标按长度分组，`analyses` 与 `targets` 一一对应。`infeasible_attempts` 为视为实际上找不到的期望尝试次数，
+/// 例如按当前速度一年能生成的数量；为空时只把不可能命中的目标算作找不到
+pub fn length_histogram(targets: &[Target], analyses: &[PatternAnalysis], infeasible_attempts: Option<f64>) -> Vec<LengthBucket> {
+    let mut buckets: BTreeMap<usize, LengthBucket> = BTreeMap::new();
+    for (target, analysis) in targets.iter().zip(analyses) {
+        let len = match &target.regex {
+            Some(_) if analysis.reachable() => analysis.expected_attempts().log(58.0).round() as usize,
+            Some(_) => 0,
+            None => target.prefix.len() + target.suffix.len() + target.contains.len(),
+        };
+        let attempts = target.count as f64 * analysis.expected_attempts();
+        let bucket = buckets.entry(len).or_insert(LengthBucket { len, patterns: 0, mean_attempts: 0.0, infeasible: 0 });
+        bucket.patterns += 1;
+        bucket.mean_attempts += attempts;
+        if !analysis.reachable() || infeasible_attempts.is_some_and(|limit| attempts > limit) {
+            bucket.infeasible += 1;
+        }
+    }
+    buckets.into_values()
+        .map(|bucket| LengthBucket { mean_attempts: bucket.mean_attempts / bucket.patterns as f64, ..bucket })
+        .collect()
+}
+
+/// 有目标不可能命中时返回错误：先检查字符是否都在 base58 字母表中，再说明是哪个位置的哪个字符取不到
+pub fn ensure_reachable(targets: &[Target], ignore_case: bool) -> Result<(), GrindError> {
+    for target in targets {
+        target.check_alphabet(ignore_case)?;
+        let analysis = analyze(target, ignore_case);
+        if let Some(warning) = analysis.warnings.iter().find(|w| matches!(w, PatternWarning::Unreachable { .. } | PatternWarning::RegexUnreachable)) {
+            return Err(GrindError::Pattern(format!("目标 '{}' {}", analysis.pattern, warning)));
+        }
+    }
+    Ok(())
+}
+
+// 忽略大小写（或 leet 目标）时把前缀的每种写法的精确概率相加；组合过多时只展开前面的字符
+fn prefix_probability(prefix: &str, ignore_case: bool, leet: bool) -> f64 {
+    let bytes = prefix.as_bytes();
+    let exact_len = if ignore_case || leet {
+        let leading_ones = bytes.iter().take_while(|&&c| c == b'1').count();
+        (leading_ones + CASE_EXACT_CHARS).min(bytes.len())
+    } else {
+        bytes.len()
+    };
+    let mut heads = vec![String::new()];
+    for &c in &bytes[..exact_len] {
+        heads = heads.iter()
+            .flat_map(|head| target::char_forms(c, ignore_case, leet).into_iter().map(move |f| format!("{}{}", head, f as char)))
+            .collect();
+    }
+    let tail: f64 = bytes[exact_len..].iter().map(|&c| target::char_forms(c, ignore_case, leet).len() as f64 / 58.0).product();
+    heads.iter().map(|head| base58::prefix_probability(head)).sum::<f64>() * tail
+}
+
+// 连续相同字符的串：(起始下标, 字符, 长度)
+fn runs(text: &str, ignore_case: bool) -> Vec<(usize, char, usize)> {
+    let fold = |c: u8| if ignore_case { c.to_ascii_lowercase() } else { c };
+    let mut runs: Vec<(usize, char, usize)> = Vec::new();
+    for (i, c) in text.bytes().enumerate() {
+        match runs.last_mut() {
+            Some((_, ch, len)) if fold(*ch as u8) == fold(c) => *len += 1,
+            _ => runs.push((i, c as char, 1)),
+        }
+    }
+    runs
+}
@@ -0,0 +1,33 @@
+use thiserror::Error;
+
+/// 库 API 返回的统一错误类型
+#[derive(Debug, Error)]
+pub enum GrindError {
+    /// 读写日志、结果或密钥文件失败
+    #[error("I/O 错误: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// 参数或配置不合法
+    #[error("{0}")]
+    Config(String),
+
+    /// 目标模式不合法（例如包含非 base58 字符）
+    #[error("{0}")]
+    Pattern(String),
+
+    /// 私钥加解密失败
+    #[error("{0}")]
+    Secret(String),
+
+    /// 访问 Solana RPC 节点失败
+    #[error("RPC 错误: {0}")]
+    Rpc(String),
+
+    /// 读写地址清单数据库失败
+    #[error("数据库错误: {0}")]
+    Database(#[from] rusqlite::Error),
+
+    /// 密钥生成后端（工作线程等）出错
+    #[error("后端错误: {0}")]
+    Backend(String),
+}
@@ -0,0 +1,112 @@
+use crate::backend::{self, KeygenBackend};
+use crate::difficulty;
+use crate::error::GrindError;
+use crate::target::Target;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 测速时每批生成的数量
+const MEASURE_BATCH: usize = 256;
+
+/// 实例类型目录中的一项：该机型上实测的速度，以及可选的每小时价格
+#[derive(Debug, Clone, Deserialize)]
+pub struct InstanceType {
+    pub keys_per_sec: f64,
+    #[serde(default)]
+    pub hourly_price: Option<f64>,
+}
+
+/// 实例类型目录：JSON 对象，键为实例类型名称，例如
+/// `{"c7i.16xlarge": {"keys_per_sec": 2100000, "hourly_price": 2.856}}`
+pub type Catalog = BTreeMap<String, InstanceType>;
+
+pub fn load_catalog(path: &Path) -> Result<Catalog, GrindError> {
+    let text = std::fs::read_to_string(path)?;
+    serde_json::from_str(&text)
+        .map_err(|e| GrindError::Config(format!("无法解析实例类型目录 {}: {}", path.display(), e)))
+}
+
+/// 在本机用 CPU 后端、`threads` 个线程测速 `duration`，返回总速度（个/秒）
+pub fn measure_rate(threads: usize, duration: Duration) -> f64 {
+    let generated = Arc::new(AtomicU64::new(0));
+    let stop = Arc::new(AtomicBool::new(false));
+    let factory = backend::cpu();
+    let started = Instant::now();
+    let handles: Vec<_> = (0..threads)
+        .map(|thread_id| {
+            let mut backend: Box<dyn KeygenBackend> = factory(thread_id);
+            let (generated, stop) = (Arc::clone(&generated), Arc::clone(&stop));
+            thread::spawn(move || {
+                let mut seeds = vec![[0u8; 32]; MEASURE_BATCH];
+                let mut public_keys = vec![[0u8; 32]; MEASURE_BATCH];
+                while !stop.load(Ordering::Relaxed) {
+                    backend.generate(&mut seeds, &mut public_keys);
+                    generated.fetch_add(MEASURE_BATCH as u64, Ordering::Relaxed);
+                }
+            })
+        })
+        .collect();
+    thread::sleep(duration);
+    stop.store(true, Ordering::Relaxed);
+    for handle in handles {
+        let _ = handle.join();
+    }
+    generated.load(Ordering::Relaxed) as f64 / started.elapsed().as_secs_f64()
+}
+
+/// 一个目标找够数量的期望工作量、耗时和费用
+#[derive(Debug, Clone)]
+pub struct PatternEstimate {
+    pub pattern: String,
+    pub count: u64,
+    /// 找够 `count` 个平均需要生成的密钥对数量
+    pub expected_attempts: f64,
+    pub expected_secs: f64,
+    /// 按每小时价格计算的期望费用；未给出价格时为空
+    pub expected_cost: Option<f64>,
+}
+
+/// 按 `keys_per_sec` 的速度（和可选的每小时价格）估算每个目标的期望耗时和费用，命中概率按
+/// [`difficulty::analyze`] 精确计算。所有目标在同一次搜索中同时匹配，整次搜索的耗时取决于最难的那个目标
+pub fn estimate(targets: &[Target], ignore_case: bool, keys_per_sec: f64, hourly_price: Option<f64>) -> Vec<PatternEstimate> {
+    targets.iter()
+        .map(|target| {
+            let expected_attempts = target.count as f64 * difficulty::analyze(target, ignore_case).expected_attempts();
+            let expected_secs = expected_attempts / keys_per_sec;
+            PatternEstimate {
+                pattern: target.to_string(),
+                count: target.count,
+                expected_attempts,
+                expected_secs,
+                expected_cost: hourly_price.map(|price| expected_secs / 3600.0 * price),
+            }
+        })
+        .collect()
+}
+
+/// `table` 列出的目标长度上限
+pub const TABLE_MAX_LEN: usize = 10;
+
+/// 难度表中的一行：长度为 `len` 的目标平均需要的尝试次数，按每个字符 1/58 粗略估算；
+/// 忽略大小写时假设每个字符都是大小写两种形式都在 base58 中的字母（每个字符 1/29）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TableRow {
+    pub len: usize,
+    pub exact_attempts: f64,
+    pub folded_attempts: f64,
+}
+
+/// 长度 1 到 `max_len` 的难度表
+pub fn length_table(max_len: usize) -> Vec<TableRow> {
+    (1..=max_len)
+        .map(|len| {
+            let target = Target::with_prefix(&"a".repeat(len));
+            TableRow { len, exact_attempts: target.expected_attempts(false), folded_attempts: target.expected_attempts(true) }
+        })
+        .collect()
+}
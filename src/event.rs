@@ -0,0 +1,283 @@
+use crate::backend::RngPolicy;
+use crate::target::Target;
+use chrono::{Local, SecondsFormat};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// `FoundEvent` 的当前结构版本。新增可选字段不改变版本号；
+/// 删除、重命名字段或改变字段含义时必须递增。
+pub const FOUND_EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// 找到匹配地址时产生的事件。结果文件的 JSON 格式、通知以及库回调都使用这一结构，
+/// 下游可以根据 `schema_version` 判断如何解析。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FoundEvent {
+    pub schema_version: u32,
+    /// RFC 3339 格式的本地时间（毫秒精度）
+    pub timestamp: String,
+    /// 该密钥对在本次运行中的全局序号
+    pub counter: u64,
+    /// 目标的显示形式（[`crate::target::Target`] 的 `Display`），例如 `abc`、`...xyz`、`abc...xyz`、`...mid...`、`/^So1[1-9]{2}/` 或 `leet:seek`
+    pub pattern: String,
+    /// `prefix`、`suffix`、`prefix_suffix`、`contains` 或 `regex`
+    pub match_type: String,
+    pub prefix: String,
+    pub suffix: String,
+    pub public_key: String,
+    /// 忽略大小写匹配时公钥中实际匹配的部分（保留大小写，例如目标 `seekr` 匹配到的 `SeekR`）；与目标写法相同时为空。
+    /// 正则目标总是记录正则匹配到的部分
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_text: Option<String>,
+    /// 包含和正则目标在公钥中匹配的字符位置（从 0 计）；前缀、后缀目标的位置是固定的，为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<usize>,
+    /// base58 编码的 64 字节私钥；不应暴露私钥的输出中为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_key: Option<String>,
+    /// 产生该结果的运行 ID；从旧格式日志转换而来的记录没有该字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    /// 该公钥满足的所有仍需结果的目标（包括按归属策略未计入的目标）；只满足一个目标时为空
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matched_patterns: Vec<String>,
+    /// 按 `--announce` 模板生成的提示文本（不含私钥），方便通知直接转发；没有设置模板时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub announcement: Option<String>,
+    /// 产生该结果的后端、工作线程和主机，以及写入前通过的复核；导入的结果和旧格式的记录没有该字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+}
+
+impl FoundEvent {
+    pub fn new(timestamp: &str, counter: u64, target: &Target, public_key: &str, secret_key: &str) -> Self {
+        FoundEvent {
+            schema_version: FOUND_EVENT_SCHEMA_VERSION,
+            timestamp: timestamp.to_string(),
+            counter,
+            pattern: target.to_string(),
+            match_type: target.match_type().to_string(),
+            prefix: target.prefix.clone(),
+            suffix: target.suffix.clone(),
+            public_key: public_key.to_string(),
+            matched_text: target.matched_text(public_key),
+            position: target.match_position(public_key),
+            secret_key: Some(secret_key.to_string()),
+            run_id: None,
+            matched_patterns: Vec::new(),
+            announcement: None,
+            provenance: None,
+        }
+    }
+
+    /// 去掉私钥后的副本，用于可公开的输出
+    pub fn without_secret(&self) -> Self {
+        FoundEvent {
+            secret_key: None,
+            ..self.clone()
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("FoundEvent 序列化不会失败")
+    }
+}
+
+/// 结果写入前通过的复核步骤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Check {
+    /// 从私钥重新推导出的公钥与结果一致
+    Keypair,
+    /// 用参考实现重新编码公钥后仍满足目标
+    Pattern,
+}
+
+impl Check {
+    pub fn name(self) -> &'static str {
+        match self {
+            Check::Keypair => "keypair",
+            Check::Pattern => "pattern",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "keypair" => Some(Check::Keypair),
+            "pattern" => Some(Check::Pattern),
+            _ => None,
+        }
+    }
+}
+
+/// 一个结果的来源。某个后端日后被发现有缺陷时，可据此找出受影响的密钥
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// 后端名称，例如 `cpu`、`simulate`
+    pub backend: String,
+    /// 工作线程编号
+    pub worker: usize,
+    /// 运行所在的主机名
+    pub host: String,
+    /// 私钥种子的随机数来源；从文本格式的结果还原时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rng: Option<RngPolicy>,
+    pub checks: Vec<Check>,
+}
+
+impl Provenance {
+    /// 从文本格式结果中的 `来源: 后端#线程@主机` 和 `复核: keypair, pattern` 还原
+    pub fn parse(source: &str, checks: Option<&str>) -> Option<Self> {
+        let (backend, rest) = source.split_once('#')?;
+        let (worker, host) = rest.split_once('@')?;
+        let checks = match checks {
+            Some(checks) => checks.split(", ").map(Check::parse).collect::<Option<Vec<_>>>()?,
+            None => Vec::new(),
+        };
+        Some(Provenance { backend: backend.to_string(), worker: worker.parse().ok()?, host: host.to_string(), rng: None, checks })
+    }
+
+    /// 复核步骤的名称，以 `, ` 分隔
+    pub fn checks_label(&self) -> String {
+        self.checks.iter().map(|check| check.name()).collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// `后端#线程@主机`，文本格式结果中 `来源` 字段的写法
+impl fmt::Display for Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}#{}@{}", self.backend, self.worker, self.host)
+    }
+}
+
+/// 可公开的结果列表文件，与结果文件位于同一目录
+pub const PUBLIC_FEED_FILE: &str = "found_public.jsonl";
+
+/// 公开结果列表中的一行：只有目标、公钥和时间，绝不包含私钥，可以放心分享、同步或由网页展示
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicEntry {
+    pub pattern: String,
+    pub public_key: String,
+    /// RFC 3339 格式的本地时间（毫秒精度）
+    pub timestamp: String,
+}
+
+impl PublicEntry {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("PublicEntry 序列化不会失败")
+    }
+}
+
+impl From<&FoundEvent> for PublicEntry {
+    fn from(event: &FoundEvent) -> Self {
+        PublicEntry {
+            pattern: event.pattern.clone(),
+            public_key: event.public_key.clone(),
+            timestamp: event.timestamp.clone(),
+        }
+    }
+}
+
+/// 一次运行中值得通知的事件。库调用方通过 `GrindConfig::on_event` 接收，
+/// 命令行按种类把它们发送到各自的 webhook；序列化时 `event` 字段给出种类
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum RunEvent {
+    /// 找到一个匹配的地址（通知中不含私钥）
+    Found(Box<FoundEvent>),
+    /// 所有目标都已找够（暂停或出错结束时不产生）
+    Complete {
+        timestamp: String,
+        run_id: String,
+        attempts: u64,
+        /// 共找到的结果数量
+        found: u64,
+    },
+    /// 生成数量越过 `milestone_every` 的整数倍
+    Milestone {
+        timestamp: String,
+        run_id: String,
+        attempts: u64,
+    },
+    /// 速度持续低于校准速度（例如写入线程卡住，反压拖慢了工作线程），附带队列深度供诊断
+    Stalled {
+        timestamp: String,
+        run_id: String,
+        /// 最近一个采样窗口的平均速度（个/秒）
+        keys_per_sec: f64,
+        /// 本次运行中观察到的最高窗口速度（个/秒）
+        calibrated_keys_per_sec: f64,
+        /// 速度低于阈值已持续的秒数
+        stalled_secs: u64,
+        /// 常规日志队列中等待写入的条数及队列容量
+        log_queue_depth: usize,
+        log_queue_capacity: usize,
+        /// 结果 channel 中等待写入的结果数
+        pending_results: u64,
+    },
+    /// 按当前速度预计某个目标赶不上它的截止时间（每个目标只报告一次，恢复后重新计算）
+    DeadlineAtRisk {
+        timestamp: String,
+        run_id: String,
+        pattern: String,
+        /// RFC 3339 格式的截止时间
+        deadline: String,
+        /// 按当前速度预计找够数量的时间（RFC 3339）
+        projected: String,
+        /// 最近一个检查间隔的平均速度（个/秒）
+        keys_per_sec: f64,
+        /// 在截止时间前找够所需的速度（个/秒）；截止时间已过时为空
+        required_keys_per_sec: Option<f64>,
+    },
+    /// 开始后测得的速度比 `speed_baseline` 慢了超过容许的比例（每次运行只检查一次）
+    BelowBaseline {
+        timestamp: String,
+        run_id: String,
+        /// 测量窗口内的平均速度（个/秒）
+        keys_per_sec: f64,
+        baseline_keys_per_sec: f64,
+        /// 容许比基线慢的比例
+        tolerance: f64,
+        /// 程序是否为调试构建（未使用 --release 编译）
+        debug_build: bool,
+    },
+    /// 找到结果后执行的钩子失败（启动失败、非零退出、超时或回调返回错误）
+    HookFailed {
+        timestamp: String,
+        run_id: String,
+        /// 钩子的命令或回调名称
+        hook: String,
+        public_key: String,
+        message: String,
+    },
+    /// 运行因错误中止
+    Error {
+        timestamp: String,
+        run_id: String,
+        message: String,
+    },
+}
+
+impl RunEvent {
+    /// 事件种类的名称，与序列化后的 `event` 字段相同
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RunEvent::Found(_) => "found",
+            RunEvent::Complete { .. } => "complete",
+            RunEvent::Milestone { .. } => "milestone",
+            RunEvent::Stalled { .. } => "stalled",
+            RunEvent::DeadlineAtRisk { .. } => "deadline_at_risk",
+            RunEvent::BelowBaseline { .. } => "below_baseline",
+            RunEvent::HookFailed { .. } => "hook_failed",
+            RunEvent::Error { .. } => "error",
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("RunEvent 序列化不会失败")
+    }
+}
+
+/// 非 `Found` 事件使用的时间戳：RFC 3339 格式的本地时间（毫秒精度）
+pub fn now_timestamp() -> String {
+    Local::now().to_rfc3339_opts(SecondsFormat::Millis, false)
+}
@@ -0,0 +1,2238 @@
+use crate::announce::{AnnounceTemplate, Announcement};
+use crate::backend::{self, BackendFactory};
+use crate::base58::{self, EncodedKey, PrefixEncoder};
+use crate::clock::{self, SharedClock};
+use crate::collision::{self, CollisionIndex};
+use crate::console;
+use crate::dedup::{self, RecordedResults};
+use crate::debug::{self, DebugDump};
+use crate::difficulty;
+use crate::error::GrindError;
+use crate::event::{self, Check, FoundEvent, Provenance, PublicEntry, RunEvent};
+use crate::hooks::{self, Hook, HookRunner};
+use crate::logsink::{self, LogOutput, LogSink};
+use crate::matcher::Matcher;
+use crate::net;
+use crate::policy::SecretPolicy;
+use crate::priority::{self, IoPriority};
+use crate::recovery;
+use crate::report;
+use crate::secrets::{self, SecretWriter};
+use crate::session::{self, SessionEvent, SessionLog};
+use crate::state::{self, ResumePoint, RunState, TargetState};
+use crate::{progress, status};
+use crate::stats::{self, RecentFinds, StatsAccess, StatsSnapshot, StatsSource};
+use crate::target::Target;
+use crate::units::{self, CountFormat, RateMeter};
+use crossbeam_queue::ArrayQueue;
+use chrono::{DateTime, FixedOffset, Local, SecondsFormat, TimeZone};
+use serde::{Deserialize, Serialize};
+use solana_sdk::signature::{Keypair, Signer};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Write, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock, mpsc};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const MAX_LINES_PER_FILE: u64 = 1_000_000;
+/// 常规日志文件名的前缀：`keypairs_0000.log`；`--shard-logs` 时每个线程为 `keypairs_t00_0000.log`
+pub const LOG_STEM: &str = "keypairs";
+// 常规日志环形队列的容量（预分配），写满时工作线程让出 CPU 等待写入线程消费
+const LOG_QUEUE_CAPACITY: usize = 1 << 16;
+// 结果 channel 的容量；结果很少，写入线程处理不过来时工作线程等待
+const RESULT_CHANNEL_BOUND: usize = 1024;
+// 低内存模式下的结果 channel 容量
+const LOW_MEMORY_RESULT_CHANNEL_BOUND: usize = 4;
+// 写入线程每批最多取出的消息数，每批只 flush 一次
+const LOG_DEQUEUE_BATCH: usize = 4096;
+/// 停止时默认的宽限期
+pub const DEFAULT_SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+// 宽限期过后，等待写入线程处理完手头这一批再退出的时间
+const ABANDON_WAIT: Duration = Duration::from_secs(1);
+// 轮询暂停请求文件的间隔
+const SUSPEND_POLL_INTERVAL: Duration = Duration::from_millis(500);
+// 通过 RunControl 暂停时，工作线程检查是否继续的间隔
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+// 检查是否越过里程碑的间隔
+const MILESTONE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+// 停滞检测采样计数器的间隔，以及计算窗口速度用的采样数
+const STALL_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+const STALL_RATE_WINDOW: usize = 10;
+// 按最近这段时间的速度预测各目标能否赶上截止时间
+const DEADLINE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+// 与速度基线比较时跳过开始的这段时间（线程启动、缓存预热），再按之后一个窗口的平均速度比较
+const BASELINE_WARMUP: Duration = Duration::from_secs(5);
+const BASELINE_WINDOW: Duration = Duration::from_secs(15);
+/// 速度基线默认容许慢的比例
+pub const DEFAULT_BASELINE_TOLERANCE: f64 = 0.25;
+// 设置了速度平滑窗口时输出进度行的间隔
+const TICKER_INTERVAL: Duration = Duration::from_secs(1);
+// 工作线程每批生成并编码的密钥对数量：从初始值开始，按每批的耗时和写入端的积压在上下限之间调整
+const KEYGEN_BATCH_INITIAL: usize = 64;
+const KEYGEN_BATCH_MIN: usize = 1;
+const KEYGEN_BATCH_MAX: usize = 8192;
+/// 从生成密钥对到写出结果的默认最长延迟
+pub const DEFAULT_MAX_RESULT_DELAY: Duration = Duration::from_secs(1);
+
+// 消息只携带定长的原始字节，base58 编码和时间格式化都在写入线程中完成，
+// 工作线程每生成一个密钥对不再需要分配字符串
+#[derive(Debug, Clone)]
+enum LogMessage {
+    Regular {
+        // Unix 毫秒时间戳
+        timestamp_ms: i64,
+        counter: u64,
+        public_key: [u8; 32],
+        secret_key: [u8; 64],
+    },
+    Found {
+        timestamp_ms: i64,
+        counter: u64,
+        public_key: [u8; 32],
+        secret_key: [u8; 64],
+        // 命中的目标在目标列表中的下标
+        matched: Vec<usize>,
+        // 产生该密钥的后端和工作线程；复核步骤由结果写入线程补上
+        source: Provenance,
+    },
+}
+
+/// 常规日志的多生产者单消费者传输：预分配的无锁环形队列，
+/// 发送不分配内存，写入线程成批取出，队列为空时短暂休眠而不是逐条唤醒
+struct LogQueue {
+    queue: ArrayQueue<LogMessage>,
+    closed: AtomicBool,
+    // 写入线程已退出（例如写文件出错），此后的消息直接丢弃，避免生产者在满队列上空等
+    consumer_gone: AtomicBool,
+    // 停止时宽限期已过，写入线程不再取新的消息
+    abandoned: AtomicBool,
+    // 已写入并 flush 的常规日志条数，停止时据此算出丢弃的条数
+    written: AtomicU64,
+}
+
+impl LogQueue {
+    fn new(capacity: usize) -> Self {
+        LogQueue {
+            queue: ArrayQueue::new(capacity),
+            closed: AtomicBool::new(false),
+            consumer_gone: AtomicBool::new(false),
+            abandoned: AtomicBool::new(false),
+            written: AtomicU64::new(0),
+        }
+    }
+
+    fn push(&self, mut message: LogMessage) {
+        // 日志不能丢，队列满时等待写入线程腾出空间
+        while let Err(rejected) = self.queue.push(message) {
+            if self.consumer_gone.load(Ordering::Relaxed) {
+                return;
+            }
+            message = rejected;
+            thread::yield_now();
+        }
+    }
+
+    /// 所有生产者都已退出，写入线程取完剩余消息后结束
+    fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+    }
+
+    /// 队列已过半，写入线程跟不上生成的速度
+    fn backlogged(&self) -> bool {
+        self.queue.len() * 2 > self.queue.capacity()
+    }
+}
+
+// 工作线程自己数的生成数量和当前批次大小，只供调试转储读取
+#[derive(Default)]
+struct WorkerGauge {
+    generated: AtomicU64,
+    batch_size: AtomicUsize,
+}
+
+// 工作线程的批次大小。一批生成完才逐个检查和发送，结果最多晚一批的耗时才进入结果 channel：
+// 一批的耗时超过延迟目标的一半（另一半留给写入）时减半；远低于目标且写入端没有积压时加倍。
+// 常规日志队列写满时工作线程在入队处等待，这段时间计入批次耗时，批次随之变小
+struct BatchSizer {
+    size: usize,
+    budget: Duration,
+}
+
+impl BatchSizer {
+    fn new(max_result_delay: Duration) -> Self {
+        BatchSizer { size: KEYGEN_BATCH_INITIAL, budget: max_result_delay / 2 }
+    }
+
+    fn adjust(&mut self, elapsed: Duration, backlogged: bool) {
+        if elapsed > self.budget {
+            self.size = (self.size / 2).max(KEYGEN_BATCH_MIN);
+        } else if elapsed * 4 < self.budget && !backlogged {
+            self.size = (self.size * 2).min(KEYGEN_BATCH_MAX);
+        }
+    }
+}
+
+pub(crate) struct LogWriter {
+    dir: PathBuf,
+    stem: String,
+    sink: LogSink,
+    writer: Box<dyn LogOutput>,
+    pub(crate) file_index: u32,
+    line_count: u64,
+    // 轮转时记入会话日志
+    session: Option<SessionLog>,
+}
+
+/// result 文件的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResultFormat {
+    /// 人类可读的文本行，写入 result.log
+    #[default]
+    Text,
+    /// 每行一个 `FoundEvent` JSON 对象，写入 result.jsonl
+    Jsonl,
+}
+
+impl ResultFormat {
+    pub fn parse(value: &str) -> Result<Self, GrindError> {
+        match value {
+            "text" => Ok(ResultFormat::Text),
+            "jsonl" => Ok(ResultFormat::Jsonl),
+            _ => Err(GrindError::Config(format!("不支持的结果格式 '{}'（可选: text, jsonl）", value))),
+        }
+    }
+
+    /// 结果文件名；扩展名与 migrate、query 识别的格式一致
+    pub fn file_name(self) -> &'static str {
+        match self {
+            ResultFormat::Text => "result.log",
+            ResultFormat::Jsonl => "result.jsonl",
+        }
+    }
+}
+
+/// 一个公钥同时满足多个仍需结果的目标时，计入哪些目标
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Attribution {
+    /// 只计入排在最前面的目标
+    First,
+    /// 计入所有满足的目标（与 solana-keygen grind 一致）
+    #[default]
+    All,
+    /// 只计入最难找到（期望尝试次数最多）的目标；一样难时取排在前面的
+    MostSpecific,
+}
+
+impl Attribution {
+    pub fn parse(value: &str) -> Result<Self, GrindError> {
+        match value {
+            "first" => Ok(Attribution::First),
+            "all" => Ok(Attribution::All),
+            "most-specific" => Ok(Attribution::MostSpecific),
+            _ => Err(GrindError::Config(format!("不支持的归属策略 '{}'（可选: first, all, most-specific）", value))),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Attribution::First => "first",
+            Attribution::All => "all",
+            Attribution::MostSpecific => "most-specific",
+        }
+    }
+
+    /// 从满足的目标序号（升序）中选出计入的目标
+    pub fn credit(self, targets: &[Target], satisfied: &[usize], ignore_case: bool) -> Vec<usize> {
+        match self {
+            Attribution::All => satisfied.to_vec(),
+            Attribution::First => satisfied.first().copied().into_iter().collect(),
+            Attribution::MostSpecific => satisfied.iter().copied()
+                .fold(None, |best: Option<usize>, idx| match best {
+                    Some(b) if targets[b].expected_attempts(ignore_case) >= targets[idx].expected_attempts(ignore_case) => Some(b),
+                    _ => Some(idx),
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// 所有目标都找够数量之后怎么做
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnComplete {
+    /// 结束运行
+    #[default]
+    Stop,
+    /// 停止生成但不结束，等待通过控制句柄追加目标（[`RunControl::add_target`]）或停止请求
+    Idle,
+    /// 继续检查所有目标，超出要求数量的结果照常写出，直到收到停止请求
+    Continue,
+}
+
+impl OnComplete {
+    pub fn parse(value: &str) -> Result<Self, GrindError> {
+        match value {
+            "stop" => Ok(OnComplete::Stop),
+            "idle" => Ok(OnComplete::Idle),
+            "continue" => Ok(OnComplete::Continue),
+            _ => Err(GrindError::Config(format!("不支持的完成后行为 '{}'（可选: stop, idle, continue）", value))),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            OnComplete::Stop => "stop",
+            OnComplete::Idle => "idle",
+            OnComplete::Continue => "continue",
+        }
+    }
+}
+
+/// 搜索因达到哪个上限（`max_attempts` 或 `max_time`）而在目标找够之前结束
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Budget {
+    Attempts,
+    Time,
+}
+
+pub(crate) struct ResultWriter {
+    writer: BufWriter<File>,
+    format: ResultFormat,
+    // 只含目标、公钥和时间的公开列表 found_public.jsonl
+    public_feed: BufWriter<File>,
+    // 每个结果行同时写到标准输出，供管道下游处理；下游关闭后不再写
+    mirror_stdout: bool,
+    // 公私钥分离模式下，私钥写入单独的 0600 文件
+    secret_writer: Option<SecretWriter>,
+}
+
+impl LogWriter {
+    // 写入 `<stem>_XXXX.log`，从第 `file_index` 个文件、已有 `line_count` 行处接着写（继续暂停的搜索时不为 0）。
+    // 该序号的文件已经写满（有 `.done.log`）时顺延到下一个，不会追加到已完成的文件里
+    pub(crate) fn new(dir: &Path, stem: &str, file_index: u32, line_count: u64, sink: LogSink) -> std::io::Result<Self> {
+        let mut file_index = file_index;
+        let mut line_count = line_count;
+        while dir.join(log_file_name(stem, file_index, true)).exists() {
+            file_index += 1;
+            line_count = 0;
+        }
+        let file_path = dir.join(log_file_name(stem, file_index, false));
+        if !file_path.exists() {
+            create_log_file(&file_path)?;
+        }
+        let writer = logsink::open_append(&file_path, sink)?;
+        
+        status!("创建日志文件: {}", file_path.display());
+        
+        Ok(LogWriter {
+            dir: dir.to_path_buf(),
+            stem: stem.to_string(),
+            sink,
+            writer,
+            file_index,
+            line_count,
+            session: None,
+        })
+    }
+
+    pub(crate) fn with_session(mut self, session: Option<SessionLog>) -> Self {
+        self.session = session;
+        self
+    }
+
+    // 只写入缓冲区，由调用方在一批消息写完后统一 flush
+    pub(crate) fn write_line(&mut self, content: &str) -> std::io::Result<()> {
+        writeln!(self.writer, "{}", content)?;
+        self.line_count += 1;
+
+        // 如果达到最大行数，创建新文件
+        if self.line_count >= MAX_LINES_PER_FILE {
+            self.rotate_file()?;
+        }
+
+        Ok(())
+    }
+
+    // 轮转分两步，每一步都以 rename 结束，任何时刻崩溃都不会留下半成品：
+    // 1. 写完当前文件并 fsync，再改名为 `<stem>_XXXX.done.log`；
+    // 2. 下一个文件先以 `.tmp` 创建、fsync，再改名为 `<stem>_XXXX.log` 后打开。
+    // 重启时没有 `.done` 的文件就是唯一正在写的文件。新 writer 就绪后才更新序号和行数
+    fn rotate_file(&mut self) -> std::io::Result<()> {
+        let current = self.dir.join(log_file_name(&self.stem, self.file_index, false));
+        self.writer.finish()?;
+        sync_file(&current)?;
+        let done = self.dir.join(log_file_name(&self.stem, self.file_index, true));
+        if done.exists() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} 已存在，不覆盖已完成的日志", done.display()),
+            ));
+        }
+        fs::rename(&current, &done)?;
+
+        // 跳过以前的运行留下的同序号文件，新文件总是空的
+        let mut next_index = self.file_index + 1;
+        while self.dir.join(log_file_name(&self.stem, next_index, false)).exists()
+            || self.dir.join(log_file_name(&self.stem, next_index, true)).exists()
+        {
+            next_index += 1;
+        }
+        let file_path = self.dir.join(log_file_name(&self.stem, next_index, false));
+        create_log_file(&file_path)?;
+        sync_dir(&self.dir)?;
+        
+        // 替换 writer（旧的 writer 会被自动 drop）
+        self.writer = logsink::open_append(&file_path, self.sink)?;
+        self.file_index = next_index;
+        self.line_count = 0;
+        
+        progress!("创建新日志文件: {} (已达到 {} 行)", file_path.display(), MAX_LINES_PER_FILE);
+        if let Some(session) = &self.session {
+            session.record(&SessionEvent::Rotated { file: file_path.display().to_string() });
+        }
+        
+        Ok(())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+
+    // 写完并 fsync 当前文件；它没有写满，保持 `.log` 名称，继续暂停的搜索时接着写
+    pub(crate) fn finalize(&mut self) -> std::io::Result<()> {
+        self.writer.finish()?;
+        sync_file(&self.dir.join(log_file_name(&self.stem, self.file_index, false)))
+    }
+}
+
+/// 常规日志的文件名：正在写的 `<stem>_XXXX.log`，或写满后改名的 `<stem>_XXXX.done.log`。
+/// 已完成的文件仍以 `.log` 结尾，查询、迁移和审计照常读取
+pub fn log_file_name(stem: &str, index: u32, done: bool) -> String {
+    format!("{}_{:04}{}.log", stem, index, if done { ".done" } else { "" })
+}
+
+/// 从文件名中解析 `<stem>_XXXX.log` 或 `<stem>_XXXX.done.log` 的序号
+pub fn parse_log_index(name: &str, stem: &str) -> Option<u32> {
+    let index = name.strip_prefix(stem)?.strip_prefix('_')?.strip_suffix(".log")?;
+    index.strip_suffix(".done").unwrap_or(index).parse().ok()
+}
+
+// 先写临时文件并 fsync，再改名为最终名称，目录中不会出现只创建了一半的日志文件
+fn create_log_file(path: &Path) -> std::io::Result<()> {
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp = PathBuf::from(tmp_name);
+    File::create(&tmp)?.sync_all()?;
+    fs::rename(&tmp, path)
+}
+
+fn sync_file(path: &Path) -> std::io::Result<()> {
+    // Windows 上 fsync 需要写权限，因此以追加方式打开
+    OpenOptions::new().append(true).open(path)?.sync_all()
+}
+
+// 让目录中的改名落盘；Windows 不能打开目录，改名本身已经是持久的
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// 分片日志中第 `thread_id` 个线程的文件名前缀，例如 `keypairs_t03`
+pub fn shard_stem(thread_id: usize) -> String {
+    format!("{}_t{:02}", LOG_STEM, thread_id)
+}
+
+// 分片日志从已有的最大文件序号之后开始，不追加到以前（例如暂停前）的文件里
+fn next_shard_index(dir: &Path, stem: &str) -> u32 {
+    let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    let Ok(entries) = fs::read_dir(dir) else { return 0 };
+    entries.filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            parse_log_index(&name, stem)
+        })
+        .max()
+        .map_or(0, |index| index + 1)
+}
+
+fn regular_log_line(timestamp_ms: i64, counter: u64, public_key: &[u8; 32], secret_key: &[u8; 64]) -> String {
+    format!(
+        "[{}] 序号: {} | 公钥: {} | 私钥: {}",
+        local_time_str(&local_time(timestamp_ms)),
+        counter,
+        encode_public_key(public_key),
+        bs58::encode(secret_key).into_string()
+    )
+}
+
+impl ResultWriter {
+    pub(crate) fn new(dir: &Path, format: ResultFormat, secret_writer: Option<SecretWriter>, mirror_stdout: bool) -> std::io::Result<Self> {
+        let file_path = dir.join(format.file_name());
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file_path)?;
+        let writer = BufWriter::new(file);
+        let public_feed = BufWriter::new(OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(event::PUBLIC_FEED_FILE))?);
+        
+        if secret_writer.is_some() {
+            status!("创建结果文件: {} (仅包含公钥，可公开分享)", file_path.display());
+        } else {
+            status!("创建结果文件: {}", file_path.display());
+        }
+        
+        Ok(ResultWriter { writer, format, public_feed, mirror_stdout, secret_writer })
+    }
+
+    pub(crate) fn write_result(&mut self, time_str: &str, target: &Target, event: &FoundEvent) -> std::io::Result<()> {
+        let (counter, public_key) = (event.counter, &event.public_key);
+        let private_key = event.secret_key.as_deref().unwrap_or_default();
+        if let Some(secret_writer) = &mut self.secret_writer {
+            secret_writer.write_secret(time_str, public_key, private_key)?;
+        }
+        let mut actual = event.matched_text.as_ref().map_or_else(String::new, |text| format!(" | 实际匹配: {}", text));
+        if let Some(position) = event.position {
+            actual += &format!(" | 位置: {}", position);
+        }
+        let mut also = match &event.provenance {
+            Some(provenance) => format!(" | 来源: {} | 复核: {}", provenance, provenance.checks_label()),
+            None => String::new(),
+        };
+        if !event.matched_patterns.is_empty() {
+            also += &format!(" | 同时满足: {}", event.matched_patterns.join(", "));
+        }
+        let log_line = match (self.format, self.secret_writer.is_some()) {
+            (ResultFormat::Jsonl, true) => event.without_secret().to_json(),
+            (ResultFormat::Jsonl, false) => event.to_json(),
+            (ResultFormat::Text, true) => format!(
+                "[{}] [FOUND] 匹配{}: {}{} | 序号: {} | 公钥: {}{}",
+                time_str, target.kind(), target, actual, counter, public_key, also
+            ),
+            (ResultFormat::Text, false) => format!(
+                "[{}] [FOUND] 匹配{}: {}{} | 序号: {} | 公钥: {} | 私钥: {}{}",
+                time_str, target.kind(), target, actual, counter, public_key, private_key, also
+            ),
+        };
+        writeln!(self.writer, "{}", log_line)?;
+        self.writer.flush()?;
+        writeln!(self.public_feed, "{}", PublicEntry::from(event).to_json())?;
+        self.public_feed.flush()?;
+        if self.mirror_stdout {
+            let mut stdout = std::io::stdout().lock();
+            if let Err(e) = writeln!(stdout, "{}", log_line).and_then(|_| stdout.flush()) {
+                // 下游进程已退出（通常是 broken pipe）：结果仍在文件中，停止镜像但继续搜索
+                eprintln!("警告: 无法把结果写到标准输出（{}），之后的结果只写入结果文件", e);
+                self.mirror_stdout = false;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn finalize(&mut self) -> std::io::Result<()> {
+        self.writer.flush()?;
+        self.public_feed.flush()?;
+        if let Some(secret_writer) = &mut self.secret_writer {
+            secret_writer.finalize()?;
+        }
+        Ok(())
+    }
+}
+
+/// 将毫秒时间戳转为本地时间
+fn local_time(timestamp_ms: i64) -> DateTime<Local> {
+    Local.timestamp_millis_opt(timestamp_ms).single().unwrap_or_else(Local::now)
+}
+
+/// 日志中使用的本地时间格式，例如 20250101120000-123
+pub(crate) fn local_time_str(now: &DateTime<Local>) -> String {
+    format!("{}-{:03}", now.format("%Y%m%d%H%M%S"), now.timestamp_millis() % 1000)
+}
+
+/// 按速度 `rate`（个/秒）预测有截止时间的目标在何时找够数量。没有截止时间、已经找够或能在截止时间前找够时返回 None；
+/// 否则返回预计找够的时间，以及赶上截止时间需要的速度（截止时间已过时为 None）
+fn deadline_at_risk(
+    target: &Target,
+    found: u64,
+    rate: f64,
+    now: DateTime<FixedOffset>,
+    ignore_case: bool,
+) -> Option<(DateTime<FixedOffset>, Option<f64>)> {
+    let deadline = target.deadline?;
+    let remaining = target.count.saturating_sub(found) as f64 * target.expected_attempts(ignore_case);
+    let projected = now + chrono::Duration::milliseconds((remaining / rate * 1000.0).min(i64::MAX as f64 / 2.0) as i64);
+    if remaining == 0.0 || projected <= deadline {
+        return None;
+    }
+    let left = (deadline - now).num_milliseconds() as f64 / 1000.0;
+    Some((projected, (left > 0.0).then(|| remaining / left)))
+}
+
+fn encode_public_key(public_key: &[u8; 32]) -> String {
+    let mut encoded = EncodedKey::default();
+    base58::encode_32(public_key, &mut encoded);
+    encoded.as_str().to_string()
+}
+
+/// 返回通过区间剪枝（[`Matcher::may_match`]，由调用方先行检查）的公钥命中的目标下标；
+/// 配置了部分编码器时只编码出匹配所需的前若干个字符。
+fn match_key(
+    matcher: &Matcher,
+    prefix_encoder: Option<&PrefixEncoder>,
+    partial: &mut EncodedKey,
+    public_key: &[u8; 32],
+    encoded_key: &str,
+    ignore_case: bool,
+) -> Vec<usize> {
+    let candidate = match prefix_encoder {
+        Some(encoder) => {
+            encoder.encode(public_key, partial);
+            partial.as_str()
+        }
+        None => encoded_key,
+    };
+    if ignore_case {
+        matcher.find(&candidate.to_lowercase())
+    } else {
+        matcher.find(candidate)
+    }
+}
+
+/// 常规日志写入线程：成批取出队列中的消息写入 keypairs_XXXX.log，队列关闭并取空
+/// （或停止宽限期已过）后返回最后写到的文件序号和该文件的行数
+fn write_regular_logs(dir: &Path, start: (u32, u64), log_queue: &LogQueue, sink: LogSink, session: Option<SessionLog>) -> std::io::Result<(u32, u64)> {
+    let mut log_writer = LogWriter::new(dir, LOG_STEM, start.0, start.1, sink)?.with_session(session);
+
+    loop {
+        if log_queue.abandoned.load(Ordering::Acquire) {
+            log_writer.finalize()?;
+            break;
+        }
+        // 先读关闭标志再取消息：关闭之后不会再有新消息，这一轮取空即可退出
+        let closed = log_queue.closed.load(Ordering::Acquire);
+        let mut dequeued = 0;
+        while dequeued < LOG_DEQUEUE_BATCH {
+            let Some(message) = log_queue.queue.pop() else { break };
+            dequeued += 1;
+            if let LogMessage::Regular { timestamp_ms, counter, public_key, secret_key } = message {
+                log_writer.write_line(&regular_log_line(timestamp_ms, counter, &public_key, &secret_key))?;
+            }
+            // Found 消息由结果写入线程处理，这里只处理常规日志
+        }
+
+        if dequeued > 0 {
+            log_writer.flush()?;
+            log_queue.written.fetch_add(dequeued as u64, Ordering::Relaxed);
+        } else if closed {
+            // 所有工作线程都已退出
+            log_writer.finalize()?;
+            break;
+        } else {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+    Ok((log_writer.file_index, log_writer.line_count))
+}
+
+/// 写入结果前的精确复核：从私钥重新推导公钥，用参考实现重新编码后再检查目标。
+/// 工作线程中的区间剪枝、部分编码和定长编码器都只是加速手段，
+/// 它们即使出错也只会漏掉结果，而不会把错误的地址写进 result.log。
+/// 模拟模式的公钥不是由私钥推导的，只复核目标。通过时返回做过的复核步骤，记入结果的来源
+fn verify_found(public_key: &str, private_key: &str, target: &Target, ignore_case: bool, simulation: bool) -> Option<Vec<Check>> {
+    let mut checks = Vec::new();
+    if !simulation {
+        let bytes = bs58::decode(private_key).into_vec().ok()?;
+        let secret = <[u8; 32]>::try_from(&bytes[..bytes.len().min(32)]).ok()?;
+        if Keypair::new_from_array(secret).pubkey().to_string() != public_key {
+            return None;
+        }
+        checks.push(Check::Keypair);
+    }
+    let matches = if ignore_case {
+        target.matches(&public_key.to_lowercase())
+    } else {
+        target.matches(public_key)
+    };
+    matches.then(|| {
+        checks.push(Check::Pattern);
+        checks
+    })
+}
+
+/// 一次搜索的完整配置
+#[derive(Clone)]
+pub struct GrindConfig {
+    pub num_threads: usize,
+    pub targets: Vec<Target>,
+    pub ignore_case: bool,
+    // 公私钥分离输出；`secret_passphrase` 不为空时加密私钥
+    pub split_output: bool,
+    pub secret_passphrase: Option<String>,
+    /// 私钥允许流向哪些输出；设置后启动时拒绝违反策略的配置
+    pub secret_policy: Option<SecretPolicy>,
+    // 是否像 solana-keygen grind 一样为每个结果写出 <公钥>.json
+    pub write_keypair_files: bool,
+    /// 每记录一个结果时调用（在结果写入线程中执行）
+    pub on_found: Option<FoundCallback>,
+    /// 运行事件（找到结果、全部完成、里程碑、出错）的回调，在产生事件的线程中执行
+    pub on_event: Option<EventCallback>,
+    /// 在输出目录的 `session.log` 中记录生命周期事件（开始、校准、日志轮转、暂停和继续、运行事件、结束）
+    pub session_log: bool,
+    /// 每找到一个结果后执行的钩子，在单独的钩子线程中依次执行，收到的结果不含私钥
+    pub hooks: Vec<Hook>,
+    /// 单个钩子的最长执行时间，超时后终止并报告失败
+    pub hook_timeout: Duration,
+    /// 每生成这么多密钥对产生一个 [`RunEvent::Milestone`]；为空时不产生
+    pub milestone_every: Option<u64>,
+    /// 持久化的公钥碰撞索引文件；为空时不检测重复公钥
+    pub collision_index: Option<PathBuf>,
+    /// 新建碰撞索引时的设计容量（公钥数量）
+    pub collision_capacity: u64,
+    /// 记录已写入结果的（目标，公钥）组合的 SQLite 数据库；重启、继续或多个进程共用时
+    /// 同一个组合只写入结果文件一次。相对路径相对于 `output_dir`；为空时不去重
+    pub dedup_db: Option<PathBuf>,
+    /// 统计端口监听地址（如 `127.0.0.1:7878`），供 `monitor` 远程查看进度；为空时不监听
+    pub stats_addr: Option<String>,
+    /// 允许统计端口监听回环地址以外的地址（明文传输，没有 TLS）
+    pub stats_allow_remote: bool,
+    /// 统计端口的访问级别；旁观级别只推送进度和找到的公钥
+    pub stats_access: StatsAccess,
+    /// 定期原子写入的进度文件（JSON）；为空时不写
+    pub progress_file: Option<PathBuf>,
+    /// 极速模式：不写常规日志、不输出进度，计数器按批递增，时间和私钥只为命中的公钥计算
+    pub raw_speed: bool,
+    /// 控制台输出不使用 emoji 等特殊符号
+    pub plain_output: bool,
+    /// 找到结果时的提示模板：控制台提示改为按模板生成的一行，通知中的结果事件也附带生成的文本（不含私钥）；
+    /// 为空时使用默认的多行提示
+    pub announce: Option<AnnounceTemplate>,
+    /// 找到结果的控制台提示中是否出现私钥；为假时完全省略私钥那一行（模板中不能使用 `{secret}`）
+    pub console_secret: bool,
+    /// 进度和汇总中数量、速度的显示方式
+    pub count_format: CountFormat,
+    /// 设置后每秒输出一行进度（生成总数和按这个窗口平滑的速度），
+    /// 代替各工作线程每生成 1000 个输出一次的进度；为空时保持后者
+    pub rate_window: Option<Duration>,
+    /// 结果文件的格式
+    pub result_format: ResultFormat,
+    /// 每个结果行（格式同结果文件）同时写到标准输出；其余提示信息改写到标准错误
+    pub results_to_stdout: bool,
+    /// 一个公钥同时满足多个仍需结果的目标时如何计入
+    pub attribution: Attribution,
+    /// 密钥生成后端，每个工作线程各创建一个实例
+    pub backend: BackendFactory,
+    /// 时间来源：日志时间戳、批次计时、速度、停滞检测、截止时间预测和统计快照都从这里读取时间
+    pub clock: SharedClock,
+    /// 所有输出文件（日志、结果、私钥、密钥文件）所在的目录；为空时写入当前目录
+    pub output_dir: PathBuf,
+    /// 本次运行的 ID；为空时用 [`new_run_id`] 生成
+    pub run_id: Option<String>,
+    /// 模拟模式：`backend` 产生的是合成公钥（见 [`backend::simulated`]），复核时不检查公私钥是否对应
+    pub simulation: bool,
+    /// 低内存模式：不写常规日志、不预分配日志队列，结果 channel 只保留很小的容量，
+    /// 只有结果写入线程一个写线程，适合树莓派等小内存设备
+    pub low_memory: bool,
+    /// 分片日志：每个工作线程直接写自己的 `keypairs_tNN_XXXX.log`（同样按行数轮转），
+    /// 不经过共享队列和日志写入线程，日志吞吐随线程数增长；之后可用 [`crate::merge::merge_shards`] 按序号合并
+    pub shard_logs: bool,
+    /// 常规日志（包括分片日志）的写入方式
+    pub log_sink: LogSink,
+    /// 写入线程和钩子线程相对工作线程的调度策略
+    pub io_priority: IoPriority,
+    /// 停止时等待写入线程取完队列的最长时间，超时后丢弃剩余的常规日志
+    pub shutdown_grace: Duration,
+    /// 从生成密钥对到写出结果的最长延迟目标。一批生成完才逐个检查，批次越大吞吐越高、延迟也越长，
+    /// 工作线程据此和写入端的积压调整批次大小
+    pub max_result_delay: Duration,
+    /// 设置后可以暂停：输出目录中出现 `suspend.request` 时停止搜索，把完整状态写入该文件后返回
+    pub state_file: Option<PathBuf>,
+    /// 从暂停时保存的进度继续
+    pub resume: Option<ResumePoint>,
+    /// 运行中的控制句柄（例如命令行的键盘控制）；为空时只能通过 `suspend.request` 停止
+    pub control: Option<RunControl>,
+    /// 输出目录中出现 `debug_dump.request`（或通过控制句柄请求）时写出不含私钥的内部状态转储，见 [`crate::debug`]
+    pub debug_dump: bool,
+    /// 速度停滞检测；为空时不检测
+    pub stall_detection: Option<StallDetection>,
+    /// 速度基线：开始后测得的速度比基线慢得超过容许比例时醒目地警告，并产生 [`RunEvent::BelowBaseline`]；
+    /// 用于发现调试构建、线程数配置错误等问题。为空时不比较
+    pub speed_baseline: Option<SpeedBaseline>,
+    /// 共生成这么多密钥对后才结束：目标找够后不再检查，但继续计数，用于收集实际难度数据；
+    /// 为空时所有目标找够即结束
+    pub keep_counting: Option<u64>,
+    /// 本次调用最多生成这么多密钥对，目标没有找够也结束（继续暂停的搜索时从继续点算起）；为空时不限制
+    pub max_attempts: Option<u64>,
+    /// 本次调用的最长运行时间，到时目标没有找够也结束；为空时不限制
+    pub max_time: Option<Duration>,
+    /// 所有目标都找够数量之后结束、空闲等待追加目标还是继续收集结果；后两种只能通过停止请求结束
+    pub on_complete: OnComplete,
+}
+
+/// 停滞检测：最近 10 秒的平均速度低于校准速度（本次运行中观察到的最高窗口速度）的 `fraction`
+/// 并持续超过 `after` 时，输出带队列深度的警告并产生 [`RunEvent::Stalled`]；每次停滞只报告一次
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StallDetection {
+    pub fraction: f64,
+    pub after: Duration,
+}
+
+impl Default for StallDetection {
+    fn default() -> Self {
+        StallDetection { fraction: 0.5, after: Duration::from_secs(120) }
+    }
+}
+
+/// 运行中的控制句柄，可复制后在任意线程中使用：暂停和继续工作线程、请求停止、读取当前统计。
+/// 每次运行开始时重置暂停和停止状态，同一个句柄可以依次用于多次运行
+#[derive(Clone, Default)]
+pub struct RunControl {
+    paused: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+    dump: Arc<AtomicBool>,
+    added: Arc<Mutex<Vec<Target>>>,
+    stats: Arc<Mutex<Option<Arc<StatsSource>>>>,
+}
+
+impl RunControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 暂停或继续生成；暂停期间停滞检测和速度基线检查也暂停
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// 请求停止，与输出目录中出现 `suspend.request` 相同：设置了 `state_file` 时保存状态后返回
+    pub fn request_stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    fn stop_requested(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    /// 请求写出一次调试转储，与输出目录中出现 `debug_dump.request` 相同；没有启用 `debug_dump` 时不起作用
+    pub fn request_dump(&self) {
+        self.dump.store(true, Ordering::Relaxed);
+    }
+
+    fn take_dump_request(&self) -> bool {
+        self.dump.swap(false, Ordering::Relaxed)
+    }
+
+    /// 追加一个目标：停止当前运行（设置了 `state_file` 时保存状态），由调用方用 [`RunControl::take_added`]
+    /// 取出后写入保存的状态再继续，命令行的键盘控制和 `--on-complete idle` 都这样追加目标
+    pub fn add_target(&self, target: Target) {
+        self.added.lock().unwrap_or_else(|e| e.into_inner()).push(target);
+        self.set_paused(false);
+        self.request_stop();
+    }
+
+    /// 取出用 [`RunControl::add_target`] 追加、尚未加入搜索的目标
+    pub fn take_added(&self) -> Vec<Target> {
+        std::mem::take(&mut *self.added.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// 所有目标都已找到，正按 [`OnComplete::Idle`] 等待追加目标
+    pub fn is_idle(&self) -> bool {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner()).as_ref().is_some_and(|source| source.idle.load(Ordering::Relaxed))
+    }
+
+    /// 当前运行的统计快照；运行开始前为空
+    pub fn snapshot(&self) -> Option<StatsSnapshot> {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner()).as_ref().map(|source| source.snapshot())
+    }
+
+    fn attach(&self, source: Arc<StatsSource>) {
+        self.paused.store(false, Ordering::Relaxed);
+        self.stop.store(false, Ordering::Relaxed);
+        self.dump.store(false, Ordering::Relaxed);
+        *self.stats.lock().unwrap_or_else(|e| e.into_inner()) = Some(source);
+    }
+}
+
+/// 速度基线检查：跳过开始的 5 秒后按 15 秒的平均速度与 `keys_per_sec` 比较，每次运行只比较一次
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpeedBaseline {
+    /// 基线速度（个/秒），例如以前同样配置的运行报告中的速度
+    pub keys_per_sec: f64,
+    /// 容许比基线慢的比例，例如 0.25 表示慢 25% 以内不警告
+    pub tolerance: f64,
+}
+
+/// 库调用方接收结果的回调
+pub type FoundCallback = Arc<dyn Fn(&FoundEvent) + Send + Sync>;
+
+/// 库调用方接收运行事件的回调
+pub type EventCallback = Arc<dyn Fn(&RunEvent) + Send + Sync>;
+
+impl GrindConfig {
+    /// 使用默认设置（全部 CPU 核心、区分大小写、不分离输出）搜索给定目标
+    pub fn new(targets: Vec<Target>) -> Self {
+        GrindConfig {
+            num_threads: num_cpus::get(),
+            targets,
+            ignore_case: false,
+            split_output: false,
+            secret_passphrase: None,
+            secret_policy: None,
+            write_keypair_files: false,
+            on_found: None,
+            on_event: None,
+            session_log: true,
+            hooks: Vec::new(),
+            hook_timeout: hooks::DEFAULT_HOOK_TIMEOUT,
+            milestone_every: None,
+            collision_index: None,
+            collision_capacity: collision::DEFAULT_CAPACITY,
+            dedup_db: Some(PathBuf::from(dedup::DEDUP_DB)),
+            stats_addr: None,
+            stats_allow_remote: false,
+            stats_access: StatsAccess::Full,
+            progress_file: None,
+            raw_speed: false,
+            plain_output: false,
+            announce: None,
+            console_secret: true,
+            count_format: CountFormat::Raw,
+            rate_window: None,
+            result_format: ResultFormat::Text,
+            results_to_stdout: false,
+            attribution: Attribution::All,
+            keep_counting: None,
+            max_attempts: None,
+            max_time: None,
+            on_complete: OnComplete::Stop,
+            low_memory: false,
+            shard_logs: false,
+            log_sink: LogSink::Buffered,
+            io_priority: IoPriority::Normal,
+            simulation: false,
+            backend: backend::cpu(),
+            clock: clock::system(),
+            output_dir: PathBuf::new(),
+            run_id: None,
+            state_file: None,
+            resume: None,
+            control: None,
+            debug_dump: false,
+            shutdown_grace: DEFAULT_SHUTDOWN_GRACE,
+            max_result_delay: DEFAULT_MAX_RESULT_DELAY,
+            stall_detection: Some(StallDetection::default()),
+            speed_baseline: None,
+        }
+    }
+
+    /// 找到结果的控制台提示是否包含私钥（私钥策略另行检查）
+    pub fn console_shows_secret(&self) -> bool {
+        self.console_secret && self.announce.as_ref().is_none_or(AnnounceTemplate::uses_secret)
+    }
+
+    pub fn validate(&self) -> Result<(), GrindError> {
+        if self.num_threads == 0 {
+            return Err(GrindError::Config("线程数必须大于 0".to_string()));
+        }
+        if self.targets.is_empty() {
+            return Err(GrindError::Config("至少需要指定一个目标".to_string()));
+        }
+        difficulty::ensure_reachable(&self.targets, self.ignore_case)?;
+        if let Some(addr) = &self.stats_addr {
+            net::check_listen_address(addr, self.stats_allow_remote, stats::STATS_ALLOW_REMOTE_FLAG)?;
+        }
+        if self.secret_passphrase.is_some() && !self.split_output {
+            return Err(GrindError::Config("加密私钥需要同时启用公私钥分离输出".to_string()));
+        }
+        if self.shard_logs && (self.raw_speed || self.low_memory) {
+            return Err(GrindError::Config("分片日志不能与极速模式或低内存模式同时使用（这两种模式都不写常规日志）".to_string()));
+        }
+        if self.stall_detection.is_some_and(|d| !(d.fraction > 0.0 && d.fraction < 1.0)) {
+            return Err(GrindError::Config("停滞检测的比例必须在 0 和 1 之间".to_string()));
+        }
+        if self.speed_baseline.is_some_and(|b| !(b.keys_per_sec > 0.0 && b.keys_per_sec.is_finite())) {
+            return Err(GrindError::Config("速度基线必须是正数".to_string()));
+        }
+        if self.speed_baseline.is_some_and(|b| !(b.tolerance > 0.0 && b.tolerance < 1.0)) {
+            return Err(GrindError::Config("速度基线的容许比例必须在 0 和 1 之间".to_string()));
+        }
+        if !self.console_secret && self.announce.as_ref().is_some_and(AnnounceTemplate::uses_secret) {
+            return Err(GrindError::Config("提示模板中使用了 {secret}，但设置了控制台提示不显示私钥".to_string()));
+        }
+        if self.max_result_delay.is_zero() {
+            return Err(GrindError::Config("结果的最长延迟必须大于 0".to_string()));
+        }
+        if self.on_complete != OnComplete::Stop && self.keep_counting.is_some() {
+            return Err(GrindError::Config("完成后空闲或继续收集不能与持续计数同时使用".to_string()));
+        }
+        if self.keep_counting.is_some() && self.max_attempts.is_some() {
+            return Err(GrindError::Config("持续计数已经限定了生成数量，不能再设置生成数量上限".to_string()));
+        }
+        let bounded = self.max_attempts.is_some() || self.max_time.is_some();
+        if self.on_complete != OnComplete::Stop && self.control.is_none() && self.state_file.is_none() && !bounded {
+            return Err(GrindError::Config("完成后空闲或继续收集需要控制句柄、状态文件或运行上限，否则运行无法结束".to_string()));
+        }
+        if let Some(resume) = &self.resume {
+            if resume.found.len() != self.targets.len() || resume.satisfied_at.len() != self.targets.len() {
+                return Err(GrindError::Config("继续点中的目标数量与配置不一致".to_string()));
+            }
+        }
+        if let Some(policy) = &self.secret_policy {
+            policy.enforce(self)?;
+        }
+        Ok(())
+    }
+}
+
+/// 搜索结束后的汇总
+#[derive(Debug, Clone)]
+pub struct GrindSummary {
+    /// 共生成的密钥对数量
+    pub attempts: u64,
+    /// 每个目标及其找到的数量
+    pub found: Vec<(Target, u64)>,
+    /// 启用碰撞索引时，本次运行发现的疑似重复公钥数量
+    pub suspected_collisions: Option<u64>,
+    /// 已经记录过（例如由之前的运行或共用数据库的其他进程写入）、因此没有再写入的结果数量
+    pub duplicate_results: u64,
+    /// 本次运行的 ID，同时写入每个 `FoundEvent`
+    pub run_id: String,
+    /// 与 `found` 一一对应：目标找够数量时那个公钥的序号，未找够时为空
+    pub satisfied_at: Vec<Option<u64>>,
+    /// 是否因暂停请求而提前结束（状态已写入 `state_file`）
+    pub suspended: bool,
+    /// 停止时宽限期内没能写入、被丢弃的常规日志条数
+    pub dropped_logs: u64,
+    /// 本次调用的运行时长（继续暂停的搜索时不含暂停前的时间）
+    pub elapsed: Duration,
+    /// 本次调用的平均速度（个/秒）
+    pub keys_per_sec: f64,
+    /// 与 `found` 一一对应：本次调用开始后多久找够数量；未找够或暂停前已找够时为空
+    pub satisfied_after: Vec<Option<Duration>>,
+    /// 目标没有全部找够、因达到生成数量或运行时间上限而结束时为达到的上限
+    pub exhausted: Option<Budget>,
+}
+
+// 等待线程结束，最多等到 `deadline`；返回线程是否已结束
+fn wait_finished<T>(handle: &thread::JoinHandle<T>, deadline: Instant) -> bool {
+    while !handle.is_finished() {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    true
+}
+
+/// 生成运行 ID：启动时间加进程号，足以区分同一目录下的多次运行
+pub fn new_run_id() -> String {
+    format!("{}-{}", Local::now().format("%Y%m%dT%H%M%S"), std::process::id())
+}
+
+/// 运行搜索，直到所有目标都找到指定数量的地址（设置了 `keep_counting` 时直到生成够数量）
+pub fn run(config: &GrindConfig) -> Result<GrindSummary, GrindError> {
+    let run_id = config.run_id.clone().unwrap_or_else(new_run_id);
+    let started = config.clock.now();
+    let session = config.session_log.then(|| SessionLog::open(&config.output_dir, &run_id))
+        .and_then(|result| result.map_err(|e| eprintln!("警告: 无法打开 {}，不记录会话事件: {}", session::SESSION_LOG, e)).ok());
+    // 运行事件同时记入会话日志
+    let on_event = match &session {
+        Some(session) => Some(session.wrap(config.on_event.clone())),
+        None => config.on_event.clone(),
+    };
+    let result = run_with_id(config, run_id.clone(), on_event.clone(), session.clone());
+    if let (Err(e), Some(callback)) = (&result, &on_event) {
+        callback(&RunEvent::Error { timestamp: event::now_timestamp(), run_id, message: e.to_string() });
+    }
+    if let Some(session) = &session {
+        session.record(&match &result {
+            Ok(summary) => SessionEvent::Shutdown {
+                reason: if summary.suspended {
+                    "suspended"
+                } else if summary.exhausted.is_some() {
+                    "budget"
+                } else if config.keep_counting.is_some() {
+                    "limit"
+                } else {
+                    "complete"
+                },
+                attempts: Some(summary.attempts),
+                elapsed_secs: summary.elapsed.as_secs_f64(),
+                found: Some(summary.found.iter().map(|(_, n)| n).sum()),
+                dropped_logs: Some(summary.dropped_logs),
+            },
+            Err(_) => SessionEvent::Shutdown {
+                reason: "error",
+                attempts: None,
+                elapsed_secs: config.clock.now().duration_since(started).as_secs_f64(),
+                found: None,
+                dropped_logs: None,
+            },
+        });
+    }
+    result
+}
+
+fn run_with_id(config: &GrindConfig, run_id: String, on_event: Option<EventCallback>, session: Option<SessionLog>) -> Result<GrindSummary, GrindError> {
+    config.validate()?;
+    let num_threads = config.num_threads;
+    let ignore_case = config.ignore_case;
+    let split_output = config.split_output;
+    let secret_passphrase = config.secret_passphrase.clone();
+    let write_keypair_files = config.write_keypair_files;
+    let raw_speed = config.raw_speed;
+    let simulation = config.simulation;
+    // 极速模式和低内存模式都不写常规日志
+    let write_logs = !raw_speed && !config.low_memory;
+    // 分片日志由工作线程各自写入，共享的队列和日志写入线程不再使用
+    let shard_logs = config.shard_logs;
+    let shared_logs = write_logs && !shard_logs;
+    let log_sink = config.log_sink;
+    let io_priority = config.io_priority;
+    let clock = Arc::clone(&config.clock);
+    let plain_output = config.plain_output;
+    let count_format = config.count_format;
+    // 设置了平滑窗口时由单独的线程输出进度，工作线程不再输出
+    let thread_progress = config.rate_window.is_none();
+    // 私钥策略禁止在控制台显示私钥时，找到结果的提示中隐去私钥
+    let show_secret = config.secret_policy.is_none_or(|p| p.show_on_console());
+    let console_secret = config.console_secret;
+    let announce = config.announce.clone();
+    let result_format = config.result_format;
+    let results_to_stdout = config.results_to_stdout;
+    let attribution = config.attribution;
+    if results_to_stdout {
+        console::set_status_to_stderr(true);
+    }
+    let keep_counting = config.keep_counting;
+    let on_complete = config.on_complete;
+    let output_dir = config.output_dir.clone();
+    let on_found = config.on_found.clone();
+    let targets: Vec<Target> = if ignore_case {
+        // 与 solana-keygen grind 一致：目标和公钥都转为小写后比较
+        config.targets.iter().map(Target::to_lowercase).collect()
+    } else {
+        config.targets.clone()
+    };
+
+    // 共享状态；继续暂停的搜索时从保存的进度开始
+    let resume = config.resume.clone().unwrap_or_else(|| ResumePoint {
+        found: vec![0; targets.len()],
+        satisfied_at: vec![None; targets.len()],
+        ..ResumePoint::default()
+    });
+    // 开始前检查上次运行崩溃留下的临时文件、半行和未提交的事务日志
+    let dedup_path = config.dedup_db.as_ref().map(|path| output_dir.join(path));
+    let recovered = recovery::scan(&output_dir, dedup_path.as_deref()).unwrap_or_else(|e| {
+        eprintln!("警告: 输出目录的恢复检查失败，跳过: {}", e);
+        Vec::new()
+    });
+    for action in &recovered {
+        status!("恢复检查: {}", action);
+    }
+    if let Some(session) = &session {
+        session.record(&SessionEvent::Start {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            threads: num_threads,
+            backend: (config.backend)(0).name().to_string(),
+            rng: (config.backend)(0).rng_policy(),
+            targets: config.targets.iter().map(Target::to_string).collect(),
+            ignore_case,
+        });
+        if !recovered.is_empty() {
+            session.record(&SessionEvent::Recovered { actions: recovered });
+        }
+        if config.resume.is_some() {
+            session.record(&SessionEvent::Resumed { attempts: resume.attempts, found: resume.found.clone() });
+        }
+    }
+    let counter = Arc::new(AtomicU64::new(resume.attempts));
+    // 每个目标已找到的数量
+    let found_counts = Arc::new(Mutex::new(resume.found.clone()));
+    // 最近找到的公钥，供统计快照使用
+    let recent_finds = Arc::new(RecentFinds::default());
+    let satisfied_at = Arc::new(Mutex::new(resume.satisfied_at.clone()));
+    let started = clock.now();
+    // 本次调用的生成数量和运行时间上限
+    let attempt_limit = config.max_attempts.map(|n| resume.attempts.saturating_add(n));
+    // 工作线程在生成每一批之前从 --max-attempts 中领取的数量，最后一批只领取剩余的部分
+    let attempts_claimed = Arc::new(AtomicU64::new(resume.attempts));
+    let time_limit = config.max_time.map(|t| started + t);
+    let satisfied_after = Arc::new(Mutex::new(vec![None; targets.len()]));
+    let mut active: Vec<usize> = (0..targets.len()).filter(|&idx| resume.found[idx] < targets[idx].count).collect();
+    let all_found = Arc::new(AtomicBool::new(active.is_empty() && keep_counting.is_none() && on_complete == OnComplete::Stop));
+    // 所有目标都已找到后按 --on-complete 空闲：工作线程不再生成，直到收到停止请求
+    let idle = Arc::new(AtomicBool::new(active.is_empty() && on_complete == OnComplete::Idle));
+    // 所有目标都已找到后继续收集：重新检查所有目标，超出数量的结果也写出
+    let collecting = active.is_empty() && on_complete == OnComplete::Continue;
+    if collecting {
+        active = (0..targets.len()).collect();
+    }
+    // 收到暂停请求后工作线程停止，状态写入 state_file
+    let suspended = Arc::new(AtomicBool::new(false));
+    // 编译后的目标集合，所有工作线程共享；某个目标找够数量后，结果写入线程会
+    // 用剩余目标重新编译并递增版本号，工作线程在下一批开始时换用新的集合
+    let matcher = Arc::new(RwLock::new(Arc::new(Matcher::with_active(&targets, &active, ignore_case))));
+    let matcher_version = Arc::new(AtomicU64::new(0));
+    
+    // 常规日志走环形队列，数量很少的结果走独立的 channel
+    let log_queue = Arc::new(LogQueue::new(if shared_logs { LOG_QUEUE_CAPACITY } else { 1 }));
+    // 已发送但结果写入线程尚未取出的结果数，停滞时用于诊断
+    let pending_results = Arc::new(AtomicU64::new(0));
+    let result_channel_bound = if config.low_memory { LOW_MEMORY_RESULT_CHANNEL_BOUND } else { RESULT_CHANNEL_BOUND };
+    let (result_tx, result_rx) = mpsc::sync_channel::<LogMessage>(result_channel_bound);
+    let workers: Arc<Vec<WorkerGauge>> = Arc::new((0..num_threads).map(|_| WorkerGauge::default()).collect());
+    
+    // 启动日志写入线程（常规日志）
+    let log_writer_handle = {
+        let log_queue = Arc::clone(&log_queue);
+        let output_dir = output_dir.clone();
+        let log_start = (resume.log_file_index, resume.log_line_count);
+        let session = session.clone();
+        thread::spawn(move || -> std::io::Result<(u32, u64)> {
+            priority::enter_io(io_priority);
+            // 不写常规日志时也不创建日志文件，线程立即结束
+            let result = if shared_logs { write_regular_logs(&output_dir, log_start, &log_queue, log_sink, session) } else { Ok(log_start) };
+            log_queue.consumer_gone.store(true, Ordering::Relaxed);
+            result
+        })
+    };
+    
+    // 有钩子时启动钩子线程，结果写入线程只负责把结果排队
+    let hook_runner = (!config.hooks.is_empty()).then(|| {
+        HookRunner::start(config.hooks.clone(), config.hook_timeout, &output_dir, run_id.clone(), on_event.clone(), io_priority)
+    });
+    
+    // 已记录结果的数据库在启动前打开，无法打开时直接报错
+    let recorded = match &config.dedup_db {
+        Some(path) => Some(RecordedResults::open(&output_dir.join(path), &output_dir)?),
+        None => None,
+    };
+    let duplicate_results = Arc::new(AtomicU64::new(0));
+    
+    // 启动结果写入线程（result.log）
+    let result_writer_handle = {
+        let mut recorded = recorded;
+        let duplicate_results = Arc::clone(&duplicate_results);
+        let result_rx = result_rx;
+        let found_counts = Arc::clone(&found_counts);
+        let recent_finds = Arc::clone(&recent_finds);
+        let satisfied_at = Arc::clone(&satisfied_at);
+        let satisfied_after = Arc::clone(&satisfied_after);
+        let all_found = Arc::clone(&all_found);
+        let idle = Arc::clone(&idle);
+        let mut collecting = collecting;
+        let targets = targets.clone();
+        let matcher = Arc::clone(&matcher);
+        let matcher_version = Arc::clone(&matcher_version);
+        let run_id = run_id.clone();
+        let on_event = on_event.clone();
+        let hook_sender = hook_runner.as_ref().map(HookRunner::sender);
+        let pending_results = Arc::clone(&pending_results);
+        let resumed_attempts = resume.attempts;
+        let clock = Arc::clone(&clock);
+        thread::spawn(move || -> std::io::Result<()> {
+            priority::enter_io(io_priority);
+            // 提示模板的 {eta_beaten} 按精确的命中概率计算预计找到的时间
+            let expected: Vec<f64> = targets.iter().map(|t| difficulty::analyze(t, ignore_case).expected_attempts()).collect();
+            let secret_writer = if split_output {
+                Some(SecretWriter::new(&output_dir, secret_passphrase)?)
+            } else {
+                None
+            };
+            let mut result_writer = ResultWriter::new(&output_dir, result_format, secret_writer, results_to_stdout)?;
+            
+            loop {
+                match result_rx.recv() {
+                    Ok(LogMessage::Found { timestamp_ms, counter, public_key, secret_key, matched, source }) => {
+                        pending_results.fetch_sub(1, Ordering::Relaxed);
+                        let now = local_time(timestamp_ms);
+                        let time_str = local_time_str(&now);
+                        let timestamp = now.to_rfc3339_opts(SecondsFormat::Millis, false);
+                        let public_key = encode_public_key(&public_key);
+                        let private_key = bs58::encode(secret_key).into_string();
+                        let mut found = found_counts.lock().unwrap_or_else(|e| e.into_inner());
+                        let mut checks = Vec::new();
+                        let mut satisfied: Vec<usize> = matched.into_iter()
+                            .filter(|&idx| collecting || found[idx] < targets[idx].count)
+                            .filter(|&idx| match verify_found(&public_key, &private_key, &targets[idx], ignore_case, simulation) {
+                                Some(passed) => {
+                                    checks = passed;
+                                    true
+                                }
+                                None => {
+                                    eprintln!(
+                                        "错误: 公钥 {} 未通过复核（不匹配 '{}' 或与私钥不符），已丢弃；这说明 {} 后端（线程 {}）或匹配快速路径存在缺陷",
+                                        public_key, targets[idx], source.backend, source.worker
+                                    );
+                                    false
+                                }
+                            })
+                            .collect();
+                        let provenance = Provenance { checks, ..source };
+                        if satisfied.is_empty() {
+                            continue;
+                        }
+                        satisfied.sort_unstable();
+                        // 按归属策略决定计入哪些目标；结果中记录满足的所有目标
+                        let credited = attribution.credit(&targets, &satisfied, ignore_case);
+                        let matched_patterns: Vec<String> = if satisfied.len() > 1 {
+                            satisfied.iter().map(|&idx| targets[idx].to_string()).collect()
+                        } else {
+                            Vec::new()
+                        };
+                        
+                        // 实际写入的结果数；全部已经记录过时不再写密钥文件和输出进度
+                        let mut written = 0;
+                        for &idx in &credited {
+                            let target = &targets[idx];
+                            // 先登记（目标，公钥），结果写入后才提交
+                            let claim = match &mut recorded {
+                                Some(recorded) => match recorded.claim(&target.to_string(), &public_key, &run_id, &timestamp)
+                                    .map_err(std::io::Error::other)?
+                                {
+                                    Some(claim) => Some(claim),
+                                    None => {
+                                        duplicate_results.fetch_add(1, Ordering::Relaxed);
+                                        eprintln!("警告: 公钥 {} 已作为 '{}' 的结果记录过，不再重复写入", public_key, target);
+                                        continue;
+                                    }
+                                },
+                                None => None,
+                            };
+                            found[idx] += 1;
+                            written += 1;
+                            if found[idx] == target.count {
+                                satisfied_at.lock().unwrap_or_else(|e| e.into_inner())[idx] = Some(counter);
+                                satisfied_after.lock().unwrap_or_else(|e| e.into_inner())[idx] = Some(clock.now().duration_since(started));
+                            }
+                            
+                            // 按模板生成提示：预计用时按本次运行的平均速度，把继续前的进度也计算在内
+                            let pattern = target.to_string();
+                            let elapsed_secs = clock.now().duration_since(started).as_secs_f64();
+                            let fresh = counter.saturating_sub(resumed_attempts);
+                            let announcement = Announcement {
+                                pattern: &pattern,
+                                kind: target.kind(),
+                                public_key: &public_key,
+                                secret: None,
+                                counter,
+                                found: found[idx],
+                                count: target.count,
+                                time: &time_str,
+                                elapsed_secs,
+                                eta_beaten_secs: (elapsed_secs > 0.0 && fresh > 0).then(|| {
+                                    (found[idx] as f64 * expected[idx] - counter as f64) * elapsed_secs / fresh as f64
+                                }),
+                            };
+                            
+                            // 写入结果文件
+                            let event = FoundEvent {
+                                run_id: Some(run_id.clone()),
+                                matched_patterns: matched_patterns.clone(),
+                                announcement: announce.as_ref().map(|template| template.render(&announcement)),
+                                provenance: Some(provenance.clone()),
+                                ..FoundEvent::new(&timestamp, counter, target, &public_key, &private_key)
+                            };
+                            result_writer.write_result(&time_str, target, &event)?;
+                            recent_finds.push(PublicEntry::from(&event));
+                            if let Some(claim) = claim {
+                                claim.commit().map_err(std::io::Error::other)?;
+                            }
+                            if let Some(callback) = &on_found {
+                                callback(&event);
+                            }
+                            if let Some(callback) = &on_event {
+                                callback(&RunEvent::Found(Box::new(event.without_secret())));
+                            }
+                            if let Some(hooks) = &hook_sender {
+                                hooks.submit(event.without_secret());
+                            }
+                            
+                            let secret = if show_secret { private_key.as_str() } else { "（按私钥策略不显示）" };
+                            if let Some(template) = &announce {
+                                status!("{}", template.render(&Announcement { secret: Some(secret), ..announcement }));
+                            } else {
+                                let mut actual = event.matched_text.as_ref().map_or_else(String::new, |text| format!("（实际为 {}）", text));
+                                if let Some(position) = event.position {
+                                    actual += &format!("（位置 {}）", position);
+                                }
+                                let secret = if console_secret { format!("私钥: {}\n", secret) } else { String::new() };
+                                status!(
+                                    "{}找到匹配{} '{}'{} 的地址！({}/{})\n序号: {}\n时间: {}\n公钥: {}\n{}",
+                                    if plain_output { "" } else { "✓ " },
+                                    target.kind(), target, actual, found[idx], target.count, counter, time_str, public_key, secret
+                                );
+                            }
+                        }
+                        
+                        if written == 0 {
+                            continue;
+                        }
+                        
+                        if credited.len() < satisfied.len() {
+                            status!(
+                                "该公钥同时满足 {}，按归属策略 {} 只计入 {}\n",
+                                matched_patterns.join(", "),
+                                attribution.name(),
+                                credited.iter().map(|&idx| targets[idx].to_string()).collect::<Vec<_>>().join(", ")
+                            );
+                        }
+                        
+                        if write_keypair_files {
+                            let path = output_dir.join(format!("{}.json", public_key));
+                            secrets::write_keypair_file(&path, &private_key)?;
+                            status!("已写出密钥文件: {}", path.display());
+                        }
+                        
+                        // 检查是否所有目标都已找到
+                        let remaining: Vec<String> = targets.iter().zip(found.iter())
+                            .filter(|(t, &n)| n < t.count)
+                            .map(|(t, &n)| format!("{} ({}/{})", t, n, t.count))
+                            .collect();
+                        if remaining.is_empty() && keep_counting.is_some() {
+                            status!(
+                                "{}所有目标都已找到，继续生成直到共 {} 个密钥对\n",
+                                if plain_output { "" } else { "🎉 " },
+                                keep_counting.unwrap_or_default()
+                            );
+                        } else if remaining.is_empty() && on_complete == OnComplete::Stop {
+                            status!("{}所有目标都已找到！", if plain_output { "" } else { "🎉 " });
+                            all_found.store(true, Ordering::Relaxed);
+                            result_writer.finalize()?;
+                            break;
+                        } else if remaining.is_empty() && on_complete == OnComplete::Idle {
+                            status!("{}所有目标都已找到，停止生成并等待追加目标或停止请求\n", if plain_output { "" } else { "🎉 " });
+                            idle.store(true, Ordering::Relaxed);
+                        } else if remaining.is_empty() && !collecting {
+                            status!("{}所有目标都已找到，继续收集更多结果直到收到停止请求\n", if plain_output { "" } else { "🎉 " });
+                            collecting = true;
+                            *matcher.write().unwrap_or_else(|e| e.into_inner()) =
+                                Arc::new(Matcher::with_active(&targets, &(0..targets.len()).collect::<Vec<_>>(), ignore_case));
+                            matcher_version.fetch_add(1, Ordering::Release);
+                            continue;
+                        } else if !remaining.is_empty() {
+                            status!("剩余目标: {:?}\n", remaining);
+                        }
+                        
+                        // 有目标刚刚找够数量：后续只匹配剩余目标，剪枝和部分编码都会更有效；继续收集时所有目标都保留
+                        if !collecting && credited.iter().any(|&idx| found[idx] == targets[idx].count) {
+                            let active: Vec<usize> = (0..targets.len())
+                                .filter(|&idx| found[idx] < targets[idx].count)
+                                .collect();
+                            *matcher.write().unwrap_or_else(|e| e.into_inner()) =
+                                Arc::new(Matcher::with_active(&targets, &active, ignore_case));
+                            matcher_version.fetch_add(1, Ordering::Release);
+                        }
+                    }
+                    Ok(LogMessage::Regular { .. }) => {
+                        // 结果 channel 不应该收到常规日志
+                    }
+                    Err(_) => {
+                        // Channel关闭
+                        result_writer.finalize()?;
+                        break;
+                    }
+                }
+            }
+            Ok(())
+        })
+    };
+    
+    // 可选的公钥碰撞索引
+    let collision_index = match &config.collision_index {
+        Some(path) => {
+            let index = CollisionIndex::load_or_create(path, config.collision_capacity)?;
+            status!(
+                "碰撞索引: {}（已记录 {} 个公钥，设计容量 {}）",
+                path.display(), index.inserted(), index.capacity()
+            );
+            Some(Arc::new(index))
+        }
+        None => None,
+    };
+    
+    // 可选的统计端口和进度文件
+    let stats_source = Arc::new(StatsSource {
+        started: clock.now(),
+        clock: Arc::clone(&clock),
+        threads: num_threads,
+        targets: targets.clone(),
+        ignore_case,
+        counter: Arc::clone(&counter),
+        found_counts: Arc::clone(&found_counts),
+        finds: Arc::clone(&recent_finds),
+        config: serde_json::to_value(debug::ConfigDump::new(config)).ok(),
+        finished: Arc::clone(&all_found),
+        idle: Arc::clone(&idle),
+    });
+    if let Some(control) = &config.control {
+        control.attach(Arc::clone(&stats_source));
+    }
+    let control = config.control.clone();
+    let mut stats_handles = Vec::new();
+    if let Some(addr) = &config.stats_addr {
+        stats_handles.push(stats::serve(addr, config.stats_allow_remote, Arc::clone(&stats_source), config.stats_access)?);
+    }
+    if let Some(path) = &config.progress_file {
+        stats_handles.push(stats::write_progress_file(path.clone(), Arc::clone(&stats_source)));
+    }
+    
+    // 可以暂停时，轮询输出目录中的暂停请求（启动前残留的请求不算数），以及控制句柄的停止请求
+    if config.state_file.is_some() || control.is_some() {
+        let request = config.state_file.is_some().then(|| config.output_dir.join(state::SUSPEND_REQUEST));
+        if let Some(request) = &request {
+            let _ = fs::remove_file(request);
+        }
+        let saves_state = request.is_some();
+        let control = control.clone();
+        let all_found = Arc::clone(&all_found);
+        let suspended = Arc::clone(&suspended);
+        stats_handles.push(thread::spawn(move || {
+            while !all_found.load(Ordering::Relaxed) {
+                if let Some(request) = request.as_ref().filter(|r| r.exists()) {
+                    let _ = fs::remove_file(request);
+                    status!("收到暂停请求，正在停止并保存状态...");
+                    suspended.store(true, Ordering::Relaxed);
+                    break;
+                }
+                if control.as_ref().is_some_and(RunControl::stop_requested) {
+                    status!("收到停止请求，正在停止{}...", if saves_state { "并保存状态" } else { "" });
+                    suspended.store(true, Ordering::Relaxed);
+                    break;
+                }
+                thread::sleep(SUSPEND_POLL_INTERVAL);
+            }
+        }));
+    }
+    
+    // 收到请求时写出调试转储；启动前残留的请求不算数
+    if config.debug_dump {
+        let request = config.output_dir.join(debug::DUMP_REQUEST);
+        let _ = fs::remove_file(&request);
+        let config_dump = debug::ConfigDump::new(config);
+        let output_dir = config.output_dir.clone();
+        let run_id = run_id.clone();
+        let resumed_attempts = resume.attempts;
+        let patterns: Vec<(String, u64)> = config.targets.iter().map(|t| (t.to_string(), t.count)).collect();
+        let control = control.clone();
+        let counter = Arc::clone(&counter);
+        let found_counts = Arc::clone(&found_counts);
+        let matcher = Arc::clone(&matcher);
+        let matcher_version = Arc::clone(&matcher_version);
+        let duplicate_results = Arc::clone(&duplicate_results);
+        let pending_results = Arc::clone(&pending_results);
+        let log_queue = Arc::clone(&log_queue);
+        let workers = Arc::clone(&workers);
+        let all_found = Arc::clone(&all_found);
+        let idle = Arc::clone(&idle);
+        let suspended = Arc::clone(&suspended);
+        let clock = Arc::clone(&clock);
+        stats_handles.push(thread::spawn(move || {
+            while !all_found.load(Ordering::Relaxed) && !suspended.load(Ordering::Relaxed) {
+                thread::sleep(SUSPEND_POLL_INTERVAL);
+                let requested = request.exists() && fs::remove_file(&request).is_ok();
+                if !(requested || control.as_ref().is_some_and(RunControl::take_dump_request)) {
+                    continue;
+                }
+                let found = found_counts.lock().unwrap_or_else(|e| e.into_inner()).clone();
+                let dump = DebugDump {
+                    timestamp: event::now_timestamp(),
+                    run_id: run_id.clone(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                    elapsed_secs: clock.now().duration_since(started).as_secs_f64(),
+                    config: config_dump.clone(),
+                    patterns: patterns.iter().zip(&found).enumerate()
+                        .map(|(index, ((pattern, count), &found))| debug::PatternDump {
+                            index, pattern: pattern.clone(), found, count: *count, active: found < *count,
+                        })
+                        .collect(),
+                    matcher: matcher.read().unwrap_or_else(|e| e.into_inner()).layout(),
+                    matcher_version: matcher_version.load(Ordering::Acquire),
+                    counters: debug::CounterDump {
+                        attempts: counter.load(Ordering::Relaxed),
+                        worker_attempts: workers.iter().map(|w| w.generated.load(Ordering::Relaxed)).sum(),
+                        resumed_attempts,
+                        duplicate_results: duplicate_results.load(Ordering::Relaxed),
+                        paused: control.as_ref().is_some_and(RunControl::is_paused),
+                        idle: idle.load(Ordering::Relaxed),
+                        suspended: suspended.load(Ordering::Relaxed),
+                        all_found: all_found.load(Ordering::Relaxed),
+                    },
+                    log_queue: debug::LogQueueDump {
+                        depth: log_queue.queue.len(),
+                        capacity: log_queue.queue.capacity(),
+                        written: log_queue.written.load(Ordering::Relaxed),
+                        closed: log_queue.closed.load(Ordering::Relaxed),
+                        writer_exited: log_queue.consumer_gone.load(Ordering::Relaxed),
+                        abandoned: log_queue.abandoned.load(Ordering::Relaxed),
+                    },
+                    results: debug::ResultsDump {
+                        channel_bound: result_channel_bound,
+                        pending: pending_results.load(Ordering::Relaxed),
+                        written: found.iter().sum(),
+                    },
+                    workers: workers.iter().enumerate()
+                        .map(|(thread, w)| debug::WorkerDump {
+                            thread,
+                            generated: w.generated.load(Ordering::Relaxed),
+                            batch_size: w.batch_size.load(Ordering::Relaxed),
+                        })
+                        .collect(),
+                };
+                match debug::write(&output_dir, &dump) {
+                    Ok(path) => status!("调试转储已写入 {}", path.display()),
+                    Err(e) => eprintln!("警告: 写入调试转储失败: {}", e),
+                }
+            }
+        }));
+    }
+    
+    // 生成数量每越过 milestone_every 的一个整数倍就通知一次
+    if let (Some(every), Some(callback)) = (config.milestone_every, &on_event) {
+        let counter = Arc::clone(&counter);
+        let all_found = Arc::clone(&all_found);
+        let callback = Arc::clone(callback);
+        let run_id = run_id.clone();
+        stats_handles.push(thread::spawn(move || {
+            let mut reached = counter.load(Ordering::Relaxed) / every;
+            while !all_found.load(Ordering::Relaxed) {
+                thread::sleep(MILESTONE_POLL_INTERVAL);
+                let attempts = counter.load(Ordering::Relaxed);
+                if attempts / every > reached {
+                    reached = attempts / every;
+                    callback(&RunEvent::Milestone {
+                        timestamp: event::now_timestamp(),
+                        run_id: run_id.clone(),
+                        attempts: reached * every,
+                    });
+                }
+            }
+        }));
+    }
+    
+    // 每秒输出一行进度：生成总数、最近一个窗口内的平均速度，以及按这个速度最难的剩余目标还需多久（中位数）
+    if let Some(window) = config.rate_window {
+        let control = control.clone();
+        let clock = Arc::clone(&clock);
+        let analyses: Vec<_> = config.targets.iter().map(|t| (difficulty::analyze(t, ignore_case), t.count)).collect();
+        let found_counts = Arc::clone(&found_counts);
+        let counter = Arc::clone(&counter);
+        let all_found = Arc::clone(&all_found);
+        let suspended = Arc::clone(&suspended);
+        let idle = Arc::clone(&idle);
+        stats_handles.push(thread::spawn(move || {
+            let mut meter = RateMeter::new(window, clock.now(), counter.load(Ordering::Relaxed));
+            let window = units::rate_window_name(window);
+            while !all_found.load(Ordering::Relaxed) && !suspended.load(Ordering::Relaxed) {
+                thread::sleep(TICKER_INTERVAL);
+                if idle.load(Ordering::Relaxed) || control.as_ref().is_some_and(RunControl::is_paused) {
+                    continue;
+                }
+                let attempts = counter.load(Ordering::Relaxed);
+                let rate = meter.record(clock.now(), attempts);
+                let hardest = {
+                    let found = found_counts.lock().unwrap_or_else(|e| e.into_inner());
+                    analyses.iter().zip(found.iter())
+                        .map(|((analysis, count), &n)| (analysis, analysis.median_attempts(count.saturating_sub(n))))
+                        .filter(|&(_, attempts)| attempts > 0.0)
+                        .max_by(|a, b| a.1.total_cmp(&b.1))
+                };
+                let hardest = match hardest {
+                    Some((analysis, remaining)) if rate > 0.0 => format!(
+                        " | 最难的剩余目标 '{}' 按当前速度约需 {}（中位数）", analysis.pattern, units::duration(remaining / rate)
+                    ),
+                    _ => String::new(),
+                };
+                progress!(
+                    "已生成 {} 个 | 最近 {} 平均 {} 个/秒{}",
+                    count_format.count(attempts), window, count_format.rate(rate), hardest
+                );
+            }
+        }));
+    }
+    
+    // 停滞检测：每秒采样计数器，用最近一个窗口的平均速度与校准速度比较
+    if let Some(detection) = config.stall_detection {
+        let clock = Arc::clone(&clock);
+        let counter = Arc::clone(&counter);
+        let all_found = Arc::clone(&all_found);
+        let suspended = Arc::clone(&suspended);
+        let log_queue = Arc::clone(&log_queue);
+        let pending_results = Arc::clone(&pending_results);
+        let on_event = on_event.clone();
+        let run_id = run_id.clone();
+        let session = session.clone();
+        let control = control.clone();
+        let idle = Arc::clone(&idle);
+        stats_handles.push(thread::spawn(move || {
+            let mut samples = VecDeque::from([(clock.now(), counter.load(Ordering::Relaxed))]);
+            let mut calibrated = 0.0f64;
+            let mut slow_since: Option<Instant> = None;
+            let mut reported = false;
+            while !all_found.load(Ordering::Relaxed) && !suspended.load(Ordering::Relaxed) {
+                thread::sleep(STALL_SAMPLE_INTERVAL);
+                let now = clock.now();
+                // 暂停或空闲期间不算停滞，继续后重新积累一个窗口
+                if idle.load(Ordering::Relaxed) || control.as_ref().is_some_and(RunControl::is_paused) {
+                    samples = VecDeque::from([(now, counter.load(Ordering::Relaxed))]);
+                    slow_since = None;
+                    continue;
+                }
+                samples.push_back((now, counter.load(Ordering::Relaxed)));
+                if samples.len() > STALL_RATE_WINDOW + 1 {
+                    samples.pop_front();
+                }
+                if samples.len() <= STALL_RATE_WINDOW {
+                    continue;
+                }
+                let (start, first) = samples[0];
+                let rate = (samples[STALL_RATE_WINDOW].1 - first) as f64 / now.duration_since(start).as_secs_f64();
+                if rate >= calibrated * detection.fraction {
+                    if let Some(session) = session.as_ref().filter(|_| calibrated == 0.0 && rate > 0.0) {
+                        session.record(&SessionEvent::Calibrated { keys_per_sec: rate });
+                    }
+                    calibrated = calibrated.max(rate);
+                    slow_since = None;
+                    reported = false;
+                    continue;
+                }
+                let stalled = now.duration_since(*slow_since.get_or_insert(now));
+                if reported || stalled < detection.after {
+                    continue;
+                }
+                reported = true;
+                let log_queue_depth = log_queue.queue.len();
+                let pending = pending_results.load(Ordering::Relaxed);
+                eprintln!(
+                    "警告: 速度已持续 {} 秒低于校准速度的 {:.0}%（当前 {:.0} 个/秒，校准 {:.0} 个/秒）；\
+                     常规日志队列 {}/{}，待写入结果 {}。队列接近满时通常是写入线程卡住（例如磁盘或网络文件系统）",
+                    stalled.as_secs(), detection.fraction * 100.0, rate, calibrated,
+                    log_queue_depth, log_queue.queue.capacity(), pending
+                );
+                if let Some(callback) = &on_event {
+                    callback(&RunEvent::Stalled {
+                        timestamp: event::now_timestamp(),
+                        run_id: run_id.clone(),
+                        keys_per_sec: rate,
+                        calibrated_keys_per_sec: calibrated,
+                        stalled_secs: stalled.as_secs(),
+                        log_queue_depth,
+                        log_queue_capacity: log_queue.queue.capacity(),
+                        pending_results: pending,
+                    });
+                }
+            }
+        }));
+    }
+    
+    // 速度基线检查：预热之后测一个窗口的平均速度，明显慢于基线时醒目地警告
+    if let Some(baseline) = config.speed_baseline {
+        let clock = Arc::clone(&clock);
+        let counter = Arc::clone(&counter);
+        let all_found = Arc::clone(&all_found);
+        let suspended = Arc::clone(&suspended);
+        let on_event = on_event.clone();
+        let run_id = run_id.clone();
+        let control = control.clone();
+        let idle = Arc::clone(&idle);
+        stats_handles.push(thread::spawn(move || {
+            let started = clock.now();
+            let mut window_start: Option<(Instant, u64)> = None;
+            while !all_found.load(Ordering::Relaxed) && !suspended.load(Ordering::Relaxed) {
+                thread::sleep(STALL_SAMPLE_INTERVAL);
+                let now = clock.now();
+                // 暂停会拉低窗口速度，继续后重新开始测量
+                if idle.load(Ordering::Relaxed) || control.as_ref().is_some_and(RunControl::is_paused) {
+                    window_start = None;
+                    continue;
+                }
+                let Some((since, first)) = window_start else {
+                    if now.duration_since(started) >= BASELINE_WARMUP {
+                        window_start = Some((now, counter.load(Ordering::Relaxed)));
+                    }
+                    continue;
+                };
+                if now.duration_since(since) < BASELINE_WINDOW {
+                    continue;
+                }
+                let rate = counter.load(Ordering::Relaxed).saturating_sub(first) as f64 / now.duration_since(since).as_secs_f64();
+                if rate >= baseline.keys_per_sec * (1.0 - baseline.tolerance) {
+                    status!("速度 {:.0} 个/秒，与基线 {:.0} 个/秒相符", rate, baseline.keys_per_sec);
+                    break;
+                }
+                let debug_build = console::debug_build();
+                let banner = "!".repeat(72);
+                eprintln!("\n{}", banner);
+                eprintln!(
+                    "警告: 当前速度 {:.0} 个/秒，比基线 {:.0} 个/秒慢 {:.0}%（容许 {:.0}%）",
+                    rate, baseline.keys_per_sec, (1.0 - rate / baseline.keys_per_sec) * 100.0, baseline.tolerance * 100.0
+                );
+                if debug_build {
+                    eprintln!("当前程序是调试构建，速度通常比发布构建慢 {}：请用 cargo run --release 重新运行", console::DEBUG_SLOWDOWN);
+                } else {
+                    eprintln!("请检查线程数、CPU 频率和机器上的其他负载，以及是否开启了额外的输出（与基线运行的配置对比）");
+                }
+                eprintln!("{}\n", banner);
+                if let Some(callback) = &on_event {
+                    callback(&RunEvent::BelowBaseline {
+                        timestamp: event::now_timestamp(),
+                        run_id: run_id.clone(),
+                        keys_per_sec: rate,
+                        baseline_keys_per_sec: baseline.keys_per_sec,
+                        tolerance: baseline.tolerance,
+                        debug_build,
+                    });
+                }
+                break;
+            }
+        }));
+    }
+    
+    // 截止时间检查：每隔一段时间按这段时间的平均速度预测各目标找够数量的时间，赶不上时警告
+    if targets.iter().any(|t| t.deadline.is_some()) {
+        let clock = Arc::clone(&clock);
+        let counter = Arc::clone(&counter);
+        let all_found = Arc::clone(&all_found);
+        let suspended = Arc::clone(&suspended);
+        let found_counts = Arc::clone(&found_counts);
+        let targets = config.targets.clone();
+        let on_event = on_event.clone();
+        let run_id = run_id.clone();
+        stats_handles.push(thread::spawn(move || {
+            let mut last = (clock.now(), counter.load(Ordering::Relaxed));
+            let mut reported = vec![false; targets.len()];
+            while !all_found.load(Ordering::Relaxed) && !suspended.load(Ordering::Relaxed) {
+                thread::sleep(STALL_SAMPLE_INTERVAL);
+                let now = clock.now();
+                if now.duration_since(last.0) < DEADLINE_CHECK_INTERVAL {
+                    continue;
+                }
+                let attempts = counter.load(Ordering::Relaxed);
+                let rate = attempts.saturating_sub(last.1) as f64 / now.duration_since(last.0).as_secs_f64();
+                last = (now, attempts);
+                if rate <= 0.0 {
+                    continue;
+                }
+                let found = found_counts.lock().unwrap_or_else(|e| e.into_inner()).clone();
+                let wall_now = clock.local().fixed_offset();
+                for (idx, target) in targets.iter().enumerate() {
+                    let Some(deadline) = target.deadline else { continue };
+                    let Some((projected, required)) = deadline_at_risk(target, found[idx], rate, wall_now, ignore_case) else {
+                        reported[idx] = false;
+                        continue;
+                    };
+                    if reported[idx] {
+                        continue;
+                    }
+                    reported[idx] = true;
+                    eprintln!(
+                        "警告: 按当前速度 {:.0} 个/秒，目标 '{}' 预计在 {} 才能找够，晚于截止时间 {}{}；\
+                         可以增加线程或机器，或放宽该目标（缩短前缀或后缀、使用 --ignore-case）",
+                        rate, target, projected.format("%Y-%m-%d %H:%M:%S"), deadline.format("%Y-%m-%d %H:%M:%S"),
+                        required.map_or_else(|| "（截止时间已过）".to_string(), |r| format!("（需要约 {:.0} 个/秒）", r))
+                    );
+                    if let Some(callback) = &on_event {
+                        callback(&RunEvent::DeadlineAtRisk {
+                            timestamp: event::now_timestamp(),
+                            run_id: run_id.clone(),
+                            pattern: target.to_string(),
+                            deadline: deadline.to_rfc3339_opts(SecondsFormat::Secs, false),
+                            projected: projected.to_rfc3339_opts(SecondsFormat::Secs, false),
+                            keys_per_sec: rate,
+                            required_keys_per_sec: required,
+                        });
+                    }
+                }
+            }
+        }));
+    }
+    
+    // 启动工作线程；主机名记入每个结果的来源
+    let host: Arc<str> = report::hostname().into();
+    let mut handles = Vec::new();
+    for thread_id in 0..num_threads {
+        let host = Arc::clone(&host);
+        let clock = Arc::clone(&clock);
+        let counter = Arc::clone(&counter);
+        let attempts_claimed = Arc::clone(&attempts_claimed);
+        let all_found = Arc::clone(&all_found);
+        let suspended = Arc::clone(&suspended);
+        let log_queue = Arc::clone(&log_queue);
+        let result_tx = result_tx.clone();
+        let pending_results = Arc::clone(&pending_results);
+        let shared_matcher = Arc::clone(&matcher);
+        let matcher_version = Arc::clone(&matcher_version);
+        let collision_index = collision_index.clone();
+        let backend = Arc::clone(&config.backend);
+        let output_dir = config.output_dir.clone();
+        let session = session.clone();
+        let control = control.clone();
+        let idle = Arc::clone(&idle);
+        let workers = Arc::clone(&workers);
+        let mut batch = BatchSizer::new(config.max_result_delay);
+        
+        // 返回放入常规日志队列的条数，以及写分片日志时遇到的错误
+        let handle = thread::spawn(move || -> (u64, Option<std::io::Error>) {
+            priority::enter_worker(io_priority);
+            let mut local_counter = 0u64;
+            // 本线程放入常规日志队列的条数
+            let mut pushed = 0u64;
+            // 分片日志出错后本线程不再写日志，错误在运行结束时返回
+            let mut shard_error = None;
+            let mut shard = if shard_logs {
+                let stem = shard_stem(thread_id);
+                match LogWriter::new(&output_dir, &stem, next_shard_index(&output_dir, &stem), 0, log_sink) {
+                    Ok(writer) => Some(writer.with_session(session)),
+                    Err(e) => {
+                        eprintln!("错误: 线程 {} 无法创建分片日志: {}", thread_id, e);
+                        shard_error = Some(e);
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            let mut generator = backend(thread_id);
+            let source = Provenance {
+                backend: generator.name().to_string(),
+                worker: thread_id,
+                host: host.to_string(),
+                rng: Some(generator.rng_policy()),
+                checks: Vec::new(),
+            };
+            // 批次缓冲区按上限分配一次并在线程内复用，避免每个公钥分配一次内存
+            let mut seeds = vec![[0u8; 32]; KEYGEN_BATCH_MAX];
+            let mut public_keys = vec![[0u8; 32]; KEYGEN_BATCH_MAX];
+            let mut encoded = EncodedKey::default();
+            let mut local_version = matcher_version.load(Ordering::Acquire);
+            let mut matcher = Arc::clone(&shared_matcher.read().unwrap_or_else(|e| e.into_inner()));
+            // 只有前缀目标时，匹配只需编码出最长前缀那么多个字符
+            let mut prefix_encoder = matcher.encoded_prefix_len().map(PrefixEncoder::new);
+            let mut partial = EncodedKey::default();
+            
+            loop {
+                // 检查是否所有目标都已找到，或者收到了暂停请求
+                if all_found.load(Ordering::Relaxed) || suspended.load(Ordering::Relaxed) {
+                    break;
+                }
+                if keep_counting.is_some_and(|limit| counter.load(Ordering::Relaxed) >= limit) {
+                    break;
+                }
+                if attempt_limit.is_some_and(|limit| counter.load(Ordering::Relaxed) >= limit) || time_limit.is_some_and(|limit| clock.now() >= limit) {
+                    break;
+                }
+                // 暂停或空闲时不生成，但仍及时响应结束和停止
+                if idle.load(Ordering::Relaxed) || control.as_ref().is_some_and(RunControl::is_paused) {
+                    thread::sleep(PAUSE_POLL_INTERVAL);
+                    continue;
+                }
+                
+                // 目标集合已更新（有目标找够了数量），换用新的集合
+                let version = matcher_version.load(Ordering::Acquire);
+                if version != local_version {
+                    local_version = version;
+                    matcher = Arc::clone(&shared_matcher.read().unwrap_or_else(|e| e.into_inner()));
+                    prefix_encoder = matcher.encoded_prefix_len().map(PrefixEncoder::new);
+                }
+                
+                // 由后端生成一批新的密钥对
+                let batch_started = clock.now();
+                let mut size = batch.size;
+                if let Some(limit) = attempt_limit {
+                    let claimed = attempts_claimed.fetch_add(size as u64, Ordering::Relaxed);
+                    if claimed >= limit {
+                        break;
+                    }
+                    size = size.min((limit - claimed) as usize);
+                }
+                generator.generate(&mut seeds[..size], &mut public_keys[..size]);
+                
+                // 极速模式：计数器每批只更新一次，不写常规日志，也不输出进度
+                let batch_start = if raw_speed {
+                    counter.fetch_add(size as u64, Ordering::Relaxed)
+                } else {
+                    0
+                };
+                
+                for (i, (seed, public_key)) in seeds[..size].iter().zip(public_keys[..size].iter()).enumerate() {
+                    if let Some(index) = &collision_index {
+                        if index.check_and_insert(public_key) {
+                            eprintln!("警告: 公钥 {} 可能与之前生成的公钥重复（碰撞索引命中）", encode_public_key(public_key));
+                        }
+                    }
+                    
+                    let global_counter = if raw_speed {
+                        batch_start + i as u64 + 1
+                    } else {
+                        // 原子递增计数器
+                        counter.fetch_add(1, Ordering::Relaxed) + 1
+                    };
+                    local_counter += 1;
+                    
+                    // 检查公钥匹配了哪些目标；未通过区间剪枝的公钥无需编码
+                    let matched = if !matcher.may_match(public_key) {
+                        Vec::new()
+                    } else {
+                        if prefix_encoder.is_none() {
+                            base58::encode_32(public_key, &mut encoded);
+                        }
+                        match_key(&matcher, prefix_encoder.as_ref(), &mut partial, public_key, encoded.as_str(), ignore_case)
+                    };
+                    
+                    if !matched.is_empty() {
+                        // 发送找到的消息到结果 channel
+                        pending_results.fetch_add(1, Ordering::Relaxed);
+                        let _ = result_tx.send(LogMessage::Found {
+                            timestamp_ms: clock.unix_millis(),
+                            counter: global_counter,
+                            public_key: *public_key,
+                            secret_key: backend::secret_key(seed, public_key),
+                            matched,
+                            source: source.clone(),
+                        });
+                    } else if !raw_speed {
+                        // 发送常规日志消息；分片时直接写本线程的日志文件
+                        if let Some(writer) = &mut shard {
+                            let line = regular_log_line(clock.unix_millis(), global_counter, public_key, &backend::secret_key(seed, public_key));
+                            if let Err(e) = writer.write_line(&line) {
+                                eprintln!("错误: 线程 {} 写分片日志失败，本线程不再写日志: {}", thread_id, e);
+                                shard_error = Some(e);
+                                shard = None;
+                            }
+                        } else if shared_logs {
+                            log_queue.push(LogMessage::Regular {
+                                timestamp_ms: clock.unix_millis(),
+                                counter: global_counter,
+                                public_key: *public_key,
+                                secret_key: backend::secret_key(seed, public_key),
+                            });
+                            pushed += 1;
+                        }
+                        
+                        // 控制台输出简化版本（每1000条输出一次，避免刷屏）
+                        if thread_progress && global_counter.is_multiple_of(1000) {
+                            progress!("[线程 {}] 已生成 {} 条记录 (本线程生成了 {} 条)", 
+                                    thread_id, count_format.count(global_counter), count_format.count(local_counter));
+                        }
+                    }
+                }
+                batch.adjust(
+                    clock.now().duration_since(batch_started),
+                    pending_results.load(Ordering::Relaxed) > 0 || (shared_logs && log_queue.backlogged()),
+                );
+                workers[thread_id].generated.store(local_counter, Ordering::Relaxed);
+                workers[thread_id].batch_size.store(batch.size, Ordering::Relaxed);
+            }
+            if let Some(Err(e)) = shard.as_mut().map(LogWriter::finalize) {
+                shard_error.get_or_insert(e);
+            }
+            (pushed, shard_error)
+        });
+        
+        handles.push(handle);
+    }
+    
+    // 等待所有工作线程完成
+    let mut pushed_logs = 0u64;
+    let mut shard_error = None;
+    for handle in handles {
+        let (pushed, error) = handle.join().map_err(|_| GrindError::Backend("工作线程异常退出".to_string()))?;
+        pushed_logs += pushed;
+        shard_error = shard_error.or(error);
+    }
+    
+    // 关闭队列和 channel，通知写入线程退出
+    log_queue.close();
+    drop(result_tx);
+    
+    // 写入线程有一段宽限期取完队列；超时后不再等待卡住的写入（例如挂起的网络文件系统），
+    // 而是放弃剩余的常规日志并如实报告丢弃的条数
+    let deadline = Instant::now() + config.shutdown_grace;
+    if !wait_finished(&log_writer_handle, deadline) {
+        log_queue.abandoned.store(true, Ordering::Release);
+        wait_finished(&log_writer_handle, Instant::now() + ABANDON_WAIT);
+    }
+    let (log_file_index, log_line_count) = if log_writer_handle.is_finished() {
+        log_writer_handle.join().map_err(|_| GrindError::Backend("日志写入线程异常退出".to_string()))??
+    } else {
+        eprintln!("警告: 日志写入线程在宽限期后仍未结束，不再等待");
+        (resume.log_file_index, resume.log_line_count)
+    };
+    let dropped_logs = pushed_logs.saturating_sub(log_queue.written.load(Ordering::Relaxed));
+    if dropped_logs > 0 {
+        eprintln!("警告: 停止时丢弃了 {} 条尚未写入的常规日志", dropped_logs);
+    }
+    
+    // 结果不能放弃，但同样不无限等待：超时后报告，结果写入线程留在后台继续
+    let results_finished = wait_finished(&result_writer_handle, deadline.max(Instant::now()));
+    // 已排队的钩子执行完再返回；结果写入线程仍在后台时它还持有钩子队列，不再等待
+    if let (true, Some(runner)) = (results_finished, hook_runner) {
+        runner.finish();
+    }
+    if results_finished {
+        result_writer_handle.join().map_err(|_| GrindError::Backend("结果写入线程异常退出".to_string()))??;
+    } else {
+        eprintln!("警告: 结果写入线程在宽限期内未能结束，最后的结果可能尚未写入");
+    }
+    
+    all_found.store(true, Ordering::Relaxed);
+    for handle in stats_handles {
+        handle.join().map_err(|_| GrindError::Backend("统计线程异常退出".to_string()))?;
+    }
+    
+    let suspected_collisions = match (&collision_index, &config.collision_index) {
+        (Some(index), Some(path)) => {
+            index.save(path)?;
+            if index.inserted() > index.capacity() {
+                eprintln!(
+                    "警告: 碰撞索引已记录 {} 个公钥，超过设计容量 {}，误报率会明显上升",
+                    index.inserted(), index.capacity()
+                );
+            }
+            Some(index.suspected())
+        }
+        _ => None,
+    };
+    
+    if let Some(e) = shard_error {
+        return Err(e.into());
+    }
+    
+    let found = found_counts.lock().unwrap_or_else(|e| e.into_inner());
+    let satisfied_at = satisfied_at.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let satisfied_after = satisfied_after.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let elapsed = clock.now().duration_since(started);
+    let attempts = counter.load(Ordering::Relaxed);
+    let suspended = suspended.load(Ordering::Relaxed);
+    let all_satisfied = targets.iter().zip(found.iter()).all(|(t, &n)| n >= t.count);
+    let exhausted = if suspended || all_satisfied {
+        None
+    } else if attempt_limit.is_some_and(|limit| attempts >= limit) {
+        Some(Budget::Attempts)
+    } else if time_limit.is_some_and(|limit| clock.now() >= limit) {
+        Some(Budget::Time)
+    } else {
+        None
+    };
+    // 达到上限时同样保存状态，之后可以接着找没有找够的目标
+    if let (true, Some(path)) = (suspended || exhausted.is_some(), &config.state_file) {
+        let state = RunState {
+            version: state::STATE_VERSION,
+            run_id: run_id.clone(),
+            attempts,
+            // 保存用户给出的原始目标（忽略大小写时匹配用的是小写形式）
+            targets: config.targets.iter().enumerate()
+                .map(|(idx, target)| TargetState {
+                    pattern: target.to_string(),
+                    count: target.count,
+                    deadline: target.deadline.map(|d| d.to_rfc3339_opts(SecondsFormat::Secs, false)),
+                    found: found[idx],
+                    satisfied_at: satisfied_at[idx],
+                })
+                .collect(),
+            num_threads,
+            ignore_case,
+            split_output,
+            encrypt_secrets: config.secret_passphrase.is_some(),
+            write_keypair_files,
+            raw_speed,
+            low_memory: config.low_memory,
+            shard_logs,
+            result_format,
+            attribution,
+            secret_policy: config.secret_policy,
+            hooks: config.hooks.iter()
+                .filter_map(|hook| match hook {
+                    Hook::Command(command) => Some(command.clone()),
+                    Hook::Callback { .. } => None,
+                })
+                .collect(),
+            hook_timeout_secs: config.hook_timeout.as_secs(),
+            keep_counting,
+            on_complete,
+            output_dir: config.output_dir.clone(),
+            dedup_db: config.dedup_db.clone(),
+            rng: (config.backend)(0).rng_policy(),
+            log_file_index,
+            log_line_count,
+        };
+        state.save(path)?;
+        status!("状态已保存到 {}，可用 resume-from 继续", path.display());
+        if let (true, Some(session)) = (suspended, &session) {
+            session.record(&SessionEvent::Suspended { attempts, state_file: path.display().to_string() });
+        }
+    }
+    let complete = !suspended && all_satisfied;
+    if let (true, Some(callback)) = (complete, &on_event) {
+        callback(&RunEvent::Complete {
+            timestamp: event::now_timestamp(),
+            run_id: run_id.clone(),
+            attempts,
+            found: found.iter().sum(),
+        });
+    }
+    Ok(GrindSummary {
+        attempts,
+        dropped_logs,
+        found: targets.iter().cloned().zip(found.iter().copied()).collect(),
+        suspected_collisions,
+        duplicate_results: duplicate_results.load(Ordering::Relaxed),
+        run_id,
+        satisfied_at,
+        suspended,
+        elapsed,
+        keys_per_sec: attempts.saturating_sub(resume.attempts) as f64 / elapsed.as_secs_f64().max(f64::MIN_POSITIVE),
+        satisfied_after,
+        exhausted,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, MockClock};
+    use crate::target;
+
+    #[test]
+    fn deadline_projection_follows_mock_clock() {
+        let clock = MockClock::new(1_700_000_000_000);
+        let deadline = target::parse_deadline("1h", &clock).unwrap();
+        assert_eq!(deadline, clock.local().fixed_offset() + chrono::Duration::hours(1));
+        let target = Target { count: 2, deadline: Some(deadline), ..Target::with_prefix("abc") };
+        let per_key = target.expected_attempts(false);
+
+        // 还差 2 个，半小时内就能找够
+        assert!(deadline_at_risk(&target, 0, 2.0 * per_key / 1800.0, clock.local().fixed_offset(), false).is_none());
+        // 速度只够两小时找够：预计时间晚于截止时间，需要的速度为剩余数量除以剩余的一小时
+        let rate = 2.0 * per_key / 7200.0;
+        let (projected, required) = deadline_at_risk(&target, 0, rate, clock.local().fixed_offset(), false).unwrap();
+        assert_eq!(projected, clock.local().fixed_offset() + chrono::Duration::hours(2));
+        assert!((required.unwrap() - 2.0 * per_key / 3600.0).abs() < 1e-6);
+        // 已经找到 1 个，剩下的一个刚好在截止时间找够
+        assert!(deadline_at_risk(&target, 1, rate, clock.local().fixed_offset(), false).is_none());
+        // 截止时间过后仍没找够，不再给出需要的速度
+        clock.advance(Duration::from_secs(2 * 3600));
+        let (_, required) = deadline_at_risk(&target, 1, rate, clock.local().fixed_offset(), false).unwrap();
+        assert!(required.is_none());
+        // 找够之后不再预测
+        assert!(deadline_at_risk(&target, 2, rate, clock.local().fixed_offset(), false).is_none());
+    }
+}
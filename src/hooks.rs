@@ -0,0 +1,182 @@
+use crate::event::{self, FoundEvent, RunEvent};
+use crate::grinder::EventCallback;
+use crate::priority::{self, IoPriority};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 单个钩子的默认超时
+pub const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+// 等待执行的结果数上限；钩子跟不上时跳过新结果并报告，不拖慢结果写入
+const HOOK_QUEUE_BOUND: usize = 64;
+// 失败报告中保留的标准错误输出末尾字符数
+const STDERR_TAIL: usize = 512;
+// 等待命令退出时的轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// 库调用方的钩子：收到不含私钥的结果，返回错误时作为钩子失败报告
+pub type HookCallback = Arc<dyn Fn(&FoundEvent) -> Result<(), String> + Send + Sync>;
+
+/// 每找到一个结果后执行的动作，例如把地址登记到内部的资产清单系统
+#[derive(Clone)]
+pub enum Hook {
+    /// 用 `sh -c` 执行的命令，标准输入为一行不含私钥的结果 JSON
+    Command(String),
+    /// 库调用方的回调，`name` 用于失败报告
+    Callback { name: String, callback: HookCallback },
+}
+
+impl Hook {
+    fn name(&self) -> &str {
+        match self {
+            Hook::Command(command) => command,
+            Hook::Callback { name, .. } => name,
+        }
+    }
+}
+
+/// 在独立线程中依次为每个结果执行所有钩子。钩子只收到去掉私钥的结果；命令在清空的环境变量
+/// （只保留 `PATH`）和输出目录中执行，超时后被终止；失败时输出警告并产生 [`RunEvent::HookFailed`]
+pub(crate) struct HookRunner {
+    tx: SyncSender<FoundEvent>,
+    handle: thread::JoinHandle<()>,
+}
+
+impl HookRunner {
+    pub(crate) fn start(hooks: Vec<Hook>, timeout: Duration, work_dir: &Path, run_id: String, on_event: Option<EventCallback>, io_priority: IoPriority) -> Self {
+        let (tx, rx) = mpsc::sync_channel::<FoundEvent>(HOOK_QUEUE_BOUND);
+        let work_dir = if work_dir.as_os_str().is_empty() { PathBuf::from(".") } else { work_dir.to_path_buf() };
+        let handle = thread::spawn(move || {
+            priority::enter_io(io_priority);
+            for event in rx {
+                for hook in &hooks {
+                    let result = match hook {
+                        Hook::Command(command) => run_command(command, &event, &work_dir, timeout),
+                        Hook::Callback { callback, .. } => run_callback(callback, &event, timeout),
+                    };
+                    if let Err(message) = result {
+                        report_failure(hook.name(), &event.public_key, &message, &run_id, &on_event);
+                    }
+                }
+            }
+        });
+        HookRunner { tx, handle }
+    }
+
+    pub(crate) fn sender(&self) -> HookSender {
+        HookSender(self.tx.clone())
+    }
+
+    /// 不再接收新结果，等待已排队的钩子执行完（每个钩子最多等待超时时间）
+    pub(crate) fn finish(self) {
+        drop(self.tx);
+        let _ = self.handle.join();
+    }
+}
+
+/// 结果写入线程把结果交给钩子线程的一端
+#[derive(Clone)]
+pub(crate) struct HookSender(SyncSender<FoundEvent>);
+
+impl HookSender {
+    /// 排队执行钩子；钩子线程积压时跳过该结果并输出警告，不阻塞调用方
+    pub(crate) fn submit(&self, event: FoundEvent) {
+        match self.0.try_send(event) {
+            Ok(()) | Err(TrySendError::Disconnected(_)) => {}
+            Err(TrySendError::Full(event)) => {
+                eprintln!("警告: 钩子积压超过 {} 个结果，公钥 {} 不执行钩子", HOOK_QUEUE_BOUND, event.public_key);
+            }
+        }
+    }
+}
+
+fn report_failure(hook: &str, public_key: &str, message: &str, run_id: &str, on_event: &Option<EventCallback>) {
+    eprintln!("警告: 钩子 '{}' 处理公钥 {} 失败: {}", hook, public_key, message);
+    if let Some(callback) = on_event {
+        callback(&RunEvent::HookFailed {
+            timestamp: event::now_timestamp(),
+            run_id: run_id.to_string(),
+            hook: hook.to_string(),
+            public_key: public_key.to_string(),
+            message: message.to_string(),
+        });
+    }
+}
+
+fn run_command(command: &str, event: &FoundEvent, work_dir: &Path, timeout: Duration) -> Result<(), String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(work_dir)
+        // 不把口令等环境变量泄露给钩子；常用字段另外放在环境变量中，方便简单的脚本
+        .env_clear()
+        .env("PATH", std::env::var_os("PATH").unwrap_or_else(|| "/usr/bin:/bin".into()))
+        .env("VANITY_PUBLIC_KEY", &event.public_key)
+        .env("VANITY_PATTERN", &event.pattern)
+        .env("VANITY_RUN_ID", event.run_id.as_deref().unwrap_or_default())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("无法启动: {}", e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // 命令不读标准输入就退出时会 broken pipe，不算失败
+        let _ = writeln!(stdin, "{}", event.to_json());
+    }
+    let stderr = child.stderr.take().map(|mut stderr| {
+        thread::spawn(move || {
+            let mut output = Vec::new();
+            let _ = stderr.read_to_end(&mut output);
+            output
+        })
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("超过 {} 秒未结束，已终止", timeout.as_secs()));
+            }
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(format!("等待退出失败: {}", e)),
+        }
+    };
+    if status.success() {
+        return Ok(());
+    }
+    let output = stderr.and_then(|handle| handle.join().ok()).unwrap_or_default();
+    let output = String::from_utf8_lossy(&output);
+    let output = output.trim();
+    let tail = match output.char_indices().rev().nth(STDERR_TAIL) {
+        Some((idx, _)) => &output[idx..],
+        None => output,
+    };
+    match status.code() {
+        Some(code) if tail.is_empty() => Err(format!("退出码 {}", code)),
+        Some(code) => Err(format!("退出码 {}: {}", code, tail)),
+        None => Err("被信号终止".to_string()),
+    }
+}
+
+// 回调在单独的线程中执行，超时后不再等待（线程留在后台直到回调返回）
+fn run_callback(callback: &HookCallback, event: &FoundEvent, timeout: Duration) -> Result<(), String> {
+    let (tx, rx) = mpsc::channel();
+    let (callback, event) = (Arc::clone(callback), event.clone());
+    thread::spawn(move || {
+        let _ = tx.send(callback(&event));
+    });
+    match rx.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => Err(format!("超过 {} 秒未返回", timeout.as_secs())),
+        Err(RecvTimeoutError::Disconnected) => Err("回调 panic".to_string()),
+    }
+}
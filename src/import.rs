@@ -0,0 +1,206 @@
+use crate::audit::{self, AuditEntry};
+use crate::error::GrindError;
+use crate::event::FoundEvent;
+use crate::grinder::{self, Attribution, ResultFormat, ResultWriter};
+use crate::secrets::SecretWriter;
+use crate::state::{self, RunState};
+use crate::target::Target;
+use crate::watch;
+use chrono::{Local, SecondsFormat};
+use solana_sdk::signature::{Keypair, Signer};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// 导入到哪里：结果文件所在的目录、格式和目标。目录中有暂停时保存的 `state.json` 时，
+/// 目标、大小写、结果格式、归属策略和分离输出都以状态文件为准，导入的结果同时计入它的已找到数量
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    pub dir: PathBuf,
+    pub targets: Vec<Target>,
+    pub ignore_case: bool,
+    pub result_format: ResultFormat,
+    pub attribution: Attribution,
+    pub split_output: bool,
+    pub secret_passphrase: Option<String>,
+}
+
+/// `import-results` 的处理结果
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    /// 从输入中读到的密钥对数量
+    pub read: u64,
+    /// 写入结果文件的数量
+    pub imported: u64,
+    /// 结果存储中已有（或输入中重复）的公钥数量
+    pub duplicates: u64,
+    /// 私钥已加密、无法校验而跳过的数量
+    pub encrypted: u64,
+    /// 不匹配任何目标的公钥数量
+    pub unmatched: u64,
+    /// 未通过校验的条目及原因
+    pub rejected: Vec<(String, String)>,
+    /// 更新了已找到数量的状态文件
+    pub state_updated: Option<PathBuf>,
+}
+
+/// 从目录的 `state.json` 读取暂停的搜索；不存在时为空
+pub fn load_state(dir: &Path) -> Result<Option<RunState>, GrindError> {
+    let path = dir.join(state::STATE_FILE);
+    if path.exists() {
+        RunState::load(&path).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+// 结果文件中已有的公钥
+fn existing_public_keys(path: &Path) -> Result<HashSet<String>, GrindError> {
+    let mut keys = HashSet::new();
+    if !path.exists() {
+        return Ok(keys);
+    }
+    for line in BufReader::new(File::open(path)?).lines() {
+        if let Some(event) = watch::parse_result_entry(&line?) {
+            keys.insert(event.public_key);
+        }
+    }
+    Ok(keys)
+}
+
+// 校验私钥：64 字节，前 32 字节推导出的公钥与后 32 字节及记录中的公钥一致；返回（公钥，私钥）
+fn verify_entry(entry: &AuditEntry) -> Result<(String, String), String> {
+    let bytes = bs58::decode(&entry.secret_key).into_vec()
+        .map_err(|e| format!("私钥不是有效的 base58: {}", e))?;
+    if bytes.len() != 64 {
+        return Err(format!("私钥为 {} 字节，应为 64 字节", bytes.len()));
+    }
+    let seed: [u8; 32] = bytes[..32].try_into().expect("长度已检查");
+    let derived = Keypair::new_from_array(seed).pubkey().to_string();
+    if derived != bs58::encode(&bytes[32..]).into_string() {
+        return Err(format!("私钥前 32 字节推导出的公钥 {} 与后 32 字节不一致", derived));
+    }
+    if let Some(public_key) = entry.public_key.as_ref().filter(|p| **p != derived) {
+        return Err(format!("记录中的公钥 {} 与私钥推导出的公钥 {} 不一致", public_key, derived));
+    }
+    Ok((derived, entry.secret_key.clone()))
+}
+
+/// 把 `input`（solana-keygen 的 JSON 密钥文件、包含它们的目录，或本工具支持的各种日志、结果文件）中的密钥对
+/// 校验后导入结果存储：与搜索得到的结果一样写入结果文件和 `found_public.jsonl`（分离输出时私钥写入私钥文件），
+/// 已有的公钥不会重复写入。一个公钥匹配多个目标时按归属策略计入，与搜索时的语义相同
+pub fn import_results(input: &Path, options: &ImportOptions) -> Result<ImportReport, GrindError> {
+    let mut report = ImportReport::default();
+    let state = load_state(&options.dir)?;
+    let state_path = options.dir.join(state::STATE_FILE);
+    let options = match &state {
+        Some(state) => {
+            if state.encrypt_secrets && options.secret_passphrase.is_none() {
+                return Err(GrindError::Config(format!(
+                    "{} 中的运行加密了私钥，导入时需要通过环境变量提供口令", state_path.display()
+                )));
+            }
+            ImportOptions {
+                targets: state.targets()?,
+                ignore_case: state.ignore_case,
+                result_format: state.result_format,
+                attribution: state.attribution,
+                split_output: state.split_output,
+                secret_passphrase: options.secret_passphrase.clone().filter(|_| state.encrypt_secrets),
+                ..options.clone()
+            }
+        }
+        None => options.clone(),
+    };
+    let mut found = state.as_ref().map(|s| s.targets.iter().map(|t| t.found).collect::<Vec<_>>());
+    // 比较用的目标：忽略大小写时转为小写，显示和写入时用原始形式
+    let match_targets: Vec<Target> = if options.ignore_case {
+        options.targets.iter().map(Target::to_lowercase).collect()
+    } else {
+        options.targets.clone()
+    };
+
+    let mut seen = existing_public_keys(&options.dir.join(options.result_format.file_name()))?;
+    // 有结果要写时才创建结果文件
+    let mut writer: Option<ResultWriter> = None;
+    let run_id = format!("import-{}", grinder::new_run_id());
+
+    for entry in audit::collect_entries(input)? {
+        report.read += 1;
+        if entry.secret_key.starts_with("enc1:") {
+            report.encrypted += 1;
+            continue;
+        }
+        let (public_key, secret_key) = match verify_entry(&entry) {
+            Ok(keys) => keys,
+            Err(reason) => {
+                report.rejected.push((entry.source.clone(), reason));
+                continue;
+            }
+        };
+        if seen.contains(&public_key) {
+            report.duplicates += 1;
+            continue;
+        }
+        let candidate = if options.ignore_case { public_key.to_lowercase() } else { public_key.clone() };
+        let matched: Vec<usize> = (0..match_targets.len()).filter(|&idx| match_targets[idx].matches(&candidate)).collect();
+        if matched.is_empty() {
+            report.unmatched += 1;
+            continue;
+        }
+
+        let writer = match &mut writer {
+            Some(writer) => writer,
+            None => {
+                let secret_writer = if options.split_output {
+                    Some(SecretWriter::new(&options.dir, options.secret_passphrase.clone())?)
+                } else {
+                    None
+                };
+                writer.insert(ResultWriter::new(&options.dir, options.result_format, secret_writer, false)?)
+            }
+        };
+        let now = Local::now();
+        let time_str = grinder::local_time_str(&now);
+        let timestamp = now.to_rfc3339_opts(SecondsFormat::Millis, false);
+        let matched_patterns: Vec<String> = if matched.len() > 1 {
+            matched.iter().map(|&idx| options.targets[idx].to_string()).collect()
+        } else {
+            Vec::new()
+        };
+        for idx in options.attribution.credit(&match_targets, &matched, options.ignore_case) {
+            let target = &options.targets[idx];
+            // 导入的结果不属于任何一次搜索，序号记为 0
+            let event = FoundEvent {
+                run_id: Some(run_id.clone()),
+                matched_patterns: matched_patterns.clone(),
+                ..FoundEvent::new(&timestamp, 0, target, &public_key, &secret_key)
+            };
+            writer.write_result(&time_str, target, &event)?;
+            if let Some(found) = &mut found {
+                found[idx] += 1;
+            }
+        }
+        seen.insert(public_key);
+        report.imported += 1;
+    }
+    if let Some(writer) = &mut writer {
+        writer.finalize()?;
+    }
+
+    // 暂停的搜索继续时，已在别处找够的目标不再参与匹配
+    if let (Some(mut state), Some(found)) = (state, found) {
+        if report.imported > 0 {
+            for (target, found) in state.targets.iter_mut().zip(found) {
+                if target.satisfied_at.is_none() && found >= target.count {
+                    target.satisfied_at = Some(state.attempts);
+                }
+                target.found = found;
+            }
+            state.save(&state_path)?;
+            report.state_updated = Some(state_path);
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,342 @@
+use crate::audit;
+use crate::error::GrindError;
+use crate::event::{FoundEvent, PublicEntry, PUBLIC_FEED_FILE};
+use crate::net::HttpClient;
+use crate::target::Target;
+use crate::watch;
+use chrono::{DateTime, Local, SecondsFormat};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// 地址清单数据库的默认文件名
+pub const INVENTORY_DB: &str = "inventory.db";
+
+// 各版本的数据库结构变更，按顺序执行；数据库已执行到的版本记录在 SQLite 的 user_version 中。
+// 清单只保存公钥和元数据，私钥始终留在结果文件或私钥文件中
+const MIGRATIONS: &[&str] = &[
+    "
+CREATE TABLE IF NOT EXISTS addresses (
+    public_key TEXT PRIMARY KEY,
+    pattern TEXT NOT NULL,
+    run_id TEXT,
+    found_at TEXT NOT NULL,
+    source TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'unused',
+    status_changed_at TEXT
+);
+CREATE TABLE IF NOT EXISTS tags (
+    public_key TEXT NOT NULL REFERENCES addresses(public_key) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (public_key, tag)
+);
+CREATE INDEX IF NOT EXISTS tags_by_tag ON tags(tag);
+",
+    // inventory sync 查询到的链上余额和最近一笔交易
+    "
+ALTER TABLE addresses ADD COLUMN lamports INTEGER;
+ALTER TABLE addresses ADD COLUMN last_signature TEXT;
+ALTER TABLE addresses ADD COLUMN last_activity_at TEXT;
+ALTER TABLE addresses ADD COLUMN synced_at TEXT;
+",
+];
+
+/// 地址的使用状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressStatus {
+    /// 刚导入，尚未使用
+    Unused,
+    /// 已分配给某个用途
+    Used,
+    /// 已转入资产
+    Funded,
+    /// 不再使用
+    Retired,
+}
+
+impl AddressStatus {
+    pub fn parse(value: &str) -> Result<Self, GrindError> {
+        match value {
+            "unused" => Ok(AddressStatus::Unused),
+            "used" => Ok(AddressStatus::Used),
+            "funded" => Ok(AddressStatus::Funded),
+            "retired" => Ok(AddressStatus::Retired),
+            _ => Err(GrindError::Config(format!("不支持的地址状态 '{}'（可选: unused, used, funded, retired）", value))),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AddressStatus::Unused => "unused",
+            AddressStatus::Used => "used",
+            AddressStatus::Funded => "funded",
+            AddressStatus::Retired => "retired",
+        }
+    }
+}
+
+/// 清单中的一个地址
+#[derive(Debug, Clone)]
+pub struct InventoryEntry {
+    pub public_key: String,
+    pub pattern: String,
+    pub run_id: Option<String>,
+    /// 找到时间（RFC 3339）
+    pub found_at: String,
+    /// 导入时读取的文件
+    pub source: String,
+    pub status: AddressStatus,
+    pub status_changed_at: Option<String>,
+    pub tags: Vec<String>,
+    /// 最近一次 `inventory sync` 查询到的余额；从未同步时为空
+    pub lamports: Option<u64>,
+    /// 最近一笔交易的时间（RFC 3339）；没有交易或从未同步时为空
+    pub last_activity_at: Option<String>,
+    pub synced_at: Option<String>,
+}
+
+/// `inventory sync` 中一个地址的链上状态
+#[derive(Debug, Clone)]
+pub struct SyncedAddress {
+    pub public_key: String,
+    pub status: AddressStatus,
+    pub lamports: u64,
+    /// 最近一笔交易的签名；没有任何交易时为空
+    pub last_signature: Option<String>,
+    pub last_activity_at: Option<String>,
+}
+
+impl SyncedAddress {
+    /// 标记为未使用的地址却有余额或交易：可能有人误用了它，或私钥已经泄露
+    pub fn unexpected(&self) -> bool {
+        self.status == AddressStatus::Unused && (self.lamports > 0 || self.last_signature.is_some())
+    }
+}
+
+/// `inventory list` 的过滤条件，未设置的条件不参与过滤
+#[derive(Debug, Clone, Default)]
+pub struct InventoryFilter {
+    pub status: Option<AddressStatus>,
+    pub tag: Option<String>,
+    /// 与 `query --pattern` 相同：记录的目标相同，或公钥满足该目标
+    pub pattern: Option<Target>,
+}
+
+/// `inventory import` 的处理结果
+#[derive(Debug, Clone, Default)]
+pub struct InventoryImport {
+    /// 读取的结果文件
+    pub files: Vec<PathBuf>,
+    /// 读到的结果条数
+    pub read: u64,
+    /// 新加入清单的地址数量（已有的地址不会重复加入）
+    pub added: u64,
+}
+
+pub struct Inventory {
+    conn: Connection,
+}
+
+impl Inventory {
+    /// 打开（不存在时创建）清单数据库
+    pub fn open(path: &Path) -> Result<Self, GrindError> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version > MIGRATIONS.len() {
+            return Err(GrindError::Config(format!(
+                "清单数据库 {} 的版本为 {}，当前只支持到版本 {}",
+                path.display(), version, MIGRATIONS.len()
+            )));
+        }
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", index + 1)?;
+            tx.commit()?;
+        }
+        Ok(Inventory { conn })
+    }
+
+    /// 从结果文件（`result.log`、`result.jsonl`、`found_public.jsonl`）或包含它们的目录（递归查找，
+    /// 例如 `runs/`）导入找到的地址；只导入公钥和元数据，已有的地址保持原状
+    pub fn import(&mut self, input: &Path) -> Result<InventoryImport, GrindError> {
+        let mut report = InventoryImport::default();
+        let mut files = Vec::new();
+        if input.is_dir() {
+            // 结果文件带有运行 ID，先于只有公钥和目标的公开结果列表导入
+            let mut feeds = Vec::new();
+            collect_result_files(input, &mut files, &mut feeds)?;
+            files.append(&mut feeds);
+        } else {
+            files.push(input.to_path_buf());
+        }
+
+        let tx = self.conn.transaction()?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT OR IGNORE INTO addresses (public_key, pattern, run_id, found_at, source) VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for path in &files {
+                let source = path.display().to_string();
+                for line in BufReader::new(File::open(path)?).lines() {
+                    let Some((public_key, pattern, run_id, timestamp)) = parse_line(&line?) else { continue };
+                    report.read += 1;
+                    report.added += insert.execute(params![public_key, pattern, run_id, timestamp, source])? as u64;
+                }
+            }
+        }
+        tx.commit()?;
+        report.files = files;
+        Ok(report)
+    }
+
+    pub fn set_status(&self, public_key: &str, status: AddressStatus) -> Result<(), GrindError> {
+        let changed = self.conn.execute(
+            "UPDATE addresses SET status = ?1, status_changed_at = ?2 WHERE public_key = ?3",
+            params![status.name(), now(), public_key],
+        )?;
+        self.require(public_key, changed)
+    }
+
+    pub fn tag(&self, public_key: &str, tags: &[String]) -> Result<(), GrindError> {
+        self.require(public_key, self.exists(public_key)? as usize)?;
+        if let Some(tag) = tags.iter().find(|t| t.is_empty() || t.contains(',')) {
+            return Err(GrindError::Config(format!("标签 '{}' 不能为空或包含逗号", tag)));
+        }
+        for tag in tags {
+            self.conn.execute("INSERT OR IGNORE INTO tags (public_key, tag) VALUES (?1, ?2)", params![public_key, tag])?;
+        }
+        Ok(())
+    }
+
+    pub fn untag(&self, public_key: &str, tags: &[String]) -> Result<(), GrindError> {
+        self.require(public_key, self.exists(public_key)? as usize)?;
+        for tag in tags {
+            self.conn.execute("DELETE FROM tags WHERE public_key = ?1 AND tag = ?2", params![public_key, tag])?;
+        }
+        Ok(())
+    }
+
+    /// 按找到时间列出满足条件的地址
+    pub fn list(&self, filter: &InventoryFilter) -> Result<Vec<InventoryEntry>, GrindError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT public_key, pattern, run_id, found_at, source, status, status_changed_at,
+                    lamports, last_activity_at, synced_at,
+                    (SELECT group_concat(tag, ',') FROM (SELECT tag FROM tags t WHERE t.public_key = a.public_key ORDER BY tag))
+             FROM addresses a
+             WHERE (?1 IS NULL OR status = ?1)
+               AND (?2 IS NULL OR EXISTS (SELECT 1 FROM tags t WHERE t.public_key = a.public_key AND t.tag = ?2))
+             ORDER BY found_at, public_key",
+        )?;
+        let rows = stmt.query_map(params![filter.status.map(AddressStatus::name), filter.tag], |row| {
+            Ok((
+                InventoryEntry {
+                    public_key: row.get(0)?,
+                    pattern: row.get(1)?,
+                    run_id: row.get(2)?,
+                    found_at: row.get(3)?,
+                    source: row.get(4)?,
+                    status: AddressStatus::Unused,
+                    status_changed_at: row.get(6)?,
+                    tags: row.get::<_, Option<String>>(10)?
+                        .map(|tags| tags.split(',').map(str::to_string).collect())
+                        .unwrap_or_default(),
+                    lamports: row.get::<_, Option<i64>>(7)?.map(|l| l as u64),
+                    last_activity_at: row.get(8)?,
+                    synced_at: row.get(9)?,
+                },
+                row.get::<_, String>(5)?,
+            ))
+        })?;
+
+        let label = filter.pattern.as_ref().map(Target::to_string);
+        let mut entries = Vec::new();
+        for row in rows {
+            let (entry, status) = row?;
+            if let (Some(pattern), Some(label)) = (&filter.pattern, &label) {
+                if entry.pattern != *label && !pattern.matches(&entry.public_key) {
+                    continue;
+                }
+            }
+            entries.push(InventoryEntry { status: AddressStatus::parse(&status)?, ..entry });
+        }
+        Ok(entries)
+    }
+
+    /// 通过 RPC 节点查询满足条件的地址当前的余额和最近一笔交易，记入清单并返回查询结果
+    pub fn sync(&self, client: &HttpClient, rpc_url: &str, filter: &InventoryFilter) -> Result<Vec<SyncedAddress>, GrindError> {
+        let entries = self.list(filter)?;
+        let public_keys: Vec<String> = entries.iter().map(|e| e.public_key.clone()).collect();
+        // getMultipleAccounts 只返回余额不为 0 的公钥，其余为 0
+        let funded: HashMap<String, u64> = audit::lookup_balances(client, rpc_url, &public_keys)?.into_iter().collect();
+        let synced_at = now();
+
+        let mut synced = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let latest = audit::latest_signature(client, rpc_url, &entry.public_key)?;
+            let address = SyncedAddress {
+                lamports: funded.get(&entry.public_key).copied().unwrap_or(0),
+                last_activity_at: latest.as_ref()
+                    .and_then(|(_, time)| *time)
+                    .and_then(|secs| DateTime::from_timestamp(secs, 0))
+                    .map(|time| time.with_timezone(&Local).to_rfc3339_opts(SecondsFormat::Secs, false)),
+                last_signature: latest.map(|(signature, _)| signature),
+                public_key: entry.public_key,
+                status: entry.status,
+            };
+            self.conn.execute(
+                "UPDATE addresses SET lamports = ?1, last_signature = ?2, last_activity_at = ?3, synced_at = ?4 WHERE public_key = ?5",
+                params![address.lamports as i64, address.last_signature, address.last_activity_at, synced_at, address.public_key],
+            )?;
+            synced.push(address);
+        }
+        Ok(synced)
+    }
+
+    fn exists(&self, public_key: &str) -> Result<bool, GrindError> {
+        Ok(self.conn
+            .query_row("SELECT 1 FROM addresses WHERE public_key = ?1", params![public_key], |_| Ok(()))
+            .optional()?
+            .is_some())
+    }
+
+    fn require(&self, public_key: &str, found: usize) -> Result<(), GrindError> {
+        if found == 0 {
+            return Err(GrindError::Config(format!("清单中没有公钥 {}，请先用 inventory import 导入", public_key)));
+        }
+        Ok(())
+    }
+}
+
+fn now() -> String {
+    Local::now().to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+// 递归查找目录中的结果文件和公开结果列表
+fn collect_result_files(dir: &Path, results: &mut Vec<PathBuf>, feeds: &mut Vec<PathBuf>) -> Result<(), GrindError> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)?.filter_map(|e| e.ok().map(|e| e.path())).collect();
+    entries.sort();
+    for path in entries {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        if path.is_dir() {
+            collect_result_files(&path, results, feeds)?;
+        } else if matches!(name, "result.log" | "result.jsonl") {
+            results.push(path);
+        } else if name == PUBLIC_FEED_FILE {
+            feeds.push(path);
+        }
+    }
+    Ok(())
+}
+
+// 解析结果行或公开结果列表的一行，返回（公钥，目标，运行 ID，时间）
+fn parse_line(line: &str) -> Option<(String, String, Option<String>, String)> {
+    if let Some(FoundEvent { public_key, pattern, run_id, timestamp, .. }) = watch::parse_result_entry(line) {
+        return Some((public_key, pattern, run_id, timestamp));
+    }
+    let entry: PublicEntry = serde_json::from_str(line).ok()?;
+    Some((entry.public_key, entry.pattern, None, entry.timestamp))
+}
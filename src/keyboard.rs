@@ -0,0 +1,232 @@
+//! 前台运行时的单键控制：`p` 暂停/继续、`s` 输出当前统计、`q` 停止（能暂停时保存状态）、
+//! `a` 输入一个要追加的目标、`d` 写出调试转储。按键在单独的线程中读取；Unix 上把终端切到不回显、不等回车的模式，
+//! 退出、Ctrl-C 或被终止时恢复原来的设置，其他平台上输入字母后按回车
+
+use crate::grinder::RunControl;
+use crate::target;
+use crate::units::{self, CountFormat};
+use crate::progress;
+use std::io::{self, BufRead, IsTerminal, Read};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+/// 启动时显示的按键说明
+pub const HELP: &str = "按键: p 暂停/继续  s 当前统计  q 停止并保存状态  a 追加目标  d 调试转储  h 帮助";
+
+/// 键盘控制。只有标准输入是终端时才启动；丢弃时恢复终端设置
+pub struct Keyboard {
+    active: Arc<AtomicBool>,
+}
+
+impl Keyboard {
+    /// 开始读取按键，作用于 `control` 当前附着的运行；追加的目标通过 [`RunControl::take_added`] 取出。
+    /// `can_add` 为假时（例如不能保存状态的运行）不接受追加目标，
+    /// `can_dump` 为假时（没有启用调试转储）不接受转储请求。`ignore_case` 与搜索一致，用于检查追加的目标
+    pub fn start(control: RunControl, count_format: CountFormat, ignore_case: bool, can_add: bool, can_dump: bool) -> Option<Self> {
+        if !io::stdin().is_terminal() {
+            return None;
+        }
+        #[cfg(unix)]
+        terminal::enter_cbreak();
+        let keyboard = Keyboard { active: Arc::new(AtomicBool::new(true)) };
+        let reader = Reader { control, count_format, ignore_case, can_add, can_dump, active: Arc::clone(&keyboard.active) };
+        thread::spawn(move || reader.run());
+        Some(keyboard)
+    }
+}
+
+impl Drop for Keyboard {
+    fn drop(&mut self) {
+        self.active.store(false, Ordering::Relaxed);
+        #[cfg(unix)]
+        terminal::restore();
+    }
+}
+
+struct Reader {
+    control: RunControl,
+    count_format: CountFormat,
+    ignore_case: bool,
+    can_add: bool,
+    can_dump: bool,
+    active: Arc<AtomicBool>,
+}
+
+impl Reader {
+    fn run(self) {
+        while let Some(key) = read_key() {
+            if !self.active.load(Ordering::Relaxed) {
+                break;
+            }
+            match key.to_ascii_lowercase() {
+                'p' => self.toggle_pause(),
+                's' => self.print_stats(),
+                'q' => {
+                    progress!("正在停止...");
+                    self.control.set_paused(false);
+                    self.control.request_stop();
+                }
+                'a' => self.add_target(),
+                'd' if self.can_dump => {
+                    progress!("正在写出调试转储...");
+                    self.control.request_dump();
+                }
+                'd' => progress!("启动时没有使用 --debug-dump，不能写出调试转储"),
+                'h' | '?' => progress!("{}", HELP),
+                _ => {}
+            }
+        }
+    }
+
+    fn toggle_pause(&self) {
+        let paused = !self.control.is_paused();
+        self.control.set_paused(paused);
+        if paused {
+            progress!("已暂停，按 p 继续");
+        } else {
+            progress!("继续生成");
+        }
+    }
+
+    fn print_stats(&self) {
+        let Some(snapshot) = self.control.snapshot() else {
+            progress!("搜索尚未开始");
+            return;
+        };
+        let units = self.count_format;
+        let eta = snapshot.eta_secs.map_or_else(|| "未知".to_string(), units::duration);
+        progress!(
+            "已运行 {}，共生成 {} 个密钥对，平均 {} 个/秒，{} 个线程，预计还需 {}{}",
+            units::duration(snapshot.elapsed_secs), units.count(snapshot.attempts), units.rate(snapshot.keys_per_sec),
+            snapshot.threads.unwrap_or_default(), eta,
+            if snapshot.idle { "（已完成，等待追加目标）" } else if self.control.is_paused() { "（已暂停）" } else { "" }
+        );
+        for target in &snapshot.targets {
+            let eta = match target.eta_secs {
+                _ if target.found >= target.count => "已找够".to_string(),
+                Some(secs) => format!("预计还需 {}", units::duration(secs)),
+                None => "预计时间未知".to_string(),
+            };
+            progress!("  {}: {}/{}，{}", target.pattern, target.found, target.count, eta);
+        }
+    }
+
+    // 暂停后读一行目标；给出有效目标时停止本次运行，由调用方加入目标后继续
+    fn add_target(&self) {
+        if !self.can_add {
+            progress!("当前运行不能保存状态，不支持追加目标");
+            return;
+        }
+        let was_paused = self.control.is_paused();
+        self.control.set_paused(true);
+        progress!("输入要追加的目标（abc、...xyz 或 abc...xyz），直接回车取消:");
+        #[cfg(unix)]
+        terminal::restore();
+        let mut line = String::new();
+        let read = io::stdin().lock().read_line(&mut line);
+        #[cfg(unix)]
+        terminal::enter_cbreak();
+        let label = line.trim();
+        if read.is_err() || label.is_empty() {
+            progress!("已取消");
+            self.control.set_paused(was_paused);
+            return;
+        }
+        match target::parse_label(label).and_then(|t| t.check_alphabet(self.ignore_case).map(|_| t)) {
+            Ok(target) => {
+                progress!("将追加目标 {}，正在保存状态并重新开始搜索...", target);
+                self.control.add_target(target);
+            }
+            Err(e) => {
+                progress!("{}，未追加", e);
+                self.control.set_paused(was_paused);
+            }
+        }
+    }
+}
+
+// 读一个按键；标准输入关闭时返回 None
+#[cfg(unix)]
+fn read_key() -> Option<char> {
+    let mut byte = [0u8];
+    loop {
+        match io::stdin().lock().read(&mut byte) {
+            Ok(0) => return None,
+            Ok(_) => return Some(byte[0] as char),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(_) => return None,
+        }
+    }
+}
+
+// 没有终端模式可切换的平台上按行读取，取每行第一个字符
+#[cfg(not(unix))]
+fn read_key() -> Option<char> {
+    loop {
+        let mut line = String::new();
+        match io::stdin().lock().read_line(&mut line) {
+            Ok(0) | Err(_) => return None,
+            Ok(_) => {
+                if let Some(key) = line.trim().chars().next() {
+                    return Some(key);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+mod terminal {
+    use std::sync::OnceLock;
+
+    // 启动前的终端设置，退出时恢复
+    static ORIGINAL: OnceLock<libc::termios> = OnceLock::new();
+
+    /// 关闭行缓冲和回显，保留 Ctrl-C 等信号；第一次调用时记下原来的设置并在收到信号时恢复
+    pub(super) fn enter_cbreak() {
+        let original = match ORIGINAL.get() {
+            Some(original) => *original,
+            None => {
+                // SAFETY: termios 是普通的 C 结构体，全零是合法的初始值，随后由 tcgetattr 填充
+                let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+                // SAFETY: 文件描述符 0 在整个进程期间有效，termios 指向有效的内存
+                if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } != 0 {
+                    return;
+                }
+                let _ = ORIGINAL.set(termios);
+                let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+                // SAFETY: 处理函数只调用异步信号安全的函数
+                unsafe {
+                    libc::signal(libc::SIGINT, handler);
+                    libc::signal(libc::SIGTERM, handler);
+                }
+                termios
+            }
+        };
+        let mut cbreak = original;
+        cbreak.c_lflag &= !(libc::ICANON | libc::ECHO);
+        cbreak.c_cc[libc::VMIN] = 1;
+        cbreak.c_cc[libc::VTIME] = 0;
+        // SAFETY: 同上
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &cbreak) };
+    }
+
+    /// 恢复启动前的终端设置
+    pub(super) fn restore() {
+        if let Some(original) = ORIGINAL.get() {
+            // SAFETY: 同上；tcsetattr 是异步信号安全的，可以在信号处理函数中调用
+            unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, original) };
+        }
+    }
+
+    // 恢复终端后按默认方式重新处理信号，进程照常退出
+    extern "C" fn on_signal(signal: libc::c_int) {
+        restore();
+        // SAFETY: signal 和 raise 都是异步信号安全的
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+            libc::raise(signal);
+        }
+    }
+}
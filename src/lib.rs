@@ -0,0 +1,51 @@
+//! Solana 靓号生成器
+//!
+//! 命令行程序之外，也可以作为库嵌入使用：构造 [`GrindConfig`] 后调用 [`run`]，
+//! 所有错误都以 [`GrindError`] 返回。
+
+pub mod announce;
+pub mod audit;
+pub mod backend;
+pub mod base58;
+pub mod campaign;
+pub mod check;
+pub mod clock;
+pub mod collision;
+pub mod console;
+pub mod debug;
+pub mod dedup;
+pub mod difficulty;
+pub mod error;
+pub mod estimate;
+pub mod event;
+pub mod grinder;
+pub mod hooks;
+pub mod import;
+pub mod inventory;
+pub mod keyboard;
+pub mod logsink;
+pub mod matcher;
+pub mod merge;
+pub mod migrate;
+pub mod mnemonic;
+pub mod net;
+pub mod notify;
+pub mod policy;
+pub mod priority;
+pub mod query;
+pub mod records;
+pub mod regex_target;
+pub mod recovery;
+pub mod report;
+pub mod secrets;
+pub mod session;
+pub mod state;
+pub mod stats;
+pub mod target;
+pub mod units;
+pub mod watch;
+pub mod wordlist;
+
+pub use error::GrindError;
+pub use event::{FoundEvent, RunEvent};
+pub use grinder::{run, GrindConfig, GrindSummary};
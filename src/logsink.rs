@@ -0,0 +1,307 @@
+use crate::error::GrindError;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 常规日志文件的写入方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogSink {
+    /// 标准库的 `BufWriter`，所有平台可用
+    #[default]
+    Buffered,
+    /// Linux 上的 io_uring：大块缓冲区异步提交，写入与填充下一块交替进行，系统调用远少于 `BufWriter`；
+    /// 未启用 `io-uring` 特性、不是 Linux 或内核（容器的 seccomp 策略）不支持时回退到 `Buffered`
+    IoUring,
+    /// 预分配的内存映射段：日志直接复制进映射的页面，段写满时封存并映射下一段，
+    /// 关闭文件时截掉未用完的预分配部分
+    Mmap,
+}
+
+impl LogSink {
+    pub fn parse(value: &str) -> Result<Self, GrindError> {
+        match value {
+            "buffered" => Ok(LogSink::Buffered),
+            "io-uring" => Ok(LogSink::IoUring),
+            "mmap" => Ok(LogSink::Mmap),
+            _ => Err(GrindError::Config(format!("不支持的日志写入方式 '{}'（可选: buffered, io-uring, mmap）", value))),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LogSink::Buffered => "buffered",
+            LogSink::IoUring => "io-uring",
+            LogSink::Mmap => "mmap",
+        }
+    }
+}
+
+/// 日志文件的写入端。`flush` 把缓冲的内容交给内核（io_uring 时只提交、不等待完成），
+/// `finish` 在关闭或轮转文件前等待所有写入完成并报告错误
+pub trait LogOutput: Write + Send {
+    fn finish(&mut self) -> std::io::Result<()>;
+}
+
+impl LogOutput for BufWriter<File> {
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.flush()
+    }
+}
+
+// 回退时只提示一次，轮转和分片会多次打开文件
+static FALLBACK_REPORTED: AtomicBool = AtomicBool::new(false);
+
+fn report_fallback(reason: &str) {
+    if !FALLBACK_REPORTED.swap(true, Ordering::Relaxed) {
+        eprintln!("提示: 无法使用 io_uring 写日志（{}），回退到 BufWriter", reason);
+    }
+}
+
+/// 以追加方式打开日志文件，按 `sink` 选择写入实现
+pub fn open_append(path: &Path, sink: LogSink) -> std::io::Result<Box<dyn LogOutput>> {
+    if sink == LogSink::Mmap {
+        // 可写映射需要以读写方式打开，追加位置由写入端自己维护
+        let file = OpenOptions::new().create(true).read(true).write(true).truncate(false).open(path)?;
+        return Ok(Box::new(mapped::MmapWriter::new(file)?));
+    }
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    match sink {
+        LogSink::IoUring => open_uring(file),
+        _ => Ok(Box::new(BufWriter::new(file))),
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+fn open_uring(file: File) -> std::io::Result<Box<dyn LogOutput>> {
+    match uring::UringWriter::new(file.try_clone()?) {
+        Ok(writer) => Ok(Box::new(writer)),
+        Err(e) => {
+            report_fallback(&e.to_string());
+            Ok(Box::new(BufWriter::new(file)))
+        }
+    }
+}
+
+#[cfg(not(all(target_os = "linux", feature = "io-uring")))]
+fn open_uring(file: File) -> std::io::Result<Box<dyn LogOutput>> {
+    report_fallback("未启用 io-uring 特性或不是 Linux");
+    Ok(Box::new(BufWriter::new(file)))
+}
+
+mod mapped {
+    use super::LogOutput;
+    use memmap2::{MmapMut, MmapOptions};
+    use std::fs::File;
+    use std::io::{self, Read, Seek, SeekFrom, Write};
+
+    // 每段预分配的大小，约 9 万行常规日志
+    const SEGMENT_SIZE: u64 = 16 << 20;
+    // 打开文件时向前查找预分配残留的步长
+    const SCAN_CHUNK: u64 = 64 << 10;
+
+    /// 内存映射的追加写入：文件先按段扩展，日志复制进映射的页面后由内核回写，
+    /// 不需要逐批 `write` 系统调用。段写满时异步回写（封存）并映射下一段
+    pub struct MmapWriter {
+        file: File,
+        segment: Option<MmapMut>,
+        // 当前段在文件中的起点，以及段内已写入的字节数
+        segment_start: u64,
+        used: usize,
+    }
+
+    impl MmapWriter {
+        pub fn new(mut file: File) -> io::Result<Self> {
+            // 上次进程异常退出时文件末尾可能留有未写入的预分配部分（全为 0），从真正的内容末尾接着写
+            let end = content_end(&mut file)?;
+            file.set_len(end)?;
+            Ok(MmapWriter { file, segment: None, segment_start: end, used: 0 })
+        }
+
+        fn map_segment(&mut self) -> io::Result<&mut MmapMut> {
+            if self.segment.is_none() {
+                self.file.set_len(self.segment_start + SEGMENT_SIZE)?;
+                // SAFETY: 映射的区间只由这个写入端修改；其他进程读取时最多看到尚未写入的 0
+                let map = unsafe { MmapOptions::new().offset(self.segment_start).len(SEGMENT_SIZE as usize).map_mut(&self.file)? };
+                self.segment = Some(map);
+            }
+            Ok(self.segment.as_mut().expect("segment mapped above"))
+        }
+
+        // 封存写满的段：开始异步回写后解除映射，下一次写入时映射新的一段
+        fn seal_segment(&mut self) -> io::Result<()> {
+            if let Some(map) = self.segment.take() {
+                map.flush_async()?;
+            }
+            self.segment_start += self.used as u64;
+            self.used = 0;
+            Ok(())
+        }
+    }
+
+    // 找到最后一个非 0 字节之后的位置；日志是文本，不会包含 0 字节
+    fn content_end(file: &mut File) -> io::Result<u64> {
+        let mut end = file.metadata()?.len();
+        let mut chunk = vec![0u8; SCAN_CHUNK as usize];
+        while end > 0 {
+            let start = end.saturating_sub(SCAN_CHUNK);
+            let buf = &mut chunk[..(end - start) as usize];
+            file.seek(SeekFrom::Start(start))?;
+            file.read_exact(buf)?;
+            if let Some(pos) = buf.iter().rposition(|&b| b != 0) {
+                return Ok(start + pos as u64 + 1);
+            }
+            end = start;
+        }
+        Ok(0)
+    }
+
+    impl Write for MmapWriter {
+        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+            let used = self.used;
+            let map = self.map_segment()?;
+            let n = data.len().min(map.len() - used);
+            map[used..used + n].copy_from_slice(&data[..n]);
+            self.used += n;
+            if self.used as u64 == SEGMENT_SIZE {
+                self.seal_segment()?;
+            }
+            Ok(n)
+        }
+
+        // 写入的内容已在页缓存中，其他读者立即可见；回写交给内核，这里不做任何事
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl LogOutput for MmapWriter {
+        // 同步回写已写入的部分，再截掉未用完的预分配空间
+        fn finish(&mut self) -> io::Result<()> {
+            if let Some(map) = self.segment.take() {
+                map.flush_range(0, self.used)?;
+            }
+            self.segment_start += self.used as u64;
+            self.used = 0;
+            self.file.set_len(self.segment_start)
+        }
+    }
+
+    impl Drop for MmapWriter {
+        fn drop(&mut self) {
+            let _ = self.finish();
+        }
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+mod uring {
+    use super::LogOutput;
+    use io_uring::{opcode, types, IoUring};
+    use std::fs::File;
+    use std::io::{self, Write};
+    use std::os::unix::fs::FileExt;
+    use std::os::unix::io::AsRawFd;
+
+    // 每块缓冲区的大小：写满一块就提交，同时开始填充另一块
+    const SEGMENT_SIZE: usize = 1 << 20;
+
+    /// 双缓冲的 io_uring 写入：任何时候最多一块缓冲区在内核中写入，
+    /// 在它完成之前不会改动这块缓冲区，所以提交给内核的指针始终有效
+    pub struct UringWriter {
+        file: File,
+        ring: IoUring,
+        buffers: [Vec<u8>; 2],
+        current: usize,
+        // 正在写入的缓冲区及其在文件中的偏移
+        in_flight: Option<(usize, u64)>,
+        offset: u64,
+    }
+
+    impl UringWriter {
+        pub fn new(file: File) -> io::Result<Self> {
+            let ring = IoUring::new(4)?;
+            // 追加写：从当前文件末尾开始，显式给出偏移
+            let offset = file.metadata()?.len();
+            Ok(UringWriter {
+                file,
+                ring,
+                buffers: [Vec::with_capacity(SEGMENT_SIZE), Vec::with_capacity(SEGMENT_SIZE)],
+                current: 0,
+                in_flight: None,
+                offset,
+            })
+        }
+
+        fn submit_current(&mut self) -> io::Result<()> {
+            self.wait_in_flight()?;
+            let buffer = &self.buffers[self.current];
+            if buffer.is_empty() {
+                return Ok(());
+            }
+            let entry = opcode::Write::new(types::Fd(self.file.as_raw_fd()), buffer.as_ptr(), buffer.len() as u32)
+                .offset(self.offset)
+                .build();
+            // SAFETY: 缓冲区在写入完成（wait_in_flight）之前不会被修改或释放
+            unsafe {
+                self.ring.submission().push(&entry).map_err(|_| io::Error::other("io_uring 提交队列已满"))?;
+            }
+            self.ring.submit()?;
+            self.in_flight = Some((self.current, self.offset));
+            self.offset += buffer.len() as u64;
+            self.current ^= 1;
+            Ok(())
+        }
+
+        fn wait_in_flight(&mut self) -> io::Result<()> {
+            let Some((index, offset)) = self.in_flight.take() else { return Ok(()) };
+            let result = loop {
+                if let Some(entry) = self.ring.completion().next() {
+                    break entry.result();
+                }
+                self.ring.submit_and_wait(1)?;
+            };
+            let buffer = &mut self.buffers[index];
+            if result < 0 {
+                return Err(io::Error::from_raw_os_error(-result));
+            }
+            // 极少出现的短写：剩余部分同步补写
+            let written = result as usize;
+            if written < buffer.len() {
+                self.file.write_all_at(&buffer[written..], offset + written as u64)?;
+            }
+            buffer.clear();
+            Ok(())
+        }
+    }
+
+    impl Write for UringWriter {
+        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+            let buffer = &mut self.buffers[self.current];
+            let n = data.len().min(SEGMENT_SIZE - buffer.len());
+            buffer.extend_from_slice(&data[..n]);
+            if buffer.len() == SEGMENT_SIZE {
+                self.submit_current()?;
+            }
+            Ok(n)
+        }
+
+        // 只提交未满的缓冲区，不等待它写完：下一批日志填充另一块缓冲区时内核在后台写入
+        fn flush(&mut self) -> io::Result<()> {
+            self.submit_current()
+        }
+    }
+
+    impl LogOutput for UringWriter {
+        fn finish(&mut self) -> io::Result<()> {
+            self.submit_current()?;
+            self.wait_in_flight()
+        }
+    }
+
+    impl Drop for UringWriter {
+        fn drop(&mut self) {
+            let _ = self.finish();
+        }
+    }
+}
@@ -1,141 +1,204 @@
-use solana_sdk::signature::{Keypair, Signer};
-use chrono::Local;
-use std::fs::{File, OpenOptions};
-use std::io::{Write, BufWriter};
-use std::sync::{Arc, Mutex, mpsc};
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::thread;
-use std::env;
 use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-const MAX_LINES_PER_FILE: u64 = 1_000_000;
-
-#[derive(Debug, Clone)]
-enum LogMessage {
-    Regular {
-        time_str: String,
-        counter: u64,
-        public_key: String,
-        private_key: String,
-    },
-    Found {
-        time_str: String,
-        counter: u64,
-        public_key: String,
-        private_key: String,
-        matched_prefix: String,
-    },
-}
-
-struct LogWriter {
-    writer: BufWriter<File>,
-    file_index: u32,
-    line_count: u64,
-}
-
-struct ResultWriter {
-    writer: BufWriter<File>,
-}
+use solana_vanity_generator::announce::{self, AnnounceTemplate};
+use solana_vanity_generator::audit;
+use solana_vanity_generator::backend::{self, Reseed, RngPolicy, SimulationSpec};
+use solana_vanity_generator::campaign::{self, Campaign};
+use solana_vanity_generator::check;
+use solana_vanity_generator::clock::{self, SharedClock};
+use solana_vanity_generator::collision;
+use solana_vanity_generator::debug;
+use solana_vanity_generator::dedup;
+use solana_vanity_generator::console;
+use solana_vanity_generator::difficulty::{self, PatternAnalysis};
+use solana_vanity_generator::estimate;
+use solana_vanity_generator::import::{self, ImportOptions};
+use solana_vanity_generator::inventory::{self, AddressStatus, Inventory, InventoryFilter};
+use solana_vanity_generator::keyboard::{self, Keyboard};
+use solana_vanity_generator::event;
+use solana_vanity_generator::grinder::{self, Attribution, Budget, GrindConfig, OnComplete, ResultFormat, RunControl, SpeedBaseline, StallDetection, MAX_LINES_PER_FILE};
+use solana_vanity_generator::hooks::{self, Hook};
+use solana_vanity_generator::logsink::LogSink;
+use solana_vanity_generator::priority::IoPriority;
+use solana_vanity_generator::merge;
+use solana_vanity_generator::migrate::{self, MigrateFormat};
+use solana_vanity_generator::mnemonic;
+use solana_vanity_generator::net::{HttpClient, ProxySetting};
+use solana_vanity_generator::notify::Webhooks;
+use solana_vanity_generator::policy::SecretPolicy;
+use solana_vanity_generator::query::{self, QueryFilter};
+use solana_vanity_generator::report::{self, PatternReport, RunReport};
+use solana_vanity_generator::secrets;
+use solana_vanity_generator::session;
+use solana_vanity_generator::state::{self, RunState, TargetState};
+use solana_vanity_generator::stats::{self, StatsAccess};
+use solana_vanity_generator::target::{self, Target};
+use solana_vanity_generator::units::{self, CountFormat};
+use solana_vanity_generator::watch;
+use solana_vanity_generator::wordlist::{self, Transform};
+use solana_vanity_generator::GrindError;
+use solana_vanity_generator::status;
 
-impl LogWriter {
-    fn new() -> std::io::Result<Self> {
-        let file_index = 0;
-        let file_path = format!("keypairs_{:04}.log", file_index);
-        let file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&file_path)?;
-        let writer = BufWriter::new(file);
-        
-        println!("创建日志文件: {}", file_path);
-        
-        Ok(LogWriter {
-            writer,
-            file_index,
-            line_count: 0,
-        })
-    }
-
-    fn write_line(&mut self, content: &str) -> std::io::Result<()> {
-        writeln!(self.writer, "{}", content)?;
-        self.writer.flush()?;
-        self.line_count += 1;
-
-        // 如果达到最大行数，创建新文件
-        if self.line_count >= MAX_LINES_PER_FILE {
-            self.rotate_file()?;
-        }
-
-        Ok(())
-    }
-
-    fn rotate_file(&mut self) -> std::io::Result<()> {
-        // 关闭当前文件（通过 flush 和 drop）
-        self.writer.flush()?;
-        
-        // 创建新文件
-        self.file_index += 1;
-        self.line_count = 0;
-        let file_path = format!("keypairs_{:04}.log", self.file_index);
-        let file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&file_path)?;
-        
-        // 替换 writer（旧的 writer 会被自动 drop）
-        self.writer = BufWriter::new(file);
-        
-        println!("创建新日志文件: {} (已达到 {} 行)", file_path, MAX_LINES_PER_FILE);
-        
-        Ok(())
-    }
-
-    fn finalize(&mut self) -> std::io::Result<()> {
-        self.writer.flush()?;
-        Ok(())
-    }
-}
+// estimate 未给出速度时在本机测速的时长（秒）
+const DEFAULT_MEASURE_SECS: u64 = 5;
 
-impl ResultWriter {
-    fn new() -> std::io::Result<Self> {
-        let file_path = "result.log";
-        let file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&file_path)?;
-        let writer = BufWriter::new(file);
-        
-        println!("创建结果文件: {}", file_path);
-        
-        Ok(ResultWriter { writer })
-    }
-
-    fn write_result(&mut self, time_str: &str, counter: u64, public_key: &str, private_key: &str, matched_prefix: &str) -> std::io::Result<()> {
-        let log_line = format!(
-            "[{}] [FOUND] 匹配前缀: {} | 序号: {} | 公钥: {} | 私钥: {}",
-            time_str, matched_prefix, counter, public_key, private_key
-        );
-        writeln!(self.writer, "{}", log_line)?;
-        self.writer.flush()?;
-        Ok(())
-    }
+// 模拟模式未指定 --run-dir 时的输出目录
+const SIMULATION_DIR: &str = "simulation";
+// 配置了里程碑 webhook 但未指定 --milestone-every 时的间隔
+const DEFAULT_MILESTONE_EVERY: u64 = 1_000_000_000;
+// 启动时逐个列出命中概率的目标数上限，超过时只列出有警告的目标，并输出按长度统计的难度分布
+const PATTERN_REPORT_LIMIT: usize = 20;
+// monitor 每次刷新列出的最近找到的结果数
+const MONITOR_RECENT_FINDS: usize = 5;
+// 输出难度分布时测速的时长（没有 --baseline 时）
+const HISTOGRAM_MEASURE: Duration = Duration::from_secs(1);
+// 按当前速度期望耗时超过这么久的目标视为实际上找不到
+const INFEASIBLE_SECS: f64 = 365.0 * 86400.0;
+// 因 --max-attempts 或 --max-time 结束、目标没有全部找到时的退出码，以便脚本区分"完成"和"用完预算"
+const EXIT_BUDGET_EXHAUSTED: i32 = 2;
 
-    fn finalize(&mut self) -> std::io::Result<()> {
-        self.writer.flush()?;
-        Ok(())
-    }
+/// 一次搜索怎样结束，由 `main` 换算为退出码
+enum GrindOutcome {
+    /// 目标都已找够，或按停止请求结束（包括暂停），退出码 0
+    Completed,
+    /// 达到 --max-attempts 或 --max-time 时目标还没有全部找到，退出码为 [`EXIT_BUDGET_EXHAUSTED`]
+    BudgetExhausted,
 }
 
 #[derive(Debug)]
 struct Config {
     num_threads: Option<usize>,
-    prefixes: Vec<String>,
+    targets: Vec<Target>,
+    // 明确给出数量的命令行目标（targets 中的下标），合并重复目标时数量相加
+    counted_targets: Vec<usize>,
+    split_output: bool,
+    encrypt_secrets: bool,
+    secret_policy: Option<SecretPolicy>,
+    ignore_case: bool,
+    leet: bool,
+    // 是否像 solana-keygen grind 一样为每个结果写出 <公钥>.json
+    write_keypair_files: bool,
+    no_outfile: bool,
+    collision_index: Option<String>,
+    collision_capacity: Option<u64>,
+    dedup_db: Option<String>,
+    no_dedup: bool,
+    stats_addr: Option<String>,
+    stats_allow_remote: bool,
+    stats_access: StatsAccess,
+    progress: bool,
+    raw_speed: bool,
+    plain_output: bool,
+    no_keyboard: bool,
+    debug_dump: bool,
+    announce: Option<AnnounceTemplate>,
+    no_console_secret: bool,
+    result_format: ResultFormat,
+    results_to_stdout: bool,
+    attribution: Attribution,
+    count_format: CountFormat,
+    rate_window: Option<Duration>,
+    /// `--count`：没有单独给出数量的目标都要求找到这么多个
+    count: Option<u64>,
+    keep_counting: Option<u64>,
+    max_attempts: Option<u64>,
+    max_time: Option<Duration>,
+    on_complete: OnComplete,
+    wordlist: Option<String>,
+    exclude_words: Vec<String>,
+    pattern_files: Vec<String>,
+    min_len: usize,
+    transforms: Vec<Transform>,
+    run_dir: Option<String>,
+    low_memory: bool,
+    simulate: Option<SimulationSpec>,
+    // --rng chacha20 时为重新播种间隔
+    rng: Option<String>,
+    reseed: Option<Reseed>,
+    shutdown_grace: Option<u64>,
+    max_result_delay: Option<u64>,
+    webhooks: Webhooks,
+    proxy: ProxySetting,
+    hooks: Vec<String>,
+    hook_timeout: Option<u64>,
+    milestone_every: Option<u64>,
+    stall_fraction: Option<f64>,
+    stall_after: Option<u64>,
+    baseline: Option<f64>,
+    baseline_tolerance: Option<f64>,
+    shard_logs: bool,
+    // (目标, 截止时间) 对，在所有目标确定后再附加到对应目标上
+    deadlines: Vec<(String, String)>,
+    log_sink: LogSink,
+    io_priority: IoPriority,
+    clock: SharedClock,
 }
 
-fn parse_args() -> Result<Config, String> {
-    let args: Vec<String> = env::args().collect();
+fn parse_args(args: &[String]) -> Result<Config, GrindError> {
     let mut num_threads = None;
-    let mut prefixes = Vec::new();
+    let mut targets = Vec::new();
+    let mut counted_targets = Vec::new();
+    let mut split_output = false;
+    let mut encrypt_secrets = false;
+    let mut secret_policy = None;
+    let mut ignore_case = false;
+    let mut leet = false;
+    let mut write_keypair_files = false;
+    let mut no_outfile = false;
+    let mut collision_index = None;
+    let mut collision_capacity = None;
+    let mut dedup_db = None;
+    let mut no_dedup = false;
+    let mut stats_addr = None;
+    let mut stats_allow_remote = false;
+    let mut stats_access = StatsAccess::Full;
+    let mut progress = false;
+    let mut raw_speed = false;
+    let mut plain_output = false;
+    let mut no_keyboard = false;
+    let mut debug_dump = false;
+    let mut announce = None;
+    let mut no_console_secret = false;
+    let mut result_format = ResultFormat::Text;
+    let mut results_to_stdout = false;
+    let mut attribution = Attribution::All;
+    let mut count_format = CountFormat::Raw;
+    let mut rate_window = None;
+    let mut count = None;
+    let mut keep_counting = None;
+    let mut max_attempts = None;
+    let mut max_time = None;
+    let mut on_complete = OnComplete::Stop;
+    let mut wordlist = None;
+    let mut exclude_words = Vec::new();
+    let mut pattern_files = Vec::new();
+    let mut min_len = 1;
+    let mut transforms = Vec::new();
+    let mut run_dir = None;
+    let mut low_memory = false;
+    let mut simulate = None;
+    let mut rng = None;
+    let mut reseed = None;
+    let mut shutdown_grace = None;
+    let mut max_result_delay = None;
+    let mut webhooks = Webhooks::default();
+    let mut proxy = ProxySetting::Env;
+    let mut hooks = Vec::new();
+    let mut hook_timeout = None;
+    let mut milestone_every = None;
+    let mut stall_fraction = None;
+    let mut baseline = None;
+    let mut baseline_tolerance = None;
+    let mut stall_after = None;
+    let mut shard_logs = false;
+    let mut deadlines = Vec::new();
+    let mut log_sink = LogSink::Buffered;
+    let mut io_priority = IoPriority::Normal;
+    let mut clock = clock::system();
     
     let mut i = 1; // 跳过程序名
     while i < args.len() {
@@ -147,35 +210,497 @@ fn parse_args() -> Result<Config, String> {
                         i += 2;
                     }
                     Err(_) => {
-                        return Err(format!("错误: '{}' 不是有效的线程数", args[i + 1]));
+                        return Err(GrindError::Config(format!("'{}' 不是有效的线程数", args[i + 1])));
                     }
                 }
             } else {
-                return Err(format!("错误: {} 参数需要指定线程数", args[i]));
+                return Err(GrindError::Config(format!("{} 参数需要指定线程数", args[i])));
             }
         } else if args[i] == "--prefix" || args[i] == "-p" {
             // 支持多个前缀，可以多次使用 --prefix 或一次指定多个
             if i + 1 < args.len() {
                 // 检查下一个参数是否也是选项
                 if !args[i + 1].starts_with('-') {
-                    prefixes.push(args[i + 1].clone());
+                    push_target_arg(&args[i + 1], &mut targets, &mut counted_targets, target::parse_prefix_arg)?;
                     i += 2;
                 } else {
-                    return Err(format!("错误: {} 参数需要指定至少一个前缀", args[i]));
+                    return Err(GrindError::Config(format!("{} 参数需要指定至少一个前缀", args[i])));
+                }
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定至少一个前缀", args[i])));
+            }
+        } else if args[i] == "--suffix" || args[i] == "-s" {
+            // 与 --prefix 相同，可以多次使用指定多个后缀
+            if i + 1 < args.len() && !args[i + 1].starts_with('-') {
+                push_target_arg(&args[i + 1], &mut targets, &mut counted_targets, |suffix| Ok(Target::with_suffix(suffix)))?;
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定至少一个后缀", args[i])));
+            }
+        } else if args[i] == "--contains" {
+            // 出现在公钥中任意位置，可以多次使用，可与前缀、后缀混用
+            if i + 1 < args.len() && !args[i + 1].starts_with('-') {
+                push_target_arg(&args[i + 1], &mut targets, &mut counted_targets, Target::with_contains)?;
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定要包含的字符串", args[i])));
+            }
+        } else if args[i] == "--regex" {
+            // 正则在整个公钥中搜索，可以多次使用，可与前缀、后缀混用
+            if i + 1 < args.len() {
+                targets.push(Target::with_regex(&args[i + 1])?);
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定正则表达式", args[i])));
+            }
+        } else if args[i] == "--starts-with" || args[i] == "--ends-with" || args[i] == "--starts-and-ends-with" {
+            // 兼容 solana-keygen grind 的参数格式
+            if i + 1 < args.len() {
+                counted_targets.push(targets.len());
+                targets.push(target::parse_grind_arg(&args[i], &args[i + 1])?);
+                write_keypair_files = true;
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定匹配模式", args[i])));
+            }
+        } else if args[i] == "--ignore-case" {
+            ignore_case = true;
+            i += 1;
+        } else if args[i] == "--leet" {
+            leet = true;
+            i += 1;
+        } else if args[i] == "--result-format" {
+            if i + 1 < args.len() {
+                result_format = ResultFormat::parse(&args[i + 1])?;
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定格式", args[i])));
+            }
+        } else if args[i] == "--results-to-stdout" {
+            results_to_stdout = true;
+            i += 1;
+        } else if args[i] == "--attribution" {
+            if i + 1 < args.len() {
+                attribution = Attribution::parse(&args[i + 1])?;
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定归属策略", args[i])));
+            }
+        } else if args[i] == "--units" {
+            if i + 1 < args.len() {
+                count_format = CountFormat::parse(&args[i + 1])?;
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定显示方式", args[i])));
+            }
+        } else if args[i] == "--rate-window" {
+            if i + 1 < args.len() {
+                rate_window = Some(units::parse_rate_window(&args[i + 1])?);
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定窗口", args[i])));
+            }
+        } else if args[i] == "--count" {
+            match args.get(i + 1).map(|v| v.parse::<u64>()) {
+                Some(Ok(n)) if n > 0 => {
+                    count = Some(n);
+                    i += 2;
+                }
+                _ => return Err(GrindError::Config(format!("{} 参数需要指定正整数", args[i]))),
+            }
+        } else if args[i] == "--keep-counting" {
+            match args.get(i + 1).map(|v| v.parse::<u64>()) {
+                Some(Ok(attempts)) if attempts > 0 => {
+                    keep_counting = Some(attempts);
+                    i += 2;
+                }
+                _ => return Err(GrindError::Config(format!("{} 参数需要指定正整数", args[i]))),
+            }
+        } else if args[i] == "--max-attempts" {
+            match args.get(i + 1).and_then(|v| backend::parse_quantity(v)) {
+                Some(attempts) if attempts > 0 => {
+                    max_attempts = Some(attempts);
+                    i += 2;
+                }
+                _ => return Err(GrindError::Config(format!("{} 参数需要指定正整数（可带 K、M、G 后缀）", args[i]))),
+            }
+        } else if args[i] == "--max-time" {
+            match args.get(i + 1) {
+                Some(value) => {
+                    max_time = Some(units::parse_duration(value)?);
+                    i += 2;
+                }
+                None => return Err(GrindError::Config(format!("{} 参数需要指定时间", args[i]))),
+            }
+        } else if args[i] == "--on-complete" {
+            if i + 1 < args.len() {
+                on_complete = OnComplete::parse(&args[i + 1])?;
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定 stop、idle 或 continue", args[i])));
+            }
+        } else if args[i] == "--patterns-from-wordlist" {
+            if i + 1 < args.len() {
+                wordlist = Some(args[i + 1].clone());
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定词表文件", args[i])));
+            }
+        } else if args[i] == "--exclude-words" {
+            if i + 1 < args.len() {
+                exclude_words.push(args[i + 1].clone());
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定词表文件", args[i])));
+            }
+        } else if args[i] == "--pattern-file" {
+            if i + 1 < args.len() {
+                pattern_files.push(args[i + 1].clone());
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定目标文件", args[i])));
+            }
+        } else if args[i] == "--min-len" {
+            match args.get(i + 1).map(|v| v.parse::<usize>()) {
+                Some(Ok(len)) if len > 0 => {
+                    min_len = len;
+                    i += 2;
+                }
+                _ => return Err(GrindError::Config(format!("{} 参数需要指定正整数", args[i]))),
+            }
+        } else if args[i] == "--transforms" {
+            if i + 1 < args.len() {
+                transforms = Transform::parse_list(&args[i + 1])?;
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定变换列表", args[i])));
+            }
+        } else if args[i] == "--run-dir" {
+            if i + 1 < args.len() {
+                run_dir = Some(args[i + 1].clone());
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定目录或 auto", args[i])));
+            }
+        } else if args[i] == "--progress" {
+            progress = true;
+            i += 1;
+        } else if args[i] == "--plain" {
+            plain_output = true;
+            i += 1;
+        } else if args[i] == "--no-keyboard" {
+            no_keyboard = true;
+            i += 1;
+        } else if args[i] == "--debug-dump" {
+            debug_dump = true;
+            i += 1;
+        } else if args[i] == "--announce" {
+            if i + 1 < args.len() {
+                announce = Some(AnnounceTemplate::parse(&args[i + 1])?);
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定提示模板", args[i])));
+            }
+        } else if args[i] == "--no-console-secret" {
+            no_console_secret = true;
+            i += 1;
+        } else if args[i] == "--simulate" {
+            if i + 1 < args.len() {
+                simulate = Some(SimulationSpec::parse(&args[i + 1])?);
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定模拟参数，例如 rate=1M,match-every=5M", args[i])));
+            }
+        } else if args[i] == "--shutdown-grace" {
+            match args.get(i + 1).map(|v| v.parse::<u64>()) {
+                Some(Ok(secs)) => {
+                    shutdown_grace = Some(secs);
+                    i += 2;
+                }
+                _ => return Err(GrindError::Config(format!("{} 参数需要指定秒数", args[i]))),
+            }
+        } else if args[i] == "--max-result-delay" {
+            match args.get(i + 1).map(|v| v.parse::<u64>()) {
+                Some(Ok(millis)) if millis > 0 => {
+                    max_result_delay = Some(millis);
+                    i += 2;
+                }
+                _ => return Err(GrindError::Config(format!("{} 参数需要指定大于 0 的毫秒数", args[i]))),
+            }
+        } else if args[i] == "--proxy" {
+            if i + 1 < args.len() {
+                proxy = ProxySetting::parse(&args[i + 1])?;
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定代理地址、env 或 none", args[i])));
+            }
+        } else if matches!(args[i].as_str(), "--webhook-found" | "--webhook-complete" | "--webhook-milestone" | "--webhook-error") {
+            // 每种事件各自的通知地址，可以发到不同的频道
+            if i + 1 < args.len() {
+                let url = Some(args[i + 1].clone());
+                match args[i].as_str() {
+                    "--webhook-found" => webhooks.found = url,
+                    "--webhook-complete" => webhooks.complete = url,
+                    "--webhook-milestone" => webhooks.milestone = url,
+                    _ => webhooks.error = url,
+                }
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定 URL", args[i])));
+            }
+        } else if args[i] == "--hook" {
+            // 可以多次使用，按给出的顺序依次执行
+            if i + 1 < args.len() {
+                hooks.push(args[i + 1].clone());
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定命令", args[i])));
+            }
+        } else if args[i] == "--hook-timeout" {
+            match args.get(i + 1).map(|v| v.parse::<u64>()) {
+                Some(Ok(secs)) if secs > 0 => {
+                    hook_timeout = Some(secs);
+                    i += 2;
+                }
+                _ => return Err(GrindError::Config(format!("{} 参数需要指定正整数秒数", args[i]))),
+            }
+        } else if args[i] == "--rng" {
+            match args.get(i + 1).map(String::as_str) {
+                Some(value @ ("os" | "chacha20")) => {
+                    rng = Some(value.to_string());
+                    i += 2;
+                }
+                _ => return Err(GrindError::Config(format!("{} 参数需要指定 os 或 chacha20", args[i]))),
+            }
+        } else if args[i] == "--reseed-every" {
+            let value = args.get(i + 1)
+                .ok_or_else(|| GrindError::Config(format!("{} 参数需要指定数量或时间", args[i])))?;
+            reseed = Some(Reseed::parse(value)?);
+            i += 2;
+        } else if args[i] == "--milestone-every" {
+            match args.get(i + 1).and_then(|v| backend::parse_quantity(v)) {
+                Some(every) => {
+                    milestone_every = Some(every);
+                    i += 2;
+                }
+                _ => return Err(GrindError::Config(format!("{} 参数需要指定正整数（可带 K、M、G 后缀）", args[i]))),
+            }
+        } else if args[i] == "--stall-fraction" {
+            match args.get(i + 1).map(|v| v.parse::<f64>()) {
+                Some(Ok(fraction)) if (0.0..1.0).contains(&fraction) => {
+                    stall_fraction = Some(fraction);
+                    i += 2;
+                }
+                _ => return Err(GrindError::Config(format!("{} 参数需要指定 0 到 1 之间的比例（0 表示不检测）", args[i]))),
+            }
+        } else if args[i] == "--stall-after" {
+            match args.get(i + 1).map(|v| v.parse::<u64>()) {
+                Some(Ok(secs)) if secs > 0 => {
+                    stall_after = Some(secs);
+                    i += 2;
+                }
+                _ => return Err(GrindError::Config(format!("{} 参数需要指定正整数秒数", args[i]))),
+            }
+        } else if args[i] == "--baseline" {
+            match args.get(i + 1) {
+                Some(value) => {
+                    baseline = Some(parse_baseline(value)?);
+                    i += 2;
+                }
+                None => return Err(GrindError::Config(format!("{} 参数需要指定速度（个/秒）、运行目录或运行报告文件", args[i]))),
+            }
+        } else if args[i] == "--baseline-tolerance" {
+            match args.get(i + 1).map(|v| v.parse::<f64>()) {
+                Some(Ok(percent)) if percent > 0.0 && percent < 100.0 => {
+                    baseline_tolerance = Some(percent / 100.0);
+                    i += 2;
+                }
+                _ => return Err(GrindError::Config(format!("{} 参数需要指定 0 到 100 之间的百分比", args[i]))),
+            }
+        } else if args[i] == "--shard-logs" {
+            shard_logs = true;
+            i += 1;
+        } else if args[i] == "--deadline" {
+            match args.get(i + 1).and_then(|v| v.split_once('=')) {
+                Some((pattern, time)) if !pattern.is_empty() && !time.is_empty() => {
+                    deadlines.push((pattern.to_string(), time.to_string()));
+                    i += 2;
+                }
+                _ => return Err(GrindError::Config(format!("{} 参数的格式应为 <目标>=<时间>，例如 abc=24h", args[i]))),
+            }
+        } else if args[i] == "--log-writer" {
+            if i + 1 < args.len() {
+                log_sink = LogSink::parse(&args[i + 1])?;
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定写入方式", args[i])));
+            }
+        } else if args[i] == "--io-priority" {
+            if i + 1 < args.len() {
+                io_priority = IoPriority::parse(&args[i + 1])?;
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定调度策略", args[i])));
+            }
+        } else if args[i] == "--clock" {
+            if i + 1 < args.len() {
+                clock = clock::parse(&args[i + 1])?;
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定时间来源", args[i])));
+            }
+        } else if args[i] == "--low-memory" {
+            low_memory = true;
+            i += 1;
+        } else if args[i] == "--raw-speed" {
+            raw_speed = true;
+            i += 1;
+        } else if args[i] == "--no-outfile" {
+            no_outfile = true;
+            i += 1;
+        } else if args[i] == "--collision-index" {
+            if i + 1 < args.len() {
+                collision_index = Some(args[i + 1].clone());
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定索引文件路径", args[i])));
+            }
+        } else if args[i] == "--collision-capacity" {
+            match args.get(i + 1).map(|v| v.parse::<u64>()) {
+                Some(Ok(capacity)) if capacity > 0 => {
+                    collision_capacity = Some(capacity);
+                    i += 2;
                 }
+                _ => return Err(GrindError::Config(format!("{} 参数需要指定正整数", args[i]))),
+            }
+        } else if args[i] == "--dedup-db" {
+            if i + 1 < args.len() {
+                dedup_db = Some(args[i + 1].clone());
+                i += 2;
             } else {
-                return Err(format!("错误: {} 参数需要指定至少一个前缀", args[i]));
+                return Err(GrindError::Config(format!("{} 参数需要指定数据库文件路径", args[i])));
+            }
+        } else if args[i] == "--no-dedup" {
+            no_dedup = true;
+            i += 1;
+        } else if args[i] == "--stats-addr" {
+            if i + 1 < args.len() {
+                stats_addr = Some(args[i + 1].clone());
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定监听地址", args[i])));
+            }
+        } else if args[i] == "--stats-allow-remote" {
+            stats_allow_remote = true;
+            i += 1;
+        } else if args[i] == "--stats-access" {
+            if i + 1 < args.len() {
+                stats_access = StatsAccess::parse(&args[i + 1])?;
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定访问级别", args[i])));
+            }
+        } else if args[i] == "--split-output" {
+            split_output = true;
+            i += 1;
+        } else if args[i] == "--encrypt-secrets" {
+            // 加密私钥隐含公私钥分离输出
+            split_output = true;
+            encrypt_secrets = true;
+            i += 1;
+        } else if args[i] == "--policy" {
+            if i + 1 < args.len() {
+                secret_policy = Some(SecretPolicy::load(Path::new(&args[i + 1]))?);
+                i += 2;
+            } else {
+                return Err(GrindError::Config(format!("{} 参数需要指定策略文件", args[i])));
             }
         } else if args[i].starts_with('-') {
-            return Err(format!("错误: 未知参数 '{}'", args[i]));
+            return Err(GrindError::Config(format!("未知参数 '{}'", args[i])));
         } else {
             // 如果没有指定 --prefix，但提供了非选项参数，也作为前缀处理
-            prefixes.push(args[i].clone());
+            push_target_arg(&args[i], &mut targets, &mut counted_targets, target::parse_prefix_arg)?;
             i += 1;
         }
     }
     
-    Ok(Config { num_threads, prefixes })
+    Ok(Config {
+        num_threads,
+        targets,
+        counted_targets,
+        split_output,
+        encrypt_secrets,
+        secret_policy,
+        ignore_case,
+        leet,
+        write_keypair_files,
+        no_outfile,
+        collision_index,
+        collision_capacity,
+        dedup_db,
+        no_dedup,
+        stats_addr,
+        stats_allow_remote,
+        stats_access,
+        progress,
+        raw_speed,
+        plain_output,
+        no_keyboard,
+        debug_dump,
+        announce,
+        no_console_secret,
+        result_format,
+        results_to_stdout,
+        attribution,
+        count_format,
+        rate_window,
+        count,
+        keep_counting,
+        max_attempts,
+        max_time,
+        on_complete,
+        wordlist,
+        exclude_words,
+        pattern_files,
+        min_len,
+        transforms,
+        run_dir,
+        low_memory,
+        simulate,
+        rng,
+        reseed,
+        shutdown_grace,
+        max_result_delay,
+        webhooks,
+        proxy,
+        hooks,
+        hook_timeout,
+        milestone_every,
+        stall_fraction,
+        baseline,
+        baseline_tolerance,
+        stall_after,
+        shard_logs,
+        deadlines,
+        log_sink,
+        io_priority,
+        clock,
+    })
+}
+
+// 位置参数和 --prefix、--suffix、--contains 的值，可以带 `:数量`；去掉数量后由 `parse` 解析
+fn push_target_arg(
+    value: &str,
+    targets: &mut Vec<Target>,
+    counted_targets: &mut Vec<usize>,
+    parse: impl FnOnce(&str) -> Result<Target, GrindError>,
+) -> Result<(), GrindError> {
+    let (value, count) = target::split_count(value)?;
+    if count.is_some() {
+        counted_targets.push(targets.len());
+    }
+    targets.push(Target { count: count.unwrap_or(1), ..parse(value)? });
+    Ok(())
 }
 
 fn print_usage() {
@@ -184,7 +709,110 @@ fn print_usage() {
     println!();
     println!("选项:");
     println!("  --threads, -t <数量>    指定使用的工作线程数（默认为CPU核心数）");
-    println!("  --prefix, -p <前缀>     指定要搜索的公钥前缀（可多次使用指定多个前缀）");
+    println!("  --prefix, -p <前缀>     指定要搜索的公钥前缀（可多次使用指定多个前缀）；写作 前缀...后缀 时为同时满足两者的一个目标，例如 Dead...Beef；末尾加 :数量 要求找到多个");
+    println!("  --suffix, -s <后缀>     指定要搜索的公钥后缀，例如 pump（可多次使用，可与前缀混用）；末尾加 :数量 要求找到多个");
+    println!("  --contains <字符串>     搜索在任意位置包含该字符串的公钥，结果中记录匹配的位置（可多次使用，可与前缀、后缀混用）；末尾加 :数量 要求找到多个");
+    println!("  --regex <正则>          用正则表达式描述目标，例如 '^So1[1-9]{{2}}' 或 '^(Sol|Moon)'（在整个公钥中搜索，开头需写 ^；可多次使用）");
+    println!("  --starts-with <前缀:数量>           兼容 solana-keygen grind：搜索指定数量的以该前缀开头的地址");
+    println!("  --ends-with <后缀:数量>             兼容 solana-keygen grind：搜索指定数量的以该后缀结尾的地址");
+    println!("  --starts-and-ends-with <前缀:后缀:数量>  兼容 solana-keygen grind：同时满足前缀和后缀");
+    println!("  --ignore-case           匹配时忽略大小写");
+    println!("  --leet                  目标中的字母也匹配对应的 leet 数字（seek 也匹配 5eek、s33k），结果中记录目标和实际匹配的写法");
+    println!("  --count <数量>          每个目标都要找到指定数量的地址（默认 1）；单个目标写作 前缀:数量（例如 abc:3）时以单独给出的数量为准");
+    println!("  --max-attempts <数量>   本次最多生成这么多密钥对（可带 K、M、G 后缀），目标没找齐也结束，退出码为 {}", EXIT_BUDGET_EXHAUSTED);
+    println!("  --max-time <时间>       本次最长运行时间（90s、30m、2h、7d），到时目标没找齐也结束，退出码为 {}", EXIT_BUDGET_EXHAUSTED);
+    println!("  --keep-counting <数量>  目标找够后不再检查它，但一直运行到共生成指定数量的密钥对，汇总中给出各目标找齐时的尝试次数");
+    println!("  --on-complete <行为>    所有目标都找够之后：stop（默认，结束）、idle（停止生成，等待按 a 追加目标或停止请求）或 continue（继续收集超出数量的结果，直到停止请求）");
+    println!("  --pattern-file <文件>   从文件读入目标，每行一个（abc、...xyz、abc...xyz、...mid...、/正则/），逐行校验并去重，报告无效的行（可多次使用）");
+    println!("  --patterns-from-wordlist <文件>  将词表中的每个单词作为前缀目标（去重，丢弃含非 base58 字符的形式）；内置词表 builtin:en（英文）、builtin:zh（拼音）");
+    println!("  --exclude-words <文件>  配合词表使用：跳过其中的单词（不区分大小写，可多次使用）；builtin:profanity 为内置的不雅词表");
+    println!("  --min-len <长度>        配合词表使用：跳过短于该长度的单词（默认 1）");
+    println!("  --transforms <列表>     配合词表使用：逗号分隔的变换 capitalize、upper、leet，生成原样及所有组合");
+    println!("  --run-dir <目录|auto>   所有输出写入该目录；auto 时为 runs/<运行 ID>/，每次运行各自独立");
+    println!("  --progress              每 5 秒原子地写出 {}（速度、各目标进度、预计剩余时间），供外部脚本读取", stats::PROGRESS_FILE);
+    println!("  --plain                 控制台输出不使用 emoji 等特殊符号（watch、monitor 子命令同样支持；Windows 传统控制台窗口中自动启用）");
+    println!("  --announce <模板>       找到结果时的提示改为按模板生成的一行，通知中也附带（不含私钥），例如 \"找到 {{pattern}}: {{pubkey}}（{{eta_beaten}}）\"");
+    println!("                          占位符: {}", announce::PLACEHOLDERS.iter().map(|(name, _)| format!("{{{}}}", name)).collect::<Vec<_>>().join(" "));
+    println!("  --no-console-secret     找到结果的控制台提示中完全不出现私钥（私钥仍照常写入结果或私钥文件）");
+    println!("  --no-keyboard           不读取按键控制（默认在终端中运行时可按 p 暂停、s 统计、q 停止、a 追加目标）");
+    println!("  --debug-dump            可以随时写出不含私钥的内部状态转储（按 d，或用 debug-dump 子命令），附在问题报告中");
+    println!("  --raw-speed             极速模式：不写 keypairs_XXXX.log、不输出进度，只生成、剪枝和匹配");
+    println!("  --simulate <参数>       模拟模式：以合成公钥检验输出和通知流水线，如 rate=1M,match-every=5M；输出默认写入 {}/", SIMULATION_DIR);
+    println!("  --rng <os|chacha20>     私钥种子的随机数来源：默认 os 每个密钥读取操作系统熵源，chacha20 为每个线程一个定期重新播种的用户态生成器");
+    println!("  --reseed-every <间隔>   chacha20 重新从操作系统熵源取种子的间隔：数量如 10M，或时间如 30s、10min、1h（默认{}）", backend::DEFAULT_RESEED);
+    println!("  --shutdown-grace <秒>   停止时等待日志写入的最长时间（默认 {} 秒），超时后丢弃剩余日志并报告条数", grinder::DEFAULT_SHUTDOWN_GRACE.as_secs());
+    println!("  --max-result-delay <毫秒>  从生成到写出结果的最长延迟目标（默认 {}），工作线程据此自动调整批次大小", grinder::DEFAULT_MAX_RESULT_DELAY.as_millis());
+    println!("  --webhook-found <URL>   每找到一个地址，POST 一个 JSON 事件（不含私钥）到该地址");
+    println!("  --webhook-complete <URL>  所有目标都找够时通知");
+    println!("  --webhook-milestone <URL>  生成数量每越过 --milestone-every 的整数倍时通知");
+    println!("  --proxy <地址|env|none>  webhook 通知使用的代理（http://[用户:密码@]主机:端口，或 socks4://、socks4a://、socks5://、socks5h://）；默认 env 读取 HTTPS_PROXY、HTTP_PROXY、ALL_PROXY 和 NO_PROXY");
+    println!("  --webhook-error <URL>   运行因错误中止、速度停滞、目标可能赶不上截止时间、速度低于基线或钩子失败时通知");
+    println!("  --hook <命令>           每找到一个地址后执行的命令（可多次使用），标准输入为不含私钥的结果 JSON");
+    println!("  --hook-timeout <秒>     单个钩子的最长执行时间（默认 {} 秒），超时后终止并报告失败", hooks::DEFAULT_HOOK_TIMEOUT.as_secs());
+    println!("  --milestone-every <数量>  里程碑间隔，可带 K、M、G 后缀（默认 1G）");
+    println!("  --stall-fraction <比例>  速度低于校准速度的该比例时视为停滞（默认 0.5，0 表示不检测）");
+    println!("  --stall-after <秒>      停滞持续超过该时间后警告并发送 --webhook-error 通知（默认 120 秒）");
+    println!("  --baseline <速度|运行目录>  开始后测得的速度比基线（个/秒，可带 K、M、G 后缀，或取运行报告中的速度）慢得过多时醒目地警告并发送 --webhook-error 通知");
+    println!("  --baseline-tolerance <百分比>  容许比基线慢的百分比（默认 {:.0}）", grinder::DEFAULT_BASELINE_TOLERANCE * 100.0);
+    println!("  --shard-logs            每个工作线程直接写自己的 keypairs_tNN_XXXX.log，不经过共享队列；之后可用 merge-logs 合并");
+    println!("  --deadline <目标>=<时间>  希望该目标找够的截止时间（90m、24h、7d 或 YYYY-MM-DD HH:MM:SS），按当前速度赶不上时警告并发送 --webhook-error 通知");
+    println!("  --log-writer <方式>     常规日志的写入方式：buffered（默认）、io-uring（仅 Linux，不可用时回退到 buffered）或 mmap（预分配的内存映射段）");
+    println!("  --io-priority <策略>    写入线程的调度策略：normal（默认）、nice（工作线程降低优先级）或 dedicated（写入线程独占一个核心，仅 Linux）");
+    println!("  --clock <来源>          时间来源：system（默认）或 coarse（Linux 的粗粒度时钟，精度约几毫秒，每个密钥读取时间的开销更小）");
+    println!("  --low-memory            低内存模式：不写 keypairs_XXXX.log，日志队列和结果缓冲区都很小，适合树莓派等小内存设备");
+    println!("  --no-outfile            使用 grind 风格参数时不写出 <公钥>.json 密钥文件");
+    println!("  --collision-index <文件>  将所有生成的公钥记入持久化的布隆过滤器，检测（理论上不可能的）重复公钥");
+    println!("  --collision-capacity <数量>  新建碰撞索引的设计容量（默认 {}，每个公钥占 8 字节）", collision::DEFAULT_CAPACITY);
+    println!("  --dedup-db <文件>       记录已写入结果的数据库（默认为输出目录中的 {}），多台机器或多个进程共用时同一个结果只写入一次", dedup::DEDUP_DB);
+    println!("  --no-dedup              不记录已写入的结果，重启或多个进程写同一目录时可能出现重复结果");
+    println!("  --stats-addr <地址>     开放统计端口（如 127.0.0.1:7878），可用 monitor 子命令远程查看进度；默认只允许本机回环地址");
+    println!("  --stats-allow-remote    允许统计端口监听回环地址以外的地址（明文传输，没有 TLS，建议改用 SSH 端口转发）");
+    println!("  --stats-access <级别>   统计端口的访问级别：full（默认，含线程数和配置摘要）或 spectate（旁观，只有进度和找到的公钥，可分享到看板）");
+    println!("  --result-format <格式>  结果文件格式：text（默认，写入 result.log）或 jsonl（每行一个 JSON 对象，写入 result.jsonl）");
+    println!("  --results-to-stdout     每个结果行（格式同结果文件）同时写到标准输出，其余提示改写到标准错误，便于接管道处理");
+    println!("  --attribution <策略>    一个公钥同时满足多个目标时计入哪些目标：all（默认，全部计入）、first（排在最前的目标）或 most-specific（最难的目标）");
+    println!("  --units <方式>          进度和汇总中数量、速度的显示方式：raw（默认，原样的整数）、human（K/M/B/T 单位）或 scientific（科学计数法）");
+    println!("  --rate-window <窗口>    每秒输出一行进度，速度取最近 1s、10s 或 1m 的平均，并给出最难的剩余目标还需多久，代替各线程每 1000 个输出一次的进度");
+    println!("  --split-output          公私钥分离：result.log 只记录公钥，私钥写入 0600 权限的 {}", secrets::SECRETS_FILE);
+    println!("  --encrypt-secrets       在分离模式下加密私钥，口令取自环境变量 {}", secrets::PASSPHRASE_ENV);
+    println!("  --policy <文件>         私钥策略文件（JSON），声明私钥能否明文落盘、交给 webhook、加密写入和在控制台显示，违反策略的配置拒绝运行");
+    println!();
+    println!("子命令:");
+    println!("  decrypt-secrets [文件]  解密私钥文件并输出（默认 {}）", secrets::SECRETS_FILE);
+    println!("  migrate [--format csv|jsonl] [--dir <目录>] [--force]");
+    println!("                          将旧的文本日志（keypairs_XXXX.log、result.log）转换为 CSV 或 JSONL（默认 jsonl）");
+    println!("  merge-logs [--dir <目录>] [--output <目录>] [--force]");
+    println!("                          将 --shard-logs 写出的分片日志按序号合并为 keypairs_XXXX.log（默认写入 <目录>/merged）");
+    println!("  query [--pubkey <公钥>] [--pattern <目标>] [--since <时间>] [--backend <后端>] [--dir <目录>]");
+    println!("                          在所有轮转日志和结果文件（文本/CSV/JSONL）中查找记录");
+    println!("  watch [文件] [--from-start] [--interval <毫秒>] [--show-secrets] [--plain]");
+    println!("                          跟踪结果文件（默认 result.log），实时显示新找到的地址");
+    println!("  audit --input <文件或目录> [--rpc <地址>] [--proxy <地址|env|none>]");
+    println!("                          检查密钥对：私钥长度、公私钥一致性、公钥是否在曲线上、重复公钥，可选查询链上余额");
+    println!("  suspend [目录]          请求目录中（默认当前目录）运行的搜索暂停，并把完整状态保存到 {}", state::STATE_FILE);
+    println!("  debug-dump [目录]       请求目录中以 --debug-dump 运行的搜索写出一次内部状态转储");
+    println!("  resume-from <状态文件> [--threads <数量>] [--units <方式>] [--rate-window <窗口>] [--max-attempts <数量>] [--max-time <时间>]");
+    println!("                          从暂停时保存的状态继续搜索（可以在另一台机器上）");
+    println!("  campaign <文件> [选项]  按顺序执行 JSON 文件中定义的多个搜索阶段，选项作为各阶段的默认设置（调试构建需加 --allow-debug）");
+    println!("  monitor <地址> [--plain] [--units <方式>]  连接运行中搜索的统计端口，实时显示速度和进度");
+    println!("  export-mnemonic [公钥] [--input <文件或目录>]");
+    println!("                          把找到的密钥的 32 字节种子编码为 24 个 BIP39 单词以便抄写（不是钱包派生用的助记词，见输出中的说明）");
+    println!("  restore-mnemonic [--outfile <文件>]");
+    println!("                          从标准输入读取 export-mnemonic 的 24 个单词，还原密钥对并输出公钥，可写出 solana-keygen 格式的密钥文件");
+    println!("  import-results <文件或目录> [--dir <目录>] [--pattern <目标>]... [--ignore-case] [--result-format <格式>] [--attribution <策略>] [--split-output] [--encrypt-secrets]");
+    println!("                          校验 solana-keygen grind 等工具找到的密钥对后导入结果文件，去重并计入暂停的搜索的进度");
+    println!("  inventory <import|list|sync|tag|untag|mark> ... [--db <文件>]");
+    println!("                          用 SQLite 清单（默认 {}）管理找到的地址：导入结果、打标签、标记 used/funded/retired、按目标或标签查找，", inventory::INVENTORY_DB);
+    println!("                          sync --rpc-url <地址> 查询余额和最近交易，提示有链上活动的未使用地址");
+    println!("  compare-runs <运行A> <运行B>");
+    println!("                          比较两次运行的 {}（运行目录或报告文件）：速度、硬件、后端和每个目标的找到时间", report::RUN_REPORT_FILE);
+    println!("  estimate <目标...> [--rate <个/秒> | --instance <类型> --catalog <文件>] [--price <每小时价格>]");
+    println!("                          估算每个目标的期望耗时和费用；未给出速度时在本机测速（--measure <秒>，默认 {}）", DEFAULT_MEASURE_SECS);
+    println!("  table [--rate <个/秒>] [--measure <秒>] [--threads <数量>] [--markdown]");
+    println!("                          列出长度 1-{} 的目标（区分和忽略大小写）的期望尝试次数和耗时，便于在搜索前规划目标", estimate::TABLE_MAX_LEN);
+    println!("  serve-check [--addr <地址>] [--allow-remote]");
+    println!("                          长期运行的检查服务（默认 {}）：每行提交公钥和目标的 JSON，返回是否命中及命中位置；\
+              默认只允许本机回环地址，--allow-remote 允许对外监听（明文传输）", check::DEFAULT_CHECK_ADDR);
     println!();
     println!("说明:");
     println!("  可以多次使用 --prefix 指定多个前缀，也可以直接提供前缀作为位置参数");
@@ -196,35 +824,40 @@ fn print_usage() {
     println!("  cargo run -- --prefix seekr --prefix solana");
     println!("  cargo run -- seekr solana");
     println!("  cargo run --release -- -t 16 -p seekr -p test");
+    println!("  cargo run --release -- -s pump -p seekr");
+    println!("  cargo run --release -- -p Dead...Beef");
+    println!("  cargo run --release -- --contains moon -p sol");
+    println!("  cargo run --release -- --regex '^So1[1-9]{{2}}' --regex 'pump$'");
+    println!("  cargo run --release -- --split-output seekr");
+    println!("  cargo run --release -- --starts-with so:2 --ends-with na:1 --ignore-case");
 }
 
-fn main() -> std::io::Result<()> {
+fn main() {
     // 检查是否有 --help 或 -h
     let args: Vec<String> = env::args().collect();
     if args.iter().any(|arg| arg == "--help" || arg == "-h") {
         print_usage();
-        std::process::exit(0);
+        return;
     }
     
-    // 解析命令行参数
-    let config = match parse_args() {
-        Ok(cfg) => cfg,
+    match run_cli(&args) {
+        Ok(GrindOutcome::Completed) => {}
+        Ok(GrindOutcome::BudgetExhausted) => std::process::exit(EXIT_BUDGET_EXHAUSTED),
         Err(err) => {
-            eprintln!("{}", err);
-            print_usage();
+            eprintln!("错误: {}", err);
+            if matches!(err, GrindError::Config(_) | GrindError::Pattern(_)) {
+                print_usage();
+            }
             std::process::exit(1);
         }
-    };
-    
+    }
+}
+
+fn build_grind_config(config: Config) -> Result<GrindConfig, GrindError> {
     // 处理线程数
+    let max_cores = num_cpus::get();
     let num_threads = match config.num_threads {
         Some(num) => {
-            if num == 0 {
-                eprintln!("错误: 线程数必须大于 0");
-                print_usage();
-                std::process::exit(1);
-            }
-            let max_cores = num_cpus::get();
             if num > max_cores * 2 {
                 eprintln!("警告: 指定的线程数 {} 超过建议值（CPU核心数的2倍: {}），可能会影响性能", num, max_cores * 2);
             }
@@ -232,222 +865,1466 @@ fn main() -> std::io::Result<()> {
         }
         None => {
             // 默认使用CPU核心数
-            num_cpus::get()
+            max_cores
         }
     };
     
-    // 处理前缀
-    let target_prefixes: Vec<String> = if config.prefixes.is_empty() {
-        // 如果没有指定前缀，使用默认值
-        vec!["seekr".to_string()]
+    // 处理私钥加密口令
+    let secret_passphrase = if config.encrypt_secrets {
+        match env::var(secrets::PASSPHRASE_ENV) {
+            Ok(p) if !p.is_empty() => Some(p),
+            _ => {
+                return Err(GrindError::Config(format!(
+                    "--encrypt-secrets 需要通过环境变量 {} 提供口令",
+                    secrets::PASSPHRASE_ENV
+                )));
+            }
+        }
     } else {
-        config.prefixes
+        None
     };
     
-    let max_cores = num_cpus::get();
+    // 使用 grind 风格参数时默认写出 <公钥>.json；加密私钥时不写明文密钥文件
+    let write_keypair_files = config.write_keypair_files && !config.no_outfile && !config.encrypt_secrets;
+    if config.write_keypair_files && config.encrypt_secrets && !config.no_outfile {
+        eprintln!("提示: 已启用 --encrypt-secrets，不会写出明文的 <公钥>.json 密钥文件");
+    }
     
-    if num_threads == max_cores {
-        println!("检测到 {} 个CPU核心，将使用 {} 个工作线程（默认）", max_cores, num_threads);
+    // 处理目标：目标文件和词表展开出的目标依次追加在命令行目标之后
+    let mut config_targets = config.targets;
+    for path in &config.pattern_files {
+        let file = wordlist::load_patterns(path.as_ref(), config.ignore_case)?;
+        for invalid in file.invalid.iter().take(PATTERN_REPORT_LIMIT) {
+            eprintln!("警告: 目标文件 {} 第 {} 行 '{}' 无效，已跳过: {}", path, invalid.line, invalid.text, invalid.reason);
+        }
+        if file.invalid.len() > PATTERN_REPORT_LIMIT {
+            eprintln!("警告: 目标文件 {} 另有 {} 行无效，已跳过", path, file.invalid.len() - PATTERN_REPORT_LIMIT);
+        }
+        status!(
+            "目标文件 {} 读入 {} 个目标（跳过 {} 个无效行、{} 个重复的行）",
+            path, file.targets.len(), file.invalid.len(), file.duplicates
+        );
+        config_targets.extend(file.targets);
+    }
+    if let Some(path) = &config.wordlist {
+        let mut exclude = HashSet::new();
+        for source in &config.exclude_words {
+            exclude.extend(wordlist::load_words(source)?);
+        }
+        let expansion = wordlist::expand(path, config.min_len, &config.transforms, &exclude)?;
+        status!(
+            "词表 {} 展开为 {} 个前缀目标（丢弃 {} 个含非 base58 字符的形式）",
+            path, expansion.targets.len(), expansion.rejected
+        );
+        if expansion.excluded > 0 {
+            status!("排除列表中的 {} 个单词已跳过", expansion.excluded);
+        }
+        config_targets.extend(expansion.targets);
+    }
+    if let Some(count) = config.count {
+        for (i, target) in config_targets.iter_mut().enumerate() {
+            if !config.counted_targets.contains(&i) {
+                target.count = count;
+            }
+        }
+    }
+    // 命令行、目标文件和词表中重复的目标合并为一个，而不是各自要求结果
+    let counted: Vec<bool> = (0..config_targets.len()).map(|i| config.counted_targets.contains(&i)).collect();
+    let (config_targets, merged) = target::merge_duplicates(config_targets, &counted, config.ignore_case);
+    for entry in merged.iter().take(PATTERN_REPORT_LIMIT) {
+        status!("目标 '{}' 给出了 {} 次，已合并为一个目标，要求 {} 个", entry.label, entry.sources, entry.count);
+    }
+    if merged.len() > PATTERN_REPORT_LIMIT {
+        status!("另有 {} 个重复的目标已合并", merged.len() - PATTERN_REPORT_LIMIT);
+    }
+    let mut targets: Vec<Target> = if config_targets.is_empty() {
+        // 如果没有指定前缀，使用默认值
+        vec![Target { count: config.count.unwrap_or(1), ..Target::with_prefix("seekr") }]
     } else {
-        println!("检测到 {} 个CPU核心，将使用 {} 个工作线程（用户指定）", max_cores, num_threads);
-    }
-    
-    println!("目标前缀: {:?}", target_prefixes);
-    println!("开始生成密钥对，寻找以这些前缀开头的公钥地址...");
-    println!("程序将持续运行直到所有前缀都被找到\n");
-    println!("日志将保存到 keypairs_XXXX.log 文件中，每个文件最多 {} 行", MAX_LINES_PER_FILE);
-    println!("找到的结果将保存到 result.log 文件中\n");
-
-    // 共享状态
-    let counter = Arc::new(AtomicU64::new(0));
-    let found_prefixes = Arc::new(Mutex::new(HashSet::<String>::new()));
-    let all_found = Arc::new(AtomicBool::new(false));
-    
-    // 使用两个独立的 channel：一个用于常规日志，一个用于结果
-    let (regular_log_tx, regular_log_rx) = mpsc::channel::<LogMessage>();
-    let (result_tx, result_rx) = mpsc::channel::<LogMessage>();
-    
-    // 启动日志写入线程（常规日志）
-    let log_writer_handle = {
-        let regular_log_rx = regular_log_rx;
-        thread::spawn(move || -> std::io::Result<()> {
-            let mut log_writer = LogWriter::new()?;
-            
-            loop {
-                match regular_log_rx.recv() {
-                    Ok(LogMessage::Regular { time_str, counter, public_key, private_key }) => {
-                        let log_line = format!(
-                            "[{}] 序号: {} | 公钥: {} | 私钥: {}",
-                            time_str, counter, public_key, private_key
-                        );
-                        log_writer.write_line(&log_line)?;
-                    }
-                    Ok(LogMessage::Found { .. }) => {
-                        // Found 消息由结果写入线程处理，这里只处理常规日志
-                    }
-                    Err(_) => {
-                        // Channel关闭，所有发送者都已退出
-                        log_writer.finalize()?;
-                        break;
-                    }
-                }
+        config_targets
+    };
+    for (pattern, time) in &config.deadlines {
+        let deadline = target::parse_deadline(time, &*config.clock)?;
+        let mut matched = false;
+        for target in targets.iter_mut().filter(|t| t.to_string() == *pattern) {
+            target.deadline = Some(deadline);
+            matched = true;
+        }
+        if !matched {
+            return Err(GrindError::Config(format!("--deadline 中的目标 '{}' 不在搜索目标中", pattern)));
+        }
+    }
+    // --leet：含有可替换字母的目标在启动时展开为各种 leet 写法，匹配其中任意一种；正则目标不受影响
+    if config.leet {
+        let mut expanded = 0;
+        for target in targets.iter_mut().filter(|t| t.regex.is_none()) {
+            if target.prefix.chars().chain(target.suffix.chars()).chain(target.contains.chars()).any(|c| target::leet_digit(c).is_some()) {
+                target.leet = true;
+                expanded += 1;
             }
-            Ok(())
-        })
+        }
+        status!("leet: {} 个目标中的字母也匹配对应的数字（a→4 b→8 e→3 g→9 i→1 s→5 t→7）", expanded);
+    }
+    
+    // 输出目录：auto 时每次运行使用 runs/<运行 ID>，目录名与结果中的 run_id 一致
+    // 模拟模式的结果是假的，未指定 --run-dir 时与真实输出隔开
+    let run_dir = config.run_dir.or_else(|| config.simulate.map(|_| SIMULATION_DIR.to_string()));
+    let (output_dir, run_id) = match run_dir.as_deref() {
+        Some("auto") => {
+            let run_id = grinder::new_run_id();
+            (Path::new("runs").join(&run_id), Some(run_id))
+        }
+        Some(dir) => (PathBuf::from(dir), None),
+        None => (PathBuf::new(), None),
     };
+    if !output_dir.as_os_str().is_empty() {
+        fs::create_dir_all(&output_dir)?;
+    }
     
-    // 启动结果写入线程（result.log）
-    let result_writer_handle = {
-        let result_rx = result_rx;
-        let found_prefixes = Arc::clone(&found_prefixes);
-        let all_found = Arc::clone(&all_found);
-        let target_prefixes = target_prefixes.clone();
-        thread::spawn(move || -> std::io::Result<()> {
-            let mut result_writer = ResultWriter::new()?;
-            
-            loop {
-                match result_rx.recv() {
-                    Ok(LogMessage::Found { time_str, counter, public_key, private_key, matched_prefix }) => {
-                        // 检查这个前缀是否已经被记录过
-                        let mut found_set = found_prefixes.lock().unwrap();
-                        if !found_set.contains(&matched_prefix) {
-                            found_set.insert(matched_prefix.clone());
-                            
-                            // 写入结果文件
-                            result_writer.write_result(
-                                &time_str,
-                                counter,
-                                &public_key,
-                                &private_key,
-                                &matched_prefix
-                            )?;
-                            
-                            println!(
-                                "✓ 找到匹配前缀 '{}' 的地址！\n序号: {}\n时间: {}\n公钥: {}\n私钥: {}\n",
-                                matched_prefix, counter, time_str, public_key, private_key
-                            );
-                            
-                            // 检查是否所有前缀都已找到
-                            if found_set.len() >= target_prefixes.len() {
-                                println!("🎉 所有目标前缀都已找到！");
-                                all_found.store(true, Ordering::Relaxed);
-                                result_writer.finalize()?;
-                                break;
-                            } else {
-                                let remaining: Vec<_> = target_prefixes.iter()
-                                    .filter(|p| !found_set.contains(*p))
-                                    .collect();
-                                println!("剩余目标: {:?}\n", remaining);
-                            }
-                        }
-                    }
-                    Ok(LogMessage::Regular { .. }) => {
-                        // 结果 channel 不应该收到常规日志
-                    }
-                    Err(_) => {
-                        // Channel关闭
-                        result_writer.finalize()?;
-                        break;
-                    }
-                }
-            }
-            Ok(())
-        })
+    let backend = match (config.simulate, config.rng.as_deref(), config.reseed) {
+        (Some(_), Some(_), _) | (Some(_), _, Some(_)) => {
+            return Err(GrindError::Config("模拟模式不生成真实私钥，不能与 --rng 或 --reseed-every 同时使用".to_string()));
+        }
+        (Some(spec), None, None) => backend::simulated(spec, targets.clone(), config.ignore_case, num_threads, config.clock.clone()),
+        (None, Some("chacha20"), reseed) => backend::cpu_chacha20(reseed.unwrap_or(backend::DEFAULT_RESEED), config.clock.clone()),
+        (None, _, Some(_)) => return Err(GrindError::Config("--reseed-every 只用于 --rng chacha20".to_string())),
+        (None, _, None) => backend::cpu(),
     };
     
-    // 启动工作线程
-    let mut handles = Vec::new();
-    for thread_id in 0..num_threads {
-        let counter = Arc::clone(&counter);
-        let all_found = Arc::clone(&all_found);
-        let regular_log_tx = regular_log_tx.clone();
-        let result_tx = result_tx.clone();
-        let target_prefixes = target_prefixes.clone();
-        
-        let handle = thread::spawn(move || {
-            let mut local_counter = 0u64;
-            
-            loop {
-                // 检查是否所有目标都已找到
-                if all_found.load(Ordering::Relaxed) {
-                    break;
-                }
-                
-                // 生成新的密钥对
-                let keypair = Keypair::new();
-                let public_key = keypair.pubkey();
-                let public_key_str = public_key.to_string();
-                let private_key_str = bs58::encode(keypair.to_bytes()).into_string();
-                
-                // 获取当前时间
-                let now = Local::now();
-                let millis = now.timestamp_millis() % 1000;
-                let time_str = format!("{}-{:03}", now.format("%Y%m%d%H%M%S"), millis);
-                
-                // 原子递增计数器
-                let global_counter = counter.fetch_add(1, Ordering::Relaxed) + 1;
-                local_counter += 1;
-                
-                // 检查公钥是否匹配任何一个目标前缀
-                let mut matched = false;
-                for prefix in &target_prefixes {
-                    if public_key_str.starts_with(prefix) {
-                        matched = true;
-                        // 发送找到的消息到结果 channel
-                        let _ = result_tx.send(LogMessage::Found {
-                            time_str: time_str.clone(),
-                            counter: global_counter,
-                            public_key: public_key_str.clone(),
-                            private_key: private_key_str.clone(),
-                            matched_prefix: prefix.clone(),
-                        });
-                        break;
-                    }
-                }
-                
-                if !matched {
-                    // 发送常规日志消息
-                    let _ = regular_log_tx.send(LogMessage::Regular {
-                        time_str,
-                        counter: global_counter,
-                        public_key: public_key_str,
-                        private_key: private_key_str,
-                    });
-                    
-                    // 控制台输出简化版本（每1000条输出一次，避免刷屏）
-                    if global_counter % 1000 == 0 {
-                        println!("[线程 {}] 已生成 {} 条记录 (本线程生成了 {} 条)", 
-                                thread_id, global_counter, local_counter);
-                    }
-                }
+    let dedup_db = match (config.no_dedup, config.dedup_db) {
+        (true, Some(_)) => return Err(GrindError::Config("--no-dedup 不能与 --dedup-db 同时使用".to_string())),
+        (true, None) => None,
+        // 命令行给出的路径相对于当前目录，而不是输出目录
+        (false, Some(path)) => Some(std::path::absolute(path)?),
+        (false, None) => Some(PathBuf::from(dedup::DEDUP_DB)),
+    };
+    
+    let grind_config = GrindConfig {
+        num_threads,
+        targets,
+        ignore_case: config.ignore_case,
+        split_output: config.split_output,
+        secret_passphrase,
+        secret_policy: config.secret_policy,
+        write_keypair_files,
+        on_found: None,
+        on_event: if config.webhooks.is_empty() {
+            None
+        } else {
+            Some(config.webhooks.clone().into_callback(HttpClient::new(&config.proxy)?))
+        },
+        session_log: true,
+        hooks: config.hooks.iter().cloned().map(Hook::Command).collect(),
+        hook_timeout: config.hook_timeout.map_or(hooks::DEFAULT_HOOK_TIMEOUT, Duration::from_secs),
+        // 只配置了里程碑间隔而没有里程碑 webhook 时也不必产生事件
+        milestone_every: config.webhooks.milestone.as_ref()
+            .map(|_| config.milestone_every.unwrap_or(DEFAULT_MILESTONE_EVERY)),
+        collision_index: config.collision_index.map(Into::into),
+        collision_capacity: config.collision_capacity.unwrap_or(collision::DEFAULT_CAPACITY),
+        dedup_db,
+        stats_addr: config.stats_addr,
+        stats_allow_remote: config.stats_allow_remote,
+        stats_access: config.stats_access,
+        progress_file: config.progress.then(|| output_dir.join(stats::PROGRESS_FILE)),
+        // 模拟运行没有继续的意义，不支持暂停
+        state_file: config.simulate.is_none().then(|| output_dir.join(state::STATE_FILE)),
+        resume: None,
+        shutdown_grace: config.shutdown_grace.map_or(grinder::DEFAULT_SHUTDOWN_GRACE, Duration::from_secs),
+        max_result_delay: config.max_result_delay.map_or(grinder::DEFAULT_MAX_RESULT_DELAY, Duration::from_millis),
+        stall_detection: match config.stall_fraction {
+            Some(0.0) => None,
+            fraction => {
+                let default = StallDetection::default();
+                Some(StallDetection {
+                    fraction: fraction.unwrap_or(default.fraction),
+                    after: config.stall_after.map_or(default.after, Duration::from_secs),
+                })
             }
-        });
-        
-        handles.push(handle);
+        },
+        speed_baseline: config.baseline.map(|keys_per_sec| SpeedBaseline {
+            keys_per_sec,
+            tolerance: config.baseline_tolerance.unwrap_or(grinder::DEFAULT_BASELINE_TOLERANCE),
+        }),
+        raw_speed: config.raw_speed,
+        plain_output: config.plain_output || !console::supports_emoji(),
+        control: (!config.no_keyboard).then(RunControl::new),
+        debug_dump: config.debug_dump,
+        announce: config.announce.clone(),
+        console_secret: !config.no_console_secret,
+        result_format: config.result_format,
+        results_to_stdout: config.results_to_stdout,
+        attribution: config.attribution,
+        count_format: config.count_format,
+        rate_window: config.rate_window,
+        keep_counting: config.keep_counting,
+        max_attempts: config.max_attempts,
+        max_time: config.max_time,
+        on_complete: config.on_complete,
+        low_memory: config.low_memory,
+        shard_logs: config.shard_logs,
+        log_sink: config.log_sink,
+        io_priority: config.io_priority,
+        clock: config.clock,
+        simulation: config.simulate.is_some(),
+        backend,
+        output_dir,
+        run_id,
+    };
+    grind_config.validate()?;
+    Ok(grind_config)
+}
+
+fn run_cli(args: &[String]) -> Result<GrindOutcome, GrindError> {
+    let done = |result: Result<(), GrindError>| result.map(|()| GrindOutcome::Completed);
+    match args.get(1).map(String::as_str) {
+        // 子命令: decrypt-secrets
+        Some("decrypt-secrets") => {
+            let path = args.get(2).map(String::as_str).unwrap_or(secrets::SECRETS_FILE);
+            let passphrase = env::var(secrets::PASSPHRASE_ENV).map_err(|_| {
+                GrindError::Secret(format!("请通过环境变量 {} 提供解密口令", secrets::PASSPHRASE_ENV))
+            })?;
+            return done(secrets::decrypt_file(path, &passphrase));
+        }
+        // 子命令: migrate
+        Some("migrate") => return done(run_migrate(&args[2..])),
+        // 子命令: merge-logs
+        Some("merge-logs") => return done(run_merge_logs(&args[2..])),
+        // 子命令: query
+        Some("query") => return done(run_query(&args[2..])),
+        // 子命令: watch
+        Some("watch") => return done(run_watch(&args[2..])),
+        // 子命令: monitor
+        Some("monitor") => return done(run_monitor(&args[2..])),
+        // 子命令: export-mnemonic / restore-mnemonic
+        Some("export-mnemonic") => return done(run_export_mnemonic(&args[2..])),
+        Some("restore-mnemonic") => return done(run_restore_mnemonic(&args[2..])),
+        // 子命令: import-results
+        Some("import-results") => return done(run_import(&args[2..])),
+        // 子命令: inventory
+        Some("inventory") => return done(run_inventory(&args[2..])),
+        // 子命令: compare-runs
+        Some("compare-runs") => return done(run_compare(&args[2..])),
+        // 子命令: estimate
+        Some("estimate") => return done(run_estimate(&args[2..])),
+        // 子命令: table
+        Some("table") => return done(run_table(&args[2..])),
+        // 子命令: serve-check
+        Some("serve-check") => return done(run_serve_check(&args[2..])),
+        // 子命令: audit
+        Some("audit") => return done(run_audit(&args[2..])),
+        // 子命令: campaign
+        Some("campaign") => return done(run_campaign(&args[2..])),
+        // 子命令: suspend
+        Some("suspend") => return done(run_suspend(&args[2..])),
+        // 子命令: debug-dump
+        Some("debug-dump") => return done(run_debug_dump(&args[2..])),
+        // 子命令: resume-from
+        Some("resume-from") => return run_resume(&args[2..]),
+        _ => {}
     }
     
-    // 等待所有工作线程完成
-    for handle in handles {
-        handle.join().unwrap();
-    }
+    // 解析命令行参数
+    let config = parse_args(args)?;
+    console::set_status_to_stderr(config.results_to_stdout);
+    run_grind(build_grind_config(config)?)
+}
+
+fn run_grind(mut grind_config: GrindConfig) -> Result<GrindOutcome, GrindError> {
+    let (num_threads, max_cores) = (grind_config.num_threads, num_cpus::get());
     
-    // 关闭channel，通知日志写入线程退出
-    drop(regular_log_tx);
-    drop(result_tx);
+    if num_threads == max_cores {
+        status!("检测到 {} 个CPU核心，将使用 {} 个工作线程（默认）", max_cores, num_threads);
+    } else {
+        status!("检测到 {} 个CPU核心，将使用 {} 个工作线程（用户指定）", max_cores, num_threads);
+    }
+    let backend_name = (grind_config.backend)(0).name().to_string();
+    let rng = (grind_config.backend)(0).rng_policy();
+    status!("密钥生成后端: {}，随机数来源: {}", backend_name, rng);
+    // 模拟模式不做椭圆曲线运算，构建方式对它影响不大
+    if !grind_config.simulation {
+        console::warn_debug_build();
+    }
     
-    // 等待日志写入线程完成
-    log_writer_handle.join().unwrap()?;
+    let target_labels: Vec<String> = grind_config.targets.iter()
+        .map(|t| if grind_config.ignore_case { t.to_lowercase() } else { t.clone() })
+        .map(|t| if t.count > 1 { format!("{} (x{})", t, t.count) } else { t.to_string() })
+        .collect();
+    status!("目标{}: {:?}", if grind_config.ignore_case { "（忽略大小写）" } else { "" }, target_labels);
+    print_pattern_analysis(&grind_config);
+    if !grind_config.output_dir.as_os_str().is_empty() {
+        status!("本次运行的所有输出将写入目录: {}", grind_config.output_dir.display());
+    }
+    status!("开始生成密钥对，寻找匹配这些目标的公钥地址...");
+    if let Some(attempts) = grind_config.max_attempts {
+        status!("最多生成 {} 个密钥对，达到后即使目标没有找齐也结束", grind_config.count_format.count(attempts));
+    }
+    if let Some(limit) = grind_config.max_time {
+        status!("最长运行 {}，到时即使目标没有找齐也结束", units::duration(limit.as_secs_f64()));
+    }
+    match grind_config.keep_counting {
+        Some(attempts) => status!("程序将持续运行直到共生成 {} 个密钥对（目标找够后不再检查）\n", grind_config.count_format.count(attempts)),
+        None => match grind_config.on_complete {
+            OnComplete::Stop => status!("程序将持续运行直到所有目标都被找到\n"),
+            OnComplete::Idle => status!("所有目标都被找到后程序停止生成并等待追加目标，收到停止请求（q 或 suspend）才结束\n"),
+            OnComplete::Continue => status!("所有目标都被找到后程序继续收集更多结果，收到停止请求（q 或 suspend）才结束\n"),
+        },
+    }
+    if grind_config.raw_speed && grind_config.rate_window.is_some() {
+        status!("极速模式：不记录常规日志，每秒输出一行进度");
+    } else if grind_config.raw_speed {
+        status!("极速模式：不记录常规日志，也不输出进度（可配合 --stats-addr 查看速度）");
+    } else if grind_config.low_memory {
+        status!("低内存模式：不记录常规日志，只保留很小的缓冲区");
+    } else if grind_config.shard_logs {
+        status!("日志按线程分片保存到 keypairs_tNN_XXXX.log 文件中，每个文件最多 {} 行，可用 merge-logs 合并", MAX_LINES_PER_FILE);
+    } else {
+        status!("日志将保存到 keypairs_XXXX.log 文件中，每个文件最多 {} 行", MAX_LINES_PER_FILE);
+    }
+    match grind_config.io_priority {
+        IoPriority::Normal => {}
+        IoPriority::Nice => status!("工作线程降低调度优先级，CPU 繁忙时写入线程优先得到调度"),
+        IoPriority::Dedicated => status!("写入线程绑定到单独的核心，工作线程使用其余核心"),
+    }
+    let result_file = grind_config.result_format.file_name();
+    if grind_config.split_output {
+        status!("找到的结果将保存到 {}（公钥）和 {}（私钥）文件中\n", result_file, secrets::SECRETS_FILE);
+    } else {
+        status!("找到的结果将保存到 {} 文件中\n", result_file);
+    }
     
-    // 等待结果写入线程完成
-    result_writer_handle.join().unwrap()?;
+    let keyboard = grind_config.control.clone().and_then(|control| {
+        Keyboard::start(control, grind_config.count_format, grind_config.ignore_case, grind_config.state_file.is_some(), grind_config.debug_dump)
+    });
+    if keyboard.is_some() {
+        status!("{}\n", keyboard::HELP);
+    }
+    let mut summary = grinder::run(&grind_config)?;
+    // 通过控制句柄（例如按 a）追加的目标：写入暂停时保存的状态，再从状态继续
+    while let (true, Some(control), Some(state_file)) = (summary.suspended, &grind_config.control, &grind_config.state_file) {
+        let added = control.take_added();
+        if added.is_empty() {
+            break;
+        }
+        let mut state = RunState::load(state_file)?;
+        let key = |label: &str| if grind_config.ignore_case { label.to_lowercase() } else { label.to_string() };
+        for target in &added {
+            // 已有的目标不重复追加，而是在原来的数量上再要求这么多个
+            if let Some(existing) = state.targets.iter_mut().find(|t| key(&t.pattern) == key(&target.to_string())) {
+                existing.count += target.count;
+                existing.satisfied_at = None;
+                status!("目标 '{}' 已存在，要求的数量增加到 {} 个", existing.pattern, existing.count);
+                continue;
+            }
+            state.targets.push(TargetState {
+                pattern: target.to_string(),
+                count: target.count,
+                deadline: None,
+                found: 0,
+                satisfied_at: None,
+            });
+        }
+        state.save(state_file)?;
+        grind_config.targets = state.targets()?;
+        grind_config.resume = Some((&state).into());
+        grind_config.run_id = Some(state.run_id.clone());
+        let labels: Vec<String> = grind_config.targets.iter().map(ToString::to_string).collect();
+        status!("\n已追加目标，继续搜索: {:?}\n", labels);
+        summary = grinder::run(&grind_config)?;
+    }
+    drop(keyboard);
     
     // 显示找到的所有结果
-    let found_set = found_prefixes.lock().unwrap();
-    println!("\n程序完成！");
-    println!("找到的前缀: {:?}", found_set);
-    println!("日志已保存到 keypairs_XXXX.log");
-    println!("结果已保存到 result.log");
-    
-    Ok(())
-}
-
+    let found_labels: Vec<String> = summary.found.iter()
+        .filter(|(_, n)| *n > 0)
+        .map(|(t, n)| format!("{} ({}/{})", t, n, t.count))
+        .collect();
+    if let (true, Some(path)) = (summary.suspended, &grind_config.state_file) {
+        status!("\n程序已暂停，可用 resume-from {} 继续", path.display());
+    } else if summary.suspended {
+        status!("\n程序已停止");
+    } else if let Some(budget) = summary.exhausted {
+        match budget {
+            Budget::Attempts => status!("\n已达到生成数量上限，部分目标没有找齐"),
+            Budget::Time => status!("\n已达到运行时间上限，部分目标没有找齐"),
+        }
+    } else {
+        status!("\n程序完成！");
+    }
+    status!("找到的目标: {:?}", found_labels);
+    let units = grind_config.count_format;
+    status!("共生成 {} 个密钥对，平均 {} 个/秒", units.count(summary.attempts), units.rate(summary.keys_per_sec));
+    if grind_config.keep_counting.is_some() {
+        for ((target, n), satisfied_at) in summary.found.iter().zip(&summary.satisfied_at) {
+            match satisfied_at {
+                Some(at) => status!(
+                    "  {}: 第 {} 次尝试时找齐 {} 个，之后又生成了 {} 个",
+                    target, units.count(*at), n, units.count(summary.attempts.saturating_sub(*at))
+                ),
+                None => status!("  {}: 未找齐 ({}/{})", target, n, target.count),
+            }
+        }
+    } else if summary.exhausted.is_some() {
+        for (target, n) in &summary.found {
+            let state = if *n >= target.count { "已找齐" } else { "未找齐" };
+            status!("  {}: {} ({}/{})", target, state, n, target.count);
+        }
+    }
+    if let Some(suspected) = summary.suspected_collisions {
+        status!("碰撞索引: 本次发现 {} 个疑似重复公钥", suspected);
+    }
+    if summary.duplicate_results > 0 {
+        status!("跳过了 {} 个已经记录过的结果", summary.duplicate_results);
+    }
+    if grind_config.shard_logs {
+        status!("日志已按线程分片保存到 keypairs_tNN_XXXX.log，可用 merge-logs 合并");
+    } else if !grind_config.raw_speed && !grind_config.low_memory {
+        status!("日志已保存到 keypairs_XXXX.log");
+    }
+    status!("结果已保存到 {}（运行 ID: {}）", result_file, summary.run_id);
+    status!("公开结果列表（不含私钥）已保存到 {}", event::PUBLIC_FEED_FILE);
+    RunReport::new(&grind_config, &summary, &backend_name, rng).save(&grind_config.output_dir)?;
+    status!("运行报告已保存到 {}，可用 compare-runs 与其他运行比较", report::RUN_REPORT_FILE);
+    status!("生命周期事件已记录在 {}", session::SESSION_LOG);
+    if grind_config.split_output {
+        status!("私钥已保存到 {}", secrets::SECRETS_FILE);
+    }
+    if !grind_config.output_dir.as_os_str().is_empty() {
+        status!("以上文件都位于目录 {}", grind_config.output_dir.display());
+    }
+    Ok(if summary.exhausted.is_some() { GrindOutcome::BudgetExhausted } else { GrindOutcome::Completed })
+}
+
+// 列出每个目标的精确命中概率和需要注意的统计特征；目标很多（如词表展开）时只列出有警告的目标
+fn print_pattern_analysis(grind_config: &GrindConfig) {
+    let (targets, ignore_case, units) = (&grind_config.targets, grind_config.ignore_case, grind_config.count_format);
+    let analyses: Vec<PatternAnalysis> = targets.iter().map(|t| difficulty::analyze(t, ignore_case)).collect();
+    let verbose = analyses.len() <= PATTERN_REPORT_LIMIT;
+    let mut listed = 0;
+    for analysis in analyses.iter().filter(|a| verbose || !a.warnings.is_empty()) {
+        status!(
+            "  {}: 每个公钥命中概率 {:.3e}，平均约 {} 次尝试（按每个字符 1/58 估算为 {} 次）",
+            analysis.pattern, analysis.probability, units.rate(analysis.expected_attempts()), units.rate(1.0 / analysis.estimated_probability)
+        );
+        for warning in &analysis.warnings {
+            status!("    注意: {}", warning);
+        }
+        listed += 1;
+    }
+    if !verbose {
+        status!("  共 {} 个目标，只列出其中 {} 个有统计特征需要注意的目标", analyses.len(), listed);
+        print_difficulty_histogram(grind_config, &analyses);
+    }
+}
+
+// 目标很多时按长度汇总难度，并按速度估算哪些目标实际上找不到；速度取 --baseline，没有时在本机短暂测速，模拟模式不估算时间
+fn print_difficulty_histogram(grind_config: &GrindConfig, analyses: &[PatternAnalysis]) {
+    let (targets, units) = (&grind_config.targets, grind_config.count_format);
+    let keys_per_sec = match &grind_config.speed_baseline {
+        _ if grind_config.simulation => None,
+        Some(baseline) => {
+            status!("\n目标难度分布（按 --baseline 的 {} 个/秒估算）:", units.rate(baseline.keys_per_sec));
+            Some(baseline.keys_per_sec)
+        }
+        None => {
+            let rate = estimate::measure_rate(grind_config.num_threads, HISTOGRAM_MEASURE);
+            status!("\n目标难度分布（按本机 {} 秒测速的 {} 个/秒估算）:", HISTOGRAM_MEASURE.as_secs(), units.rate(rate));
+            Some(rate)
+        }
+    };
+    if keys_per_sec.is_none() {
+        status!("\n目标难度分布:");
+    }
+    let secs = |attempts: f64| keys_per_sec.map_or(f64::INFINITY, |rate| attempts / rate);
+    let buckets = difficulty::length_histogram(targets, analyses, keys_per_sec.map(|rate| rate * INFEASIBLE_SECS));
+    let widest = buckets.iter().map(|b| b.patterns).max().unwrap_or(1);
+    for bucket in &buckets {
+        let bar = "#".repeat((bucket.patterns * 30).div_ceil(widest) as usize);
+        let time = match keys_per_sec {
+            Some(_) => format!("，约 {}", units::duration(secs(bucket.mean_attempts))),
+            None => String::new(),
+        };
+        let infeasible = if bucket.infeasible > 0 { format!("，其中 {} 个找不到", bucket.infeasible) } else { String::new() };
+        status!(
+            "  长度 {:>2}: {:<30} {:>6} 个，每个平均约 {} 次尝试{}{}",
+            bucket.len, bar, bucket.patterns, units.rate(bucket.mean_attempts), time, infeasible
+        );
+    }
+
+    // 所有目标同时匹配：第一个结果取决于命中概率之和，找齐取决于最难的可行目标
+    let infeasible: u64 = buckets.iter().map(|b| b.infeasible).sum();
+    let attempts: Vec<f64> = targets.iter().zip(analyses)
+        .map(|(target, analysis)| target.count as f64 * analysis.expected_attempts())
+        .collect();
+    let first = 1.0 / analyses.iter().map(|a| a.probability).sum::<f64>();
+    let hardest_feasible = attempts.iter().copied()
+        .filter(|&a| a.is_finite() && keys_per_sec.is_none_or(|rate| a <= rate * INFEASIBLE_SECS))
+        .fold(0.0, f64::max);
+    if infeasible > 0 {
+        status!(
+            "  {} 个目标中有 {} 个{}，实际上找不到",
+            targets.len(), infeasible,
+            if keys_per_sec.is_some() { "按当前速度预计要超过 1 年" } else { "不可能命中" }
+        );
+    }
+    match keys_per_sec {
+        Some(_) => status!(
+            "  第一个结果预计 {}，找齐{}目标至少需要 {}",
+            units::duration(secs(first)), if infeasible > 0 { "其余" } else { "所有" }, units::duration(secs(hardest_feasible))
+        ),
+        None => status!(
+            "  第一个结果平均约 {} 次尝试，找齐{}目标至少需要 {} 次",
+            units.rate(first), if infeasible > 0 { "其余" } else { "所有" }, units.rate(hardest_feasible)
+        ),
+    }
+}
+
+fn run_migrate(args: &[String]) -> Result<(), GrindError> {
+    let mut format = MigrateFormat::Jsonl;
+    let mut dir = String::from(".");
+    let mut force = false;
+    
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--format" | "--dir" => {
+                let value = args.get(i + 1)
+                    .ok_or_else(|| GrindError::Config(format!("{} 参数需要指定值", args[i])))?;
+                if args[i] == "--format" {
+                    format = MigrateFormat::parse(value)?;
+                } else {
+                    dir = value.clone();
+                }
+                i += 2;
+            }
+            "--force" => {
+                force = true;
+                i += 1;
+            }
+            other => return Err(GrindError::Config(format!("migrate: 未知参数 '{}'", other))),
+        }
+    }
+    
+    let migrated = migrate::migrate_dir(std::path::Path::new(&dir), format, force)?;
+    if migrated.is_empty() {
+        println!("在 {} 中没有找到需要迁移的日志文件", dir);
+    }
+    for file in &migrated {
+        println!(
+            "{} -> {}：转换 {} 条{}",
+            file.source.display(),
+            file.destination.display(),
+            file.converted,
+            if file.skipped > 0 { format!("，跳过 {} 条无法识别的行", file.skipped) } else { String::new() }
+        );
+    }
+    Ok(())
+}
+
+fn run_merge_logs(args: &[String]) -> Result<(), GrindError> {
+    let mut dir = PathBuf::from(".");
+    let mut output = None;
+    let mut force = false;
+    
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--dir" | "--output" => {
+                let value = args.get(i + 1)
+                    .ok_or_else(|| GrindError::Config(format!("{} 参数需要指定目录", args[i])))?;
+                if args[i] == "--dir" {
+                    dir = PathBuf::from(value);
+                } else {
+                    output = Some(PathBuf::from(value));
+                }
+                i += 2;
+            }
+            "--force" => {
+                force = true;
+                i += 1;
+            }
+            other => return Err(GrindError::Config(format!("merge-logs: 未知参数 '{}'", other))),
+        }
+    }
+    // 默认写入子目录，避免分片和合并结果混在同一目录中被 query、audit 重复读到
+    let output = output.unwrap_or_else(|| dir.join("merged"));
+    
+    let report = merge::merge_shards(&dir, &output, force)?;
+    println!(
+        "合并了 {} 个线程的 {} 个分片文件：共 {} 条，写入 {} 中的 keypairs_0000.log ~ keypairs_{:04}.log{}",
+        report.threads, report.shard_files, report.merged, output.display(), report.last_file_index,
+        if report.skipped > 0 { format!("，跳过 {} 条无法识别的行", report.skipped) } else { String::new() }
+    );
+    Ok(())
+}
+
+fn run_query(args: &[String]) -> Result<(), GrindError> {
+    let mut filter = QueryFilter::default();
+    let mut dir = String::from(".");
+    
+    let mut i = 0;
+    while i < args.len() {
+        let value = args.get(i + 1)
+            .ok_or_else(|| GrindError::Config(format!("{} 参数需要指定值", args[i])))?;
+        match args[i].as_str() {
+            "--pubkey" => filter.pubkey = Some(value.clone()),
+            "--pattern" => filter.pattern = Some(target::parse_label(value)?),
+            "--since" => filter.since = Some(query::parse_since(value)?),
+            "--backend" => filter.backend = Some(value.clone()),
+            "--dir" => dir = value.clone(),
+            other => return Err(GrindError::Config(format!("query: 未知参数 '{}'", other))),
+        }
+        i += 2;
+    }
+    if filter.pubkey.is_none() && filter.pattern.is_none() && filter.since.is_none() && filter.backend.is_none() {
+        return Err(GrindError::Config("query 至少需要 --pubkey、--pattern、--since 或 --backend 之一".to_string()));
+    }
+    
+    let hits = query::query_dir(std::path::Path::new(&dir), &filter, |hit| {
+        println!("{}:{}: {}", hit.file.display(), hit.line_number, hit.line);
+    })?;
+    println!("共找到 {} 条记录", hits);
+    Ok(())
+}
+
+fn run_watch(args: &[String]) -> Result<(), GrindError> {
+    let mut path = String::from("result.log");
+    let mut from_start = false;
+    let mut show_secrets = false;
+    let mut plain = !console::supports_emoji();
+    let mut interval_ms = 1000u64;
+    
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--from-start" => from_start = true,
+            "--show-secrets" => show_secrets = true,
+            "--plain" => plain = true,
+            "--interval" => {
+                interval_ms = args.get(i + 1)
+                    .and_then(|v| v.parse().ok())
+                    .filter(|&ms| ms > 0)
+                    .ok_or_else(|| GrindError::Config("--interval 参数需要指定正整数（毫秒）".to_string()))?;
+                i += 1;
+            }
+            other if other.starts_with('-') => {
+                return Err(GrindError::Config(format!("watch: 未知参数 '{}'", other)));
+            }
+            other => path = other.to_string(),
+        }
+        i += 1;
+    }
+    
+    println!("正在跟踪 {}（Ctrl+C 退出）...\n", path);
+    watch::watch_file(
+        std::path::Path::new(&path),
+        from_start,
+        std::time::Duration::from_millis(interval_ms),
+        |event| {
+            println!("{}[{}] 匹配 '{}' (序号 {})", if plain { "" } else { "✓ " }, event.timestamp, event.pattern, event.counter);
+            println!("  公钥: {}", event.public_key);
+            if show_secrets {
+                if let Some(secret) = &event.secret_key {
+                    println!("  私钥: {}", secret);
+                }
+            }
+            println!();
+        },
+    )
+}
+
+fn run_monitor(args: &[String]) -> Result<(), GrindError> {
+    let mut addr = None;
+    let mut plain = !console::supports_emoji();
+    let mut units = CountFormat::Raw;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--plain" => plain = true,
+            "--units" => {
+                let value = args.get(i + 1)
+                    .ok_or_else(|| GrindError::Config(format!("monitor: {} 参数需要指定显示方式", args[i])))?;
+                units = CountFormat::parse(value)?;
+                i += 1;
+            }
+            other if other.starts_with('-') => {
+                return Err(GrindError::Config(format!("monitor: 未知参数 '{}'", other)));
+            }
+            other => addr = Some(other.to_string()),
+        }
+        i += 1;
+    }
+    let addr = addr.ok_or_else(|| {
+        GrindError::Config("monitor 需要指定统计端口地址，例如 monitor 127.0.0.1:7878".to_string())
+    })?;
+    
+    println!("正在连接 {}（Ctrl+C 退出）...\n", addr);
+    stats::monitor(&addr, |snapshot| {
+        let eta = snapshot.eta_secs.map_or_else(|| "未知".to_string(), units::duration);
+        // 旁观快照不含线程数
+        let threads = snapshot.threads.map_or_else(String::new, |n| format!(" | {} 个线程", n));
+        println!(
+            "[{}] 已生成 {} 个 | {} 个/秒{} | 预计剩余 {}{}",
+            units::duration(snapshot.elapsed_secs), units.count(snapshot.attempts), units.rate(snapshot.keys_per_sec), threads, eta,
+            if snapshot.access == StatsAccess::Spectate { "（旁观）" } else { "" }
+        );
+        for target in &snapshot.targets {
+            if target.active {
+                println!("  {} ({}/{})，占剩余工作量 {:.1}%", target.pattern, target.found, target.count, target.effort_share * 100.0);
+            } else {
+                println!("  {} ({}/{})，已完成", target.pattern, target.found, target.count);
+            }
+        }
+        for find in snapshot.finds.iter().rev().take(MONITOR_RECENT_FINDS) {
+            println!("  最近找到 {}: {}（{}）", find.pattern, find.public_key, find.timestamp);
+        }
+        if snapshot.finished {
+            println!("\n{}所有目标都已找到，搜索已结束", if plain { "" } else { "🎉 " });
+        }
+    })
+}
+
+fn run_export_mnemonic(args: &[String]) -> Result<(), GrindError> {
+    let mut public_key = None;
+    let mut input = PathBuf::from(".");
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--input" => {
+                input = PathBuf::from(args.get(i + 1)
+                    .ok_or_else(|| GrindError::Config(format!("{} 参数需要指定文件或目录", args[i])))?);
+                i += 2;
+            }
+            other if other.starts_with('-') => return Err(GrindError::Config(format!("export-mnemonic: 未知参数 '{}'", other))),
+            other => {
+                public_key = Some(other.to_string());
+                i += 1;
+            }
+        }
+    }
+    if public_key.is_none() && input.is_dir() {
+        return Err(GrindError::Config("从目录中导出时需要指定公钥".to_string()));
+    }
+    
+    let passphrase = env::var(secrets::PASSPHRASE_ENV).ok().filter(|p| !p.is_empty());
+    let (public_key, seed) = mnemonic::find_seed(&input, public_key.as_deref(), passphrase.as_deref())?;
+    println!("公钥: {}\n", public_key);
+    for (index, word) in mnemonic::seed_to_words(&seed).split(' ').enumerate() {
+        print!("{:>2}. {:<10}", index + 1, word);
+        if index % 4 == 3 {
+            println!();
+        }
+    }
+    println!();
+    eprintln!("{}", mnemonic::DERIVATION_WARNING);
+    eprintln!("这些单词等同于私钥：请抄写在纸上离线保存，不要截图、复制到聊天工具或存入云端。");
+    Ok(())
+}
+
+fn run_restore_mnemonic(args: &[String]) -> Result<(), GrindError> {
+    let outfile = match args {
+        [] => None,
+        [flag, path] if flag == "--outfile" => Some(PathBuf::from(path)),
+        _ => return Err(GrindError::Config("restore-mnemonic 只接受 --outfile <文件>".to_string())),
+    };
+    if let Some(path) = outfile.as_ref().filter(|p| p.exists()) {
+        return Err(GrindError::Config(format!("{} 已存在，不会覆盖", path.display())));
+    }
+    
+    eprintln!("请输入 export-mnemonic 给出的 24 个单词（空格或换行分隔），以空行或 Ctrl+D 结束:");
+    let mut words = String::new();
+    for line in std::io::stdin().lines() {
+        let line = line?;
+        if line.trim().is_empty() && !words.trim().is_empty() {
+            break;
+        }
+        words.push(' ');
+        words.push_str(&line);
+    }
+    let secret = mnemonic::words_to_secret(&words)?;
+    let public_key = bs58::encode(&secret[32..]).into_string();
+    println!("公钥: {}", public_key);
+    if let Some(path) = &outfile {
+        secrets::write_keypair_file(path, &bs58::encode(secret).into_string())?;
+        println!("已写出密钥文件: {}（权限 0600）", path.display());
+    }
+    eprintln!("请核对公钥与靓号地址一致，再向该地址转入资产。");
+    Ok(())
+}
+
+fn run_inventory(args: &[String]) -> Result<(), GrindError> {
+    // --db 可以出现在任意位置，其余为子命令的参数
+    let mut db = PathBuf::from(inventory::INVENTORY_DB);
+    let mut rest = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        if args[i] == "--db" {
+            db = PathBuf::from(args.get(i + 1).ok_or_else(|| GrindError::Config("--db 参数需要指定文件".to_string()))?);
+            i += 2;
+        } else {
+            rest.push(args[i].clone());
+            i += 1;
+        }
+    }
+    let usage = || GrindError::Config(
+        "用法: inventory import <文件或目录>... | list [--status <状态>] [--tag <标签>] [--pattern <目标>] | \
+         sync --rpc-url <地址> [--proxy <地址|env|none>] [--status <状态>] [--tag <标签>] [--pattern <目标>] | \
+         tag <公钥> <标签>... | untag <公钥> <标签>... | mark <公钥> <unused|used|funded|retired>".to_string()
+    );
+    let (command, rest) = rest.split_first().ok_or_else(usage)?;
+    let mut inventory = Inventory::open(&db)?;
+    
+    match command.as_str() {
+        "import" => {
+            if rest.is_empty() {
+                return Err(usage());
+            }
+            for input in rest {
+                let report = inventory.import(Path::new(input))?;
+                println!(
+                    "{}: 读取 {} 个文件中的 {} 条结果，新增 {} 个地址",
+                    input, report.files.len(), report.read, report.added
+                );
+            }
+        }
+        "list" | "sync" => {
+            let mut filter = InventoryFilter::default();
+            let mut rpc_url = None;
+            let mut proxy = ProxySetting::Env;
+            let mut i = 0;
+            while i < rest.len() {
+                let value = rest.get(i + 1)
+                    .ok_or_else(|| GrindError::Config(format!("{} 参数需要指定值", rest[i])))?;
+                match rest[i].as_str() {
+                    "--status" => filter.status = Some(AddressStatus::parse(value)?),
+                    "--tag" => filter.tag = Some(value.clone()),
+                    "--pattern" => filter.pattern = Some(target::parse_label(value)?),
+                    "--rpc-url" if command == "sync" => rpc_url = Some(value.clone()),
+                    "--proxy" if command == "sync" => proxy = ProxySetting::parse(value)?,
+                    other => return Err(GrindError::Config(format!("inventory {}: 未知参数 '{}'", command, other))),
+                }
+                i += 2;
+            }
+            if command == "sync" {
+                let rpc_url = rpc_url.ok_or_else(|| GrindError::Config("inventory sync 需要 --rpc-url 指定 RPC 节点".to_string()))?;
+                return sync_inventory(&inventory, &HttpClient::new(&proxy)?, &rpc_url, &filter);
+            }
+            let entries = inventory.list(&filter)?;
+            for entry in &entries {
+                let balance = entry.lamports
+                    .map(|lamports| format!("  {} lamports", lamports))
+                    .unwrap_or_default();
+                println!(
+                    "{}  {:<8} {:<12} {}  {}{}",
+                    entry.public_key, entry.status.name(), entry.pattern, entry.found_at,
+                    if entry.tags.is_empty() { "-".to_string() } else { entry.tags.join(",") },
+                    balance
+                );
+            }
+            println!("共 {} 个地址", entries.len());
+        }
+        "tag" | "untag" if rest.len() >= 2 => {
+            let (public_key, tags) = rest.split_first().expect("长度已检查");
+            if command == "tag" {
+                inventory.tag(public_key, tags)?;
+            } else {
+                inventory.untag(public_key, tags)?;
+            }
+            println!("已更新 {} 的标签", public_key);
+        }
+        "mark" if rest.len() == 2 => {
+            let status = AddressStatus::parse(&rest[1])?;
+            inventory.set_status(&rest[0], status)?;
+            println!("{} 已标记为 {}", rest[0], status.name());
+        }
+        _ => return Err(usage()),
+    }
+    Ok(())
+}
+
+fn sync_inventory(inventory: &Inventory, client: &HttpClient, rpc_url: &str, filter: &InventoryFilter) -> Result<(), GrindError> {
+    println!("正在通过 {} 查询余额和最近交易...", rpc_url);
+    let synced = inventory.sync(client, rpc_url, filter)?;
+    for address in &synced {
+        println!(
+            "{}  {:<8} {} lamports  最近交易: {}",
+            address.public_key, address.status.name(), address.lamports,
+            match (&address.last_signature, &address.last_activity_at) {
+                (Some(_), Some(time)) => time.as_str(),
+                (Some(signature), None) => signature.as_str(),
+                (None, _) => "无",
+            }
+        );
+    }
+    let unexpected: Vec<_> = synced.iter().filter(|a| a.unexpected()).collect();
+    for address in &unexpected {
+        eprintln!(
+            "警告: {} 标记为 unused，链上却有{}，请确认是否有人在使用它或私钥已经泄露",
+            address.public_key,
+            if address.lamports > 0 { "余额" } else { "交易记录" }
+        );
+    }
+    println!("已同步 {} 个地址，其中 {} 个未使用的地址有链上活动", synced.len(), unexpected.len());
+    Ok(())
+}
+
+fn run_import(args: &[String]) -> Result<(), GrindError> {
+    let input = match args.first() {
+        Some(path) if !path.starts_with('-') => PathBuf::from(path),
+        _ => return Err(GrindError::Config("import-results 需要指定要导入的文件或目录".to_string())),
+    };
+    let mut options = ImportOptions {
+        dir: PathBuf::from("."),
+        targets: Vec::new(),
+        ignore_case: false,
+        result_format: ResultFormat::Text,
+        attribution: Attribution::All,
+        split_output: false,
+        secret_passphrase: None,
+    };
+    let mut encrypt_secrets = false;
+    
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--ignore-case" => options.ignore_case = true,
+            "--split-output" => options.split_output = true,
+            "--encrypt-secrets" => encrypt_secrets = true,
+            flag @ ("--dir" | "--pattern" | "--result-format" | "--attribution") => {
+                let value = args.get(i + 1)
+                    .ok_or_else(|| GrindError::Config(format!("{} 参数需要指定值", flag)))?;
+                match flag {
+                    "--dir" => options.dir = PathBuf::from(value),
+                    "--pattern" => options.targets.push(target::parse_label(value)?),
+                    "--attribution" => options.attribution = Attribution::parse(value)?,
+                    _ => options.result_format = ResultFormat::parse(value)?,
+                }
+                i += 1;
+            }
+            other => return Err(GrindError::Config(format!("import-results: 未知参数 '{}'", other))),
+        }
+        i += 1;
+    }
+    
+    // 导入到暂停的搜索时沿用它的设置；加密的私钥文件需要口令
+    let state = import::load_state(&options.dir)?;
+    if encrypt_secrets || state.as_ref().is_some_and(|s| s.encrypt_secrets) {
+        match env::var(secrets::PASSPHRASE_ENV) {
+            Ok(p) if !p.is_empty() => options.secret_passphrase = Some(p),
+            _ => {
+                return Err(GrindError::Config(format!(
+                    "导入加密的私钥文件需要通过环境变量 {} 提供口令", secrets::PASSPHRASE_ENV
+                )));
+            }
+        }
+        if state.is_none() && !options.split_output {
+            return Err(GrindError::Config("加密私钥需要同时启用公私钥分离输出".to_string()));
+        }
+    }
+    match &state {
+        Some(state) => println!("导入到 {} 中暂停的运行 {}，沿用它的目标和输出设置", options.dir.display(), state.run_id),
+        None if options.targets.is_empty() => {
+            return Err(GrindError::Config("目录中没有 state.json 时需要用 --pattern 指定导入的目标".to_string()));
+        }
+        None => {}
+    }
+    
+    let report = import::import_results(&input, &options)?;
+    for (source, reason) in &report.rejected {
+        println!("未通过校验: {}: {}", source, reason);
+    }
+    println!(
+        "读到 {} 个密钥对：导入 {} 个，已存在 {} 个，不匹配任何目标 {} 个，未通过校验 {} 个{}",
+        report.read, report.imported, report.duplicates, report.unmatched, report.rejected.len(),
+        if report.encrypted > 0 { format!("，私钥已加密而跳过 {} 个", report.encrypted) } else { String::new() }
+    );
+    if let Some(path) = &report.state_updated {
+        println!("已更新 {} 中各目标的已找到数量，resume-from 继续时会计入这些结果", path.display());
+    }
+    Ok(())
+}
+
+fn run_estimate(args: &[String]) -> Result<(), GrindError> {
+    let mut targets = Vec::new();
+    let mut ignore_case = false;
+    let mut rate = None;
+    let mut instance = None;
+    let mut catalog = None;
+    let mut price = None;
+    let mut measure_secs = DEFAULT_MEASURE_SECS;
+    let mut threads = num_cpus::get();
+    
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        if flag == "--ignore-case" {
+            ignore_case = true;
+            i += 1;
+            continue;
+        }
+        if !flag.starts_with('-') {
+            targets.push(target::parse_label(flag)?);
+            i += 1;
+            continue;
+        }
+        let value = args.get(i + 1)
+            .ok_or_else(|| GrindError::Config(format!("{} 参数需要指定值", flag)))?;
+        match flag {
+            "--starts-with" | "--ends-with" | "--starts-and-ends-with" => targets.push(target::parse_grind_arg(flag, value)?),
+            "--rate" => {
+                rate = Some(backend::parse_quantity(value)
+                    .ok_or_else(|| GrindError::Config(format!("{} 参数需要指定正整数（可带 K、M、G 后缀）", flag)))? as f64);
+            }
+            "--price" => match value.parse::<f64>() {
+                Ok(p) if p >= 0.0 => price = Some(p),
+                _ => return Err(GrindError::Config(format!("{} 参数需要指定非负数", flag))),
+            },
+            "--instance" => instance = Some(value.clone()),
+            "--catalog" => catalog = Some(PathBuf::from(value)),
+            "--measure" => match value.parse::<u64>() {
+                Ok(secs) if secs > 0 => measure_secs = secs,
+                _ => return Err(GrindError::Config(format!("{} 参数需要指定正整数秒数", flag))),
+            },
+            "--threads" | "-t" => match value.parse::<usize>() {
+                Ok(n) if n > 0 => threads = n,
+                _ => return Err(GrindError::Config(format!("{} 参数需要指定正整数", flag))),
+            },
+            other => return Err(GrindError::Config(format!("estimate: 未知参数 '{}'", other))),
+        }
+        i += 2;
+    }
+    if targets.is_empty() {
+        return Err(GrindError::Config("estimate 至少需要指定一个目标".to_string()));
+    }
+    difficulty::ensure_reachable(&targets, ignore_case)?;
+    
+    // 速度来源：--rate 优先，其次是目录中的实例类型，都没有时在本机测速
+    let keys_per_sec = match (rate, &instance) {
+        (Some(rate), _) => {
+            println!("速度: {:.0} 个/秒（--rate）", rate);
+            rate
+        }
+        (None, Some(name)) => {
+            let path = catalog.as_ref()
+                .ok_or_else(|| GrindError::Config("--instance 需要配合 --catalog 指定实例类型目录".to_string()))?;
+            let entry = estimate::load_catalog(path)?.remove(name)
+                .ok_or_else(|| GrindError::Config(format!("实例类型目录 {} 中没有 '{}'", path.display(), name)))?;
+            if price.is_none() {
+                price = entry.hourly_price;
+            }
+            println!("速度: {:.0} 个/秒（实例类型 {}）", entry.keys_per_sec, name);
+            entry.keys_per_sec
+        }
+        (None, None) => {
+            println!("正在本机测速（{} 个线程，{} 秒）...", threads, measure_secs);
+            let measured = estimate::measure_rate(threads, Duration::from_secs(measure_secs));
+            println!("速度: {:.0} 个/秒（本机实测）", measured);
+            measured
+        }
+    };
+    if keys_per_sec <= 0.0 {
+        return Err(GrindError::Config("速度必须大于 0".to_string()));
+    }
+    match price {
+        Some(price) => println!("每小时价格: {:.4}\n", price),
+        None => println!("未指定每小时价格（--price），只估算耗时\n"),
+    }
+    
+    let estimates = estimate::estimate(&targets, ignore_case, keys_per_sec, price);
+    for (e, target) in estimates.iter().zip(&targets) {
+        let cost = e.expected_cost.map_or_else(String::new, |cost| format!(" | 期望费用 {:.2}", cost));
+        println!(
+            "{} (x{}): 期望 {:.3e} 次尝试 | 期望耗时 {}{}",
+            e.pattern, e.count, e.expected_attempts, units::duration(e.expected_secs), cost
+        );
+        for warning in difficulty::analyze(target, ignore_case).warnings {
+            println!("  注意: {}", warning);
+        }
+    }
+    // 所有目标同时匹配，整次搜索取决于最难的目标
+    if let Some(hardest) = estimates.iter().max_by(|a, b| a.expected_secs.total_cmp(&b.expected_secs)) {
+        let cost = hardest.expected_cost.map_or_else(String::new, |cost| format!("，期望费用约 {:.2}", cost));
+        println!("\n同时搜索所有目标：期望耗时约 {}{}（取决于最难的目标 {}）", units::duration(hardest.expected_secs), cost, hardest.pattern);
+    }
+    println!("找 1 个结果的实际耗时服从指数分布：约 37% 的情况会超过期望值，约 10% 会超过期望值的 2.3 倍");
+    Ok(())
+}
+
+fn run_table(args: &[String]) -> Result<(), GrindError> {
+    let mut rate = None;
+    let mut measure_secs = DEFAULT_MEASURE_SECS;
+    let mut threads = num_cpus::get();
+    let mut markdown = false;
+    
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        if flag == "--markdown" {
+            markdown = true;
+            i += 1;
+            continue;
+        }
+        let value = args.get(i + 1)
+            .ok_or_else(|| GrindError::Config(format!("{} 参数需要指定值", flag)))?;
+        match flag {
+            "--rate" => {
+                rate = Some(backend::parse_quantity(value)
+                    .ok_or_else(|| GrindError::Config(format!("{} 参数需要指定正整数（可带 K、M、G 后缀）", flag)))? as f64);
+            }
+            "--measure" => match value.parse::<u64>() {
+                Ok(secs) if secs > 0 => measure_secs = secs,
+                _ => return Err(GrindError::Config(format!("{} 参数需要指定正整数秒数", flag))),
+            },
+            "--threads" | "-t" => match value.parse::<usize>() {
+                Ok(n) if n > 0 => threads = n,
+                _ => return Err(GrindError::Config(format!("{} 参数需要指定正整数", flag))),
+            },
+            other => return Err(GrindError::Config(format!("table: 未知参数 '{}'", other))),
+        }
+        i += 2;
+    }
+    
+    let (keys_per_sec, source) = match rate {
+        Some(rate) => (rate, "--rate".to_string()),
+        None => {
+            eprintln!("正在本机测速（{} 个线程，{} 秒）...", threads, measure_secs);
+            (estimate::measure_rate(threads, Duration::from_secs(measure_secs)), format!("本机 {} 个线程实测", threads))
+        }
+    };
+    if keys_per_sec <= 0.0 {
+        return Err(GrindError::Config("速度必须大于 0".to_string()));
+    }
+    
+    let rows = estimate::length_table(estimate::TABLE_MAX_LEN);
+    let time = |attempts: f64| units::duration(attempts / keys_per_sec);
+    if markdown {
+        println!("速度: {:.0} 个/秒（{}）\n", keys_per_sec, source);
+        println!("| 长度 | 区分大小写：期望尝试次数 | 期望耗时 | 忽略大小写：期望尝试次数 | 期望耗时 |");
+        println!("| ---: | ---: | ---: | ---: | ---: |");
+        for row in &rows {
+            println!(
+                "| {} | {:.3e} | {} | {:.3e} | {} |",
+                row.len, row.exact_attempts, time(row.exact_attempts), row.folded_attempts, time(row.folded_attempts)
+            );
+        }
+        println!();
+    } else {
+        println!("速度: {:.0} 个/秒（{}）", keys_per_sec, source);
+        for row in &rows {
+            println!(
+                "长度 {:>2}: 区分大小写 {:.3e} 次（{}） | 忽略大小写 {:.3e} 次（{}）",
+                row.len, row.exact_attempts, time(row.exact_attempts), row.folded_attempts, time(row.folded_attempts)
+            );
+        }
+    }
+    println!("按每个字符 1/58 估算，忽略大小写一列假设每个字符都是大小写均可的字母（1/29）；数字、L、i、o 等只有一种写法的字符仍是 1/58");
+    println!("开头字符在编码中分布不均，具体目标请用 estimate 精确计算；期望值是平均数，单次搜索的实际耗时可能相差数倍");
+    Ok(())
+}
+
+// 基线可以直接给出速度，也可以是以前运行的目录或 run_report.json
+fn parse_baseline(value: &str) -> Result<f64, GrindError> {
+    if let Some(keys_per_sec) = value.parse::<f64>().ok().or_else(|| backend::parse_quantity(value).map(|n| n as f64)) {
+        return Ok(keys_per_sec);
+    }
+    let report = RunReport::load(Path::new(value))
+        .map_err(|e| GrindError::Config(format!("--baseline '{}' 不是速度，作为运行报告读取也失败: {}", value, e)))?;
+    status!("速度基线取自运行 {}：{:.0} 个/秒（{} 线程）", report.run_id, report.keys_per_sec, report.threads);
+    Ok(report.keys_per_sec)
+}
+
+fn run_compare(args: &[String]) -> Result<(), GrindError> {
+    let [a, b] = args else {
+        return Err(GrindError::Config("compare-runs 需要指定两个运行目录或运行报告文件".to_string()));
+    };
+    let (a, b) = (RunReport::load(Path::new(a))?, RunReport::load(Path::new(b))?);
+    
+    let hardware = |r: &RunReport| format!(
+        "{}（{} 个逻辑核心，{}/{}）", r.hardware.cpu_model, r.hardware.logical_cores, r.hardware.os, r.hardware.arch
+    );
+    let rng = |r: &RunReport| r.rng.map_or_else(|| "未记录".to_string(), |rng| rng.to_string());
+    let rows = [
+        ("运行 ID", a.run_id.clone(), b.run_id.clone()),
+        ("开始时间", a.started_at.clone(), b.started_at.clone()),
+        ("后端", a.backend.clone(), b.backend.clone()),
+        ("随机数来源", rng(&a), rng(&b)),
+        ("硬件", hardware(&a), hardware(&b)),
+        ("线程数", a.threads.to_string(), b.threads.to_string()),
+        ("运行时长", units::duration(a.elapsed_secs), units::duration(b.elapsed_secs)),
+        ("生成数量", a.attempts.to_string(), b.attempts.to_string()),
+        ("平均速度", format!("{:.0} 个/秒", a.keys_per_sec), format!("{:.0} 个/秒", b.keys_per_sec)),
+    ];
+    for (label, a, b) in &rows {
+        // 两次运行不同的项用 * 标出
+        let mark = if a == b { ' ' } else { '*' };
+        println!("{} {}: A {} | B {}", mark, label, a, b);
+    }
+    if a.keys_per_sec > 0.0 {
+        println!("\n速度: B 是 A 的 {:.2} 倍", b.keys_per_sec / a.keys_per_sec);
+    }
+    if a.suspended || b.suspended {
+        println!("注意: {} 是暂停前的部分运行", match (a.suspended, b.suspended) {
+            (true, true) => "两次运行都",
+            (true, false) => "运行 A",
+            _ => "运行 B",
+        });
+    }
+    
+    println!("\n各目标找够数量的时间:");
+    let describe = |p: &Option<PatternReport>| match p {
+        None => "未搜索".to_string(),
+        Some(p) => match (p.secs_to_find, p.attempts_to_find) {
+            (Some(secs), Some(at)) => format!("{}（第 {} 次）", units::duration(secs), at),
+            (None, Some(at)) => format!("继续前已找够（第 {} 次）", at),
+            _ => format!("未找够 ({}/{})", p.found, p.count),
+        },
+    };
+    for row in report::compare_patterns(&a, &b) {
+        let speedup = match row.speedup() {
+            Some(s) if s >= 1.0 => format!(" | B 快 {:.2} 倍", s),
+            Some(s) => format!(" | B 慢 {:.2} 倍", 1.0 / s),
+            None => String::new(),
+        };
+        println!("  {}: A {} | B {}{}", row.pattern, describe(&row.a), describe(&row.b), speedup);
+    }
+    println!("单个目标的找到时间随机性很大（服从指数分布），比较后端和硬件时应以平均速度为准");
+    Ok(())
+}
+
+fn run_serve_check(args: &[String]) -> Result<(), GrindError> {
+    let mut addr = check::DEFAULT_CHECK_ADDR.to_string();
+    let mut allow_remote = false;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--addr" => {
+                addr = args.get(i + 1)
+                    .ok_or_else(|| GrindError::Config(format!("{} 参数需要指定地址", args[i])))?
+                    .clone();
+                i += 2;
+            }
+            "--allow-remote" => {
+                allow_remote = true;
+                i += 1;
+            }
+            other => return Err(GrindError::Config(format!("serve-check: 未知参数 '{}'", other))),
+        }
+    }
+    check::serve(&addr, allow_remote)
+}
+
+fn run_suspend(args: &[String]) -> Result<(), GrindError> {
+    let dir = match args {
+        [] => Path::new("."),
+        [dir] if !dir.starts_with('-') => Path::new(dir),
+        [other, ..] => return Err(GrindError::Config(format!("suspend: 未知参数 '{}'", other))),
+    };
+    let request = dir.join(state::SUSPEND_REQUEST);
+    fs::write(&request, "")?;
+    println!(
+        "已写入暂停请求 {}；运行中的搜索会在 1 秒内停止，并把状态保存到同一目录的 {}",
+        request.display(), state::STATE_FILE
+    );
+    Ok(())
+}
+
+fn run_debug_dump(args: &[String]) -> Result<(), GrindError> {
+    let dir = match args {
+        [] => Path::new("."),
+        [dir] if !dir.starts_with('-') => Path::new(dir),
+        [other, ..] => return Err(GrindError::Config(format!("debug-dump: 未知参数 '{}'", other))),
+    };
+    let request = dir.join(debug::DUMP_REQUEST);
+    fs::write(&request, "")?;
+    println!(
+        "已写入转储请求 {}；以 --debug-dump 运行的搜索会在 1 秒内把内部状态写到同一目录的 debug_dump_<时间>.json",
+        request.display()
+    );
+    Ok(())
+}
+
+fn run_resume(args: &[String]) -> Result<GrindOutcome, GrindError> {
+    let mut state_file = None;
+    let mut num_threads = None;
+    let mut count_format = CountFormat::Raw;
+    let mut rate_window = None;
+    let mut max_attempts = None;
+    let mut max_time = None;
+    let mut i = 0;
+    while i < args.len() {
+        if args[i] == "--threads" || args[i] == "-t" {
+            match args.get(i + 1).map(|v| v.parse::<usize>()) {
+                Some(Ok(num)) => {
+                    num_threads = Some(num);
+                    i += 2;
+                }
+                _ => return Err(GrindError::Config(format!("resume-from: {} 参数需要指定线程数", args[i]))),
+            }
+        } else if matches!(args[i].as_str(), "--units" | "--rate-window" | "--max-attempts" | "--max-time") {
+            let value = args.get(i + 1)
+                .ok_or_else(|| GrindError::Config(format!("resume-from: {} 参数需要指定值", args[i])))?;
+            match args[i].as_str() {
+                "--units" => count_format = CountFormat::parse(value)?,
+                "--rate-window" => rate_window = Some(units::parse_rate_window(value)?),
+                "--max-attempts" => {
+                    max_attempts = Some(backend::parse_quantity(value).filter(|&n| n > 0).ok_or_else(|| {
+                        GrindError::Config(format!("resume-from: --max-attempts 参数需要指定正整数，实际为 '{}'", value))
+                    })?);
+                }
+                _ => max_time = Some(units::parse_duration(value)?),
+            }
+            i += 2;
+        } else if args[i].starts_with('-') || state_file.is_some() {
+            return Err(GrindError::Config(format!("resume-from: 未知参数 '{}'", args[i])));
+        } else {
+            state_file = Some(PathBuf::from(&args[i]));
+            i += 1;
+        }
+    }
+    let state_file = state_file.ok_or_else(|| GrindError::Config("resume-from: 需要指定状态文件".to_string()))?;
+    let state = RunState::load(&state_file)?;
+    
+    let secret_passphrase = if state.encrypt_secrets {
+        match env::var(secrets::PASSPHRASE_ENV) {
+            Ok(p) if !p.is_empty() => Some(p),
+            _ => {
+                return Err(GrindError::Config(format!(
+                    "暂停前的运行加密了私钥，继续时需要通过环境变量 {} 提供口令",
+                    secrets::PASSPHRASE_ENV
+                )));
+            }
+        }
+    } else {
+        None
+    };
+    
+    // 换了机器时可以用 --threads 重新指定线程数；显示方式和本次的运行上限不属于运行状态，每次继续时各自指定
+    let grind_config = GrindConfig {
+        num_threads: num_threads.unwrap_or(state.num_threads),
+        count_format,
+        rate_window,
+        max_attempts,
+        max_time,
+        ignore_case: state.ignore_case,
+        split_output: state.split_output,
+        secret_passphrase,
+        write_keypair_files: state.write_keypair_files,
+        raw_speed: state.raw_speed,
+        low_memory: state.low_memory,
+        shard_logs: state.shard_logs,
+        result_format: state.result_format,
+        attribution: state.attribution,
+        secret_policy: state.secret_policy,
+        hooks: state.hooks.iter().cloned().map(Hook::Command).collect(),
+        hook_timeout: Duration::from_secs(state.hook_timeout_secs),
+        keep_counting: state.keep_counting,
+        on_complete: state.on_complete,
+        output_dir: state.output_dir.clone(),
+        dedup_db: state.dedup_db.clone(),
+        backend: match state.rng {
+            RngPolicy::Chacha20 { reseed } => backend::cpu_chacha20(reseed, clock::system()),
+            _ => backend::cpu(),
+        },
+        run_id: Some(state.run_id.clone()),
+        state_file: Some(state_file.clone()),
+        resume: Some((&state).into()),
+        control: Some(RunControl::new()),
+        ..GrindConfig::new(state.targets()?)
+    };
+    status!(
+        "从 {} 继续运行 {}：已生成 {} 个密钥对",
+        state_file.display(), state.run_id, count_format.count(state.attempts)
+    );
+    run_grind(grind_config)
+}
+
+fn run_campaign(args: &[String]) -> Result<(), GrindError> {
+    let path = match args.first() {
+        Some(path) if !path.starts_with('-') => path,
+        _ => return Err(GrindError::Config("campaign 需要指定阶段定义文件".to_string())),
+    };
+    let campaign = Campaign::load(std::path::Path::new(path))?;
+    
+    // 多阶段的计划任务往往要运行很久，调试构建慢得多，除非明确允许否则不启动
+    let allow_debug = args[1..].iter().any(|a| a == "--allow-debug");
+    if console::debug_build() && !allow_debug {
+        return Err(GrindError::Config(format!(
+            "当前程序是调试构建，速度通常比发布构建慢 {}，不适合运行 campaign；\
+             请用 cargo run --release 重新运行，或加 --allow-debug 仍然启动",
+            console::DEBUG_SLOWDOWN
+        )));
+    }
+    console::warn_debug_build();
+    
+    // 其余参数与普通搜索相同，作为各阶段的默认设置；目标只能来自阶段定义
+    let mut grind_args = vec![String::new()];
+    grind_args.extend(args[1..].iter().filter(|a| *a != "--allow-debug").cloned());
+    let config = parse_args(&grind_args)?;
+    if !config.targets.is_empty() {
+        return Err(GrindError::Config("campaign 模式下目标由阶段定义文件指定，不能在命令行中给出".to_string()));
+    }
+    // 阶段定义本身就是状态，暂停单个阶段没有意义
+    // 各阶段依次自动运行，不读取按键
+    let base = GrindConfig { state_file: None, control: None, ..build_grind_config(config)? };
+    
+    println!("共 {} 个阶段，阶段切换记录将追加到 {}\n", campaign.stages.len(), campaign::CAMPAIGN_LOG);
+    let reports = campaign::run_campaign(&campaign, &base)?;
+    let units = base.count_format;
+    
+    println!("\n所有阶段已完成！");
+    for report in &reports {
+        let found: Vec<String> = report.summary.found.iter()
+            .map(|(t, n)| format!("{} ({}/{})", t, n, t.count))
+            .collect();
+        println!("  {}：用时 {}，共生成 {} 个，找到 {:?}", report.name, units::duration(report.elapsed_secs), units.count(report.summary.attempts), found);
+    }
+    Ok(())
+}
+
+fn run_audit(args: &[String]) -> Result<(), GrindError> {
+    let mut input = None;
+    let mut rpc_url = None;
+    let mut proxy = ProxySetting::Env;
+    
+    let mut i = 0;
+    while i < args.len() {
+        let value = args.get(i + 1)
+            .ok_or_else(|| GrindError::Config(format!("{} 参数需要指定值", args[i])))?;
+        match args[i].as_str() {
+            "--input" => input = Some(value.clone()),
+            "--rpc" => rpc_url = Some(value.clone()),
+            "--proxy" => proxy = ProxySetting::parse(value)?,
+            other => return Err(GrindError::Config(format!("audit: 未知参数 '{}'", other))),
+        }
+        i += 2;
+    }
+    let input = input.ok_or_else(|| GrindError::Config("audit 需要通过 --input 指定文件或目录".to_string()))?;
+    
+    let entries = audit::collect_entries(std::path::Path::new(&input))?;
+    let mut report = audit::audit_entries(&entries);
+    if let Some(rpc_url) = &rpc_url {
+        let mut public_keys: Vec<String> = entries.iter()
+            .filter_map(audit::AuditEntry::resolved_public_key)
+            .collect();
+        public_keys.sort();
+        public_keys.dedup();
+        println!("正在通过 {} 查询 {} 个公钥的余额...", rpc_url, public_keys.len());
+        report.funded = audit::lookup_balances(&HttpClient::new(&proxy)?, rpc_url, &public_keys)?;
+        report.balance_checked = public_keys.len() as u64;
+    }
+    
+    for (source, issue) in &report.issues {
+        println!("{}: {}", source, issue);
+    }
+    for (public_key, lamports) in &report.funded {
+        println!("有余额: {} ({} lamports)", public_key, lamports);
+    }
+    println!();
+    println!("共检查 {} 个密钥对，发现 {} 个问题", report.checked, report.issues.len());
+    if report.encrypted > 0 {
+        println!("其中 {} 个私钥已加密，只检查了重复公钥（可先用 decrypt-secrets 解密）", report.encrypted);
+    }
+    if rpc_url.is_some() {
+        println!("查询了 {} 个公钥的余额，其中 {} 个有余额", report.balance_checked, report.funded.len());
+    }
+    Ok(())
+}
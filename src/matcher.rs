@@ -0,0 +1,365 @@
+use crate::base58;
+use crate::target::{self, Target};
+use serde::Serialize;
+use std::collections::HashMap;
+
+// 每个键占用的布隆过滤器位数和哈希函数个数（误判率约 0.1%）
+const BLOOM_BITS_PER_KEY: usize = 16;
+const BLOOM_HASHES: u64 = 4;
+// 有 leet 目标的组最多按这么多个字符分桶，每个 leet 目标展开的写法不超过 2^4 个
+const LEET_KEY_LEN: usize = 4;
+
+/// 位数组布隆过滤器，键为模式的前 k 个（或后 k 个）字符
+struct BloomFilter {
+    bits: Vec<u64>,
+    mask: u64,
+}
+
+impl BloomFilter {
+    fn new(expected_keys: usize) -> Self {
+        let num_bits = (expected_keys.max(1) * BLOOM_BITS_PER_KEY).next_power_of_two().max(64);
+        BloomFilter {
+            bits: vec![0; num_bits / 64],
+            mask: num_bits as u64 - 1,
+        }
+    }
+
+    // FNV-1a 的两个变体，用双重哈希派生出 BLOOM_HASHES 个位置
+    fn hashes(key: &[u8]) -> (u64, u64) {
+        let mut h1: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut h2: u64 = 0x8422_2325_cbf2_9ce4;
+        for &b in key {
+            h1 = (h1 ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3);
+            h2 = (h2 ^ b as u64).wrapping_mul(0x0000_0001_0000_01b3);
+        }
+        (h1, h2 | 1)
+    }
+
+    fn insert(&mut self, key: &[u8]) {
+        let (h1, h2) = Self::hashes(key);
+        for i in 0..BLOOM_HASHES {
+            let bit = h1.wrapping_add(i.wrapping_mul(h2)) & self.mask;
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+
+    fn may_contain(&self, key: &[u8]) -> bool {
+        let (h1, h2) = Self::hashes(key);
+        (0..BLOOM_HASHES).all(|i| {
+            let bit = h1.wrapping_add(i.wrapping_mul(h2)) & self.mask;
+            self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0
+        })
+    }
+}
+
+/// 一组按前 k（或后 k）个字符分桶的目标：布隆过滤器预筛，命中后再查精确索引
+struct Group {
+    key_len: usize,
+    from_end: bool,
+    bloom: BloomFilter,
+    index: HashMap<Vec<u8>, Vec<usize>>,
+}
+
+impl Group {
+    fn new(targets: &[Target], members: Vec<usize>, from_end: bool, ignore_case: bool) -> Option<Self> {
+        let pattern = |idx: usize| -> &[u8] {
+            if from_end { targets[idx].suffix.as_bytes() } else { targets[idx].prefix.as_bytes() }
+        };
+        let mut key_len = members.iter().map(|&idx| pattern(idx).len()).min()?;
+        if members.iter().any(|&idx| targets[idx].leet) {
+            key_len = key_len.min(LEET_KEY_LEN);
+        }
+
+        // leet 目标的每种写法都作为一个键放入索引，预筛仍然只查一次
+        let mut index: HashMap<Vec<u8>, Vec<usize>> = HashMap::new();
+        for idx in members {
+            let bytes = pattern(idx);
+            let key = if from_end { &bytes[bytes.len() - key_len..] } else { &bytes[..key_len] };
+            let mut keys = vec![Vec::new()];
+            for &c in key {
+                keys = keys.iter()
+                    .flat_map(|head| target::match_forms(c, ignore_case, targets[idx].leet).into_iter().map(move |f| [head.as_slice(), &[f]].concat()))
+                    .collect();
+            }
+            for key in keys {
+                index.entry(key).or_default().push(idx);
+            }
+        }
+        let mut bloom = BloomFilter::new(index.len());
+        for key in index.keys() {
+            bloom.insert(key);
+        }
+        Some(Group { key_len, from_end, bloom, index })
+    }
+
+    fn layout(&self) -> GroupLayout {
+        GroupLayout { key_len: self.key_len, buckets: self.index.len(), bloom_bits: self.bloom.bits.len() * 64 }
+    }
+
+    fn key<'a>(&self, candidate: &'a [u8]) -> Option<&'a [u8]> {
+        if candidate.len() < self.key_len {
+            return None;
+        }
+        Some(if self.from_end {
+            &candidate[candidate.len() - self.key_len..]
+        } else {
+            &candidate[..self.key_len]
+        })
+    }
+
+    fn collect(&self, targets: &[Target], candidate: &str, matched: &mut Vec<usize>) {
+        let Some(key) = self.key(candidate.as_bytes()) else { return };
+        if !self.bloom.may_contain(key) {
+            return;
+        }
+        if let Some(members) = self.index.get(key) {
+            matched.extend(members.iter().copied().filter(|&idx| targets[idx].matches(candidate)));
+        }
+    }
+}
+
+/// 编译后的目标集合的结构，用于调试转储
+#[derive(Debug, Clone, Serialize)]
+pub struct MatcherLayout {
+    /// 参与匹配的目标下标
+    pub active: Vec<usize>,
+    /// 编码前剪枝用的公钥高位字节区间数；为空时没有启用剪枝（忽略大小写，或有后缀、包含、正则或 leet 目标）
+    pub byte_ranges: Option<usize>,
+    pub prefix_group: Option<GroupLayout>,
+    pub suffix_group: Option<GroupLayout>,
+    /// 逐个在整个公钥中查找的包含目标数
+    pub contains: usize,
+    /// 逐个用正则检查的目标数
+    pub regexes: usize,
+    /// 既无前缀也无后缀的目标数
+    pub unconstrained: usize,
+    /// 只有前缀目标时需要编码的字符数
+    pub encoded_prefix_len: Option<usize>,
+}
+
+/// 一组目标的分桶方式：按前（后）`key_len` 个字符分为 `buckets` 个桶，布隆过滤器共 `bloom_bits` 位
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupLayout {
+    pub key_len: usize,
+    pub buckets: usize,
+    pub bloom_bits: usize,
+}
+
+/// 编译后的目标集合。目标再多，每个公钥的开销也基本恒定：
+/// 先用布隆过滤器检查开头（或结尾）的 k 个字符，只有预筛命中时才查精确索引并逐个校验。
+pub struct Matcher {
+    targets: Vec<Target>,
+    // 参与匹配的目标下标
+    active: Vec<usize>,
+    // 公钥最高 8 字节的可接受区间（已排序合并）；为 None 时无法在编码前剪枝
+    top_ranges: Option<Vec<(u64, u64)>>,
+    prefix_group: Option<Group>,
+    suffix_group: Option<Group>,
+    // 包含目标，位置不固定，无法按开头或结尾分桶，每个公钥都逐个查找
+    contains: Vec<usize>,
+    // 正则目标，无法分桶，每个公钥都逐个检查
+    regexes: Vec<usize>,
+    // 既无前缀也无后缀的目标，任何公钥都满足
+    unconstrained: Vec<usize>,
+}
+
+impl Matcher {
+    pub fn new(targets: &[Target], ignore_case: bool) -> Self {
+        let all: Vec<usize> = (0..targets.len()).collect();
+        Self::with_active(targets, &all, ignore_case)
+    }
+
+    /// 只匹配 `active` 中列出的目标（例如尚未找够数量的目标），返回的下标仍对应完整的 `targets`
+    pub fn with_active(targets: &[Target], active: &[usize], ignore_case: bool) -> Self {
+        let mut with_prefix = Vec::new();
+        let mut suffix_only = Vec::new();
+        let mut contains = Vec::new();
+        let mut regexes = Vec::new();
+        let mut unconstrained = Vec::new();
+        for &idx in active {
+            let target = &targets[idx];
+            if target.regex.is_some() {
+                regexes.push(idx);
+            } else if !target.prefix.is_empty() {
+                with_prefix.push(idx);
+            } else if !target.suffix.is_empty() {
+                suffix_only.push(idx);
+            } else if !target.contains.is_empty() {
+                contains.push(idx);
+            } else {
+                unconstrained.push(idx);
+            }
+        }
+        // 只有所有目标都带前缀、区分大小写且不是 leet 目标时，才能仅凭原始字节排除公钥
+        let leet = active.iter().any(|&idx| targets[idx].leet);
+        let top_ranges = if ignore_case || leet || !suffix_only.is_empty() || !contains.is_empty() || !regexes.is_empty() || !unconstrained.is_empty() {
+            None
+        } else {
+            let mut ranges: Vec<(u64, u64)> = with_prefix.iter()
+                .flat_map(|&idx| base58::prefix_top_ranges(&targets[idx].prefix))
+                .collect();
+            ranges.sort_unstable();
+            let mut merged: Vec<(u64, u64)> = Vec::with_capacity(ranges.len());
+            for (lo, hi) in ranges {
+                match merged.last_mut() {
+                    Some(last) if lo <= last.1.saturating_add(1) => last.1 = last.1.max(hi),
+                    _ => merged.push((lo, hi)),
+                }
+            }
+            Some(merged)
+        };
+        Matcher {
+            targets: targets.to_vec(),
+            active: active.to_vec(),
+            top_ranges,
+            prefix_group: Group::new(targets, with_prefix, false, ignore_case),
+            suffix_group: Group::new(targets, suffix_only, true, ignore_case),
+            contains,
+            regexes,
+            unconstrained,
+        }
+    }
+
+    /// 仅凭公钥原始字节判断是否可能命中某个目标；返回 false 时无需编码
+    pub fn may_match(&self, public_key: &[u8; 32]) -> bool {
+        let Some(ranges) = &self.top_ranges else { return true };
+        let top = u64::from_be_bytes(public_key[..8].try_into().unwrap());
+        // 找到最后一个下界不大于 top 的区间
+        let pos = ranges.partition_point(|&(lo, _)| lo <= top);
+        pos > 0 && top <= ranges[pos - 1].1
+    }
+
+    /// 所有目标都只有前缀时，匹配只需要编码结果的前若干个字符，返回这个长度
+    pub fn encoded_prefix_len(&self) -> Option<usize> {
+        let active = self.active.iter().map(|&idx| &self.targets[idx]);
+        if active.clone().any(|t| t.prefix.is_empty() || !t.suffix.is_empty()) {
+            return None;
+        }
+        active.map(|t| t.prefix.len()).max()
+    }
+
+    pub fn layout(&self) -> MatcherLayout {
+        MatcherLayout {
+            active: self.active.clone(),
+            byte_ranges: self.top_ranges.as_ref().map(Vec::len),
+            prefix_group: self.prefix_group.as_ref().map(Group::layout),
+            suffix_group: self.suffix_group.as_ref().map(Group::layout),
+            contains: self.contains.len(),
+            regexes: self.regexes.len(),
+            unconstrained: self.unconstrained.len(),
+            encoded_prefix_len: self.encoded_prefix_len(),
+        }
+    }
+
+    /// 返回候选公钥命中的所有目标下标（按下标升序）；未命中时不分配内存
+    pub fn find(&self, candidate: &str) -> Vec<usize> {
+        let mut matched = self.unconstrained.clone();
+        if let Some(group) = &self.prefix_group {
+            group.collect(&self.targets, candidate, &mut matched);
+        }
+        if let Some(group) = &self.suffix_group {
+            group.collect(&self.targets, candidate, &mut matched);
+        }
+        matched.extend(self.contains.iter().copied().filter(|&idx| {
+            let target = &self.targets[idx];
+            if target.leet { target.matches(candidate) } else { candidate.contains(&target.contains) }
+        }));
+        matched.extend(self.regexes.iter().copied().filter(|&idx| self.targets[idx].matches(candidate)));
+        if matched.len() > 1 {
+            matched.sort_unstable();
+        }
+        matched
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 与搜索时一致：忽略大小写时目标和候选公钥都先转为小写
+    fn find(targets: &[Target], candidate: &str, ignore_case: bool) -> Vec<usize> {
+        if ignore_case {
+            let lowered: Vec<Target> = targets.iter().map(Target::to_lowercase).collect();
+            Matcher::new(&lowered, true).find(&candidate.to_lowercase())
+        } else {
+            Matcher::new(targets, false).find(candidate)
+        }
+    }
+
+    fn leet(target: Target) -> Target {
+        Target { leet: true, ..target }
+    }
+
+    #[test]
+    fn case_sensitive_prefix_and_suffix() {
+        let targets = [Target::with_prefix("So"), Target::with_suffix("na")];
+        assert_eq!(find(&targets, "SoXyz123na", false), vec![0, 1]);
+        assert!(find(&targets, "soXyz123NA", false).is_empty());
+    }
+
+    #[test]
+    fn ignore_case_matches_either_case() {
+        let targets = [Target::with_prefix("So"), Target::with_suffix("NA")];
+        assert_eq!(find(&targets, "sOXyz123nA", true), vec![0, 1]);
+        assert_eq!(find(&targets, "SOXyz123", true), vec![0]);
+    }
+
+    #[test]
+    fn ignore_case_targets_with_l() {
+        // 小写的 l 不在 base58 字母表中，但公钥中的 L 转为小写后就是它
+        let targets = [Target::with_prefix("SOL"), Target::with_suffix("L"), Target::with_prefix("La")];
+        assert_eq!(find(&targets, "SoLana9xL", true), vec![0, 1]);
+        assert_eq!(find(&targets, "LAbcdef", true), vec![2]);
+        assert!(find(&targets, "Kabcdef", true).is_empty());
+    }
+
+    #[test]
+    fn byte_ranges_keep_every_matching_key() {
+        let mut seed = 1u64;
+        let keys: Vec<[u8; 32]> = (0..5000).map(|_| {
+            let mut key = [0u8; 32];
+            for b in key.iter_mut() {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                *b = (seed >> 56) as u8;
+            }
+            key
+        }).collect();
+        let targets = [Target::with_prefix("2"), Target::with_prefix("Ab"), Target::with_prefix("zz")];
+        let matcher = Matcher::new(&targets, false);
+        assert!(matcher.layout().byte_ranges.is_some());
+        let mut pruned = 0;
+        for key in &keys {
+            let encoded = bs58::encode(key).into_string();
+            let matched = matcher.find(&encoded);
+            if !matcher.may_match(key) {
+                assert!(matched.is_empty(), "{}", encoded);
+                pruned += 1;
+            }
+            let expected: Vec<usize> = targets.iter().enumerate()
+                .filter(|(_, t)| encoded.starts_with(&t.prefix))
+                .map(|(idx, _)| idx)
+                .collect();
+            assert_eq!(matched, expected, "{}", encoded);
+        }
+        assert!(pruned > keys.len() / 2);
+    }
+
+    #[test]
+    fn leet_prefix_matches_digits() {
+        let targets = [leet(Target::with_prefix("seek"))];
+        for candidate in ["seekXyz", "5eekXyz", "s33kXyz", "533kXyz"] {
+            assert_eq!(find(&targets, candidate, false), vec![0], "{}", candidate);
+        }
+        assert!(find(&targets, "SeekXyz", false).is_empty());
+    }
+
+    #[test]
+    fn leet_with_ignore_case() {
+        let targets = [leet(Target::with_prefix("SeaL")), leet(Target::with_suffix("Sol"))];
+        for candidate in ["SEALabc", "5ea1abc", "sEaLabc", "5EA1abc"] {
+            assert_eq!(find(&targets, candidate, true), vec![0], "{}", candidate);
+        }
+        assert_eq!(find(&targets, "abc5o1", true), vec![1]);
+        assert_eq!(find(&targets, "abcSOL", true), vec![1]);
+    }
+}
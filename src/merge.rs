@@ -0,0 +1,144 @@
+use crate::error::GrindError;
+use crate::grinder::{self, LogWriter, LOG_STEM};
+use crate::logsink::LogSink;
+use crate::records;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Lines};
+use std::path::{Path, PathBuf};
+
+/// `merge-logs` 的结果
+#[derive(Debug, Clone, Default)]
+pub struct MergeReport {
+    /// 读取的分片文件数和涉及的线程数
+    pub shard_files: usize,
+    pub threads: usize,
+    /// 写入合并日志的行数
+    pub merged: u64,
+    /// 无法识别序号而跳过的行数
+    pub skipped: u64,
+    /// 合并日志写到的最后一个文件序号
+    pub last_file_index: u32,
+}
+
+// 一个线程的所有分片文件，按文件序号依次读出
+struct Shard {
+    files: std::vec::IntoIter<PathBuf>,
+    lines: Option<Lines<BufReader<File>>>,
+}
+
+impl Shard {
+    fn next_line(&mut self) -> Result<Option<String>, GrindError> {
+        loop {
+            if let Some(lines) = &mut self.lines {
+                if let Some(line) = lines.next() {
+                    return Ok(Some(line?));
+                }
+            }
+            match self.files.next() {
+                Some(path) => self.lines = Some(BufReader::new(File::open(path)?).lines()),
+                None => return Ok(None),
+            }
+        }
+    }
+
+    // 下一条能识别出序号的记录；跳过的行计入 `skipped`
+    fn next_record(&mut self, skipped: &mut u64) -> Result<Option<(u64, String)>, GrindError> {
+        while let Some(line) = self.next_line()? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match records::parse_keypair_line(&line) {
+                Some(record) => return Ok(Some((record.counter, line))),
+                None => *skipped += 1,
+            }
+        }
+        Ok(None)
+    }
+}
+
+// 找出目录中的分片文件 `keypairs_tNN_XXXX.log`，按线程分组并按文件序号排序
+fn find_shards(dir: &Path) -> Result<BTreeMap<u32, Vec<PathBuf>>, GrindError> {
+    let mut shards: BTreeMap<u32, BTreeMap<u32, PathBuf>> = BTreeMap::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else { continue };
+        let parsed = name.strip_prefix(LOG_STEM)
+            .and_then(|rest| rest.strip_prefix("_t"))
+            .and_then(|rest| rest.split_once('_'))
+            .and_then(|(thread, _)| {
+                let thread_id = thread.parse::<u32>().ok()?;
+                Some((thread_id, grinder::parse_log_index(name, &grinder::shard_stem(thread_id as usize))?))
+            });
+        if let Some((thread, index)) = parsed {
+            shards.entry(thread).or_default().insert(index, path);
+        }
+    }
+    Ok(shards.into_iter().map(|(thread, files)| (thread, files.into_values().collect())).collect())
+}
+
+/// 把 `dir` 中各线程的分片日志按序号交错合并，写入 `output` 目录中按行数轮转的 `keypairs_XXXX.log`。
+/// 每个线程的序号是递增的，因此逐个归并即可，不需要把日志读进内存。分片文件不会被修改；
+/// 输出目录中已有合并日志时需要 `force` 才会覆盖。
+pub fn merge_shards(dir: &Path, output: &Path, force: bool) -> Result<MergeReport, GrindError> {
+    let shards = find_shards(dir)?;
+    if shards.is_empty() {
+        return Err(GrindError::Config(format!("{} 中没有分片日志（keypairs_tNN_XXXX.log）", dir.display())));
+    }
+    fs::create_dir_all(output)?;
+    // 合并出的第一个文件写满后会改名为 `.done.log`
+    let first = [false, true].into_iter()
+        .map(|done| output.join(grinder::log_file_name(LOG_STEM, 0, done)))
+        .find(|path| path.exists());
+    if let Some(first) = first {
+        if !force {
+            return Err(GrindError::Io(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} 已存在，如需覆盖请使用 --force", first.display()),
+            )));
+        }
+        // 覆盖时先清掉以前合并出的文件，避免追加到旧内容后面
+        for entry in fs::read_dir(output)? {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            let merged = grinder::parse_log_index(name, LOG_STEM).is_some();
+            if merged {
+                fs::remove_file(path)?;
+            }
+        }
+    }
+
+    let mut report = MergeReport {
+        shard_files: shards.values().map(Vec::len).sum(),
+        threads: shards.len(),
+        ..MergeReport::default()
+    };
+    let mut readers: Vec<Shard> = shards.into_values()
+        .map(|files| Shard { files: files.into_iter(), lines: None })
+        .collect();
+    // 小顶堆：（序号，分片下标），每个分片在堆中最多有一条
+    let mut heap = BinaryHeap::new();
+    let mut pending: Vec<Option<String>> = vec![None; readers.len()];
+    for (idx, reader) in readers.iter_mut().enumerate() {
+        if let Some((counter, line)) = reader.next_record(&mut report.skipped)? {
+            heap.push(Reverse((counter, idx)));
+            pending[idx] = Some(line);
+        }
+    }
+
+    let mut writer = LogWriter::new(output, LOG_STEM, 0, 0, LogSink::Buffered)?;
+    while let Some(Reverse((_, idx))) = heap.pop() {
+        if let Some(line) = pending[idx].take() {
+            writer.write_line(&line)?;
+            report.merged += 1;
+        }
+        if let Some((counter, line)) = readers[idx].next_record(&mut report.skipped)? {
+            heap.push(Reverse((counter, idx)));
+            pending[idx] = Some(line);
+        }
+    }
+    writer.finalize()?;
+    report.last_file_index = writer.file_index;
+    Ok(report)
+}
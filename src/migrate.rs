@@ -0,0 +1,152 @@
+use crate::error::GrindError;
+use crate::records::{self, KeypairRecord};
+use crate::event::FoundEvent;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// `migrate` 支持的目标格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrateFormat {
+    Csv,
+    Jsonl,
+}
+
+impl MigrateFormat {
+    pub fn parse(value: &str) -> Result<Self, GrindError> {
+        match value {
+            "csv" => Ok(MigrateFormat::Csv),
+            "jsonl" => Ok(MigrateFormat::Jsonl),
+            _ => Err(GrindError::Config(format!("不支持的迁移格式 '{}'（可选: csv, jsonl）", value))),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            MigrateFormat::Csv => "csv",
+            MigrateFormat::Jsonl => "jsonl",
+        }
+    }
+}
+
+/// 单个文件的迁移统计
+#[derive(Debug, Clone)]
+pub struct MigratedFile {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    pub converted: u64,
+    pub skipped: u64,
+}
+
+fn keypair_row(record: &KeypairRecord, format: MigrateFormat) -> String {
+    match format {
+        MigrateFormat::Csv => format!(
+            "{},{},{},{}",
+            record.timestamp, record.counter, record.public_key, record.private_key
+        ),
+        MigrateFormat::Jsonl => serde_json::to_string(record).expect("KeypairRecord 序列化不会失败"),
+    }
+}
+
+fn result_row(event: &FoundEvent, format: MigrateFormat) -> String {
+    match format {
+        MigrateFormat::Csv => format!(
+            "{},{},{},{},{},{},{},{}",
+            event.timestamp,
+            event.counter,
+            event.pattern,
+            event.match_type,
+            event.prefix,
+            event.suffix,
+            event.public_key,
+            event.secret_key.as_deref().unwrap_or_default()
+        ),
+        MigrateFormat::Jsonl => event.to_json(),
+    }
+}
+
+fn convert_file<F>(
+    source: &Path,
+    format: MigrateFormat,
+    header: &str,
+    force: bool,
+    convert: F,
+) -> Result<MigratedFile, GrindError>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let destination = source.with_extension(format.extension());
+    if destination.exists() && !force {
+        return Err(GrindError::Io(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!("{} 已存在，如需覆盖请使用 --force", destination.display()),
+        )));
+    }
+
+    let reader = BufReader::new(File::open(source)?);
+    let mut writer = BufWriter::new(
+        OpenOptions::new().create(true).write(true).truncate(true).open(&destination)?,
+    );
+    if format == MigrateFormat::Csv {
+        writeln!(writer, "{}", header)?;
+    }
+
+    let (mut converted, mut skipped) = (0, 0);
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match convert(&line) {
+            Some(row) => {
+                writeln!(writer, "{}", row)?;
+                converted += 1;
+            }
+            None => skipped += 1,
+        }
+    }
+    writer.flush()?;
+    Ok(MigratedFile {
+        source: source.to_path_buf(),
+        destination,
+        converted,
+        skipped,
+    })
+}
+
+/// 将目录下旧的文本格式日志（keypairs_XXXX.log、result.log）转换为 CSV 或 JSONL，
+/// 保留原有的时间和序号。原文件不会被修改。
+pub fn migrate_dir(dir: &Path, format: MigrateFormat, force: bool) -> Result<Vec<MigratedFile>, GrindError> {
+    let mut keypair_logs: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("keypairs_") && n.ends_with(".log"))
+        })
+        .collect();
+    keypair_logs.sort();
+
+    let mut migrated = Vec::new();
+    for path in keypair_logs {
+        migrated.push(convert_file(
+            &path,
+            format,
+            "timestamp,counter,public_key,secret_key",
+            force,
+            |line| records::parse_keypair_line(line).map(|r| keypair_row(&r, format)),
+        )?);
+    }
+
+    let result_log = dir.join("result.log");
+    if result_log.exists() {
+        migrated.push(convert_file(
+            &result_log,
+            format,
+            "timestamp,counter,pattern,match_type,prefix,suffix,public_key,secret_key",
+            force,
+            |line| records::parse_result_line(line).map(|e| result_row(&e, format)),
+        )?);
+    }
+    Ok(migrated)
+}
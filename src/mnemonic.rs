@@ -0,0 +1,60 @@
+use crate::audit;
+use crate::error::GrindError;
+use crate::secrets;
+use bip39::Mnemonic;
+use solana_sdk::signature::{Keypair, Signer};
+use std::path::Path;
+
+/// 助记词导出时必须同时给出的提示：这不是钱包通常使用的 BIP39 助记词
+pub const DERIVATION_WARNING: &str = "\
+这 24 个词只是把 32 字节的 ed25519 种子按 BIP39 的词表和校验和直接编码，不是通常意义上的钱包助记词：
+  - Phantom、Solflare、Ledger 以及 solana-keygen recover 会对助记词做 PBKDF2 扩展并按派生路径（如 m/44'/501'/0'/0'）
+    推导密钥，导入这些词得到的是另一个地址，不是这个靓号地址；
+  - 只能用 restore-mnemonic 或其他明确支持\"原始种子助记词\"（entropy 即种子、不经派生）的工具恢复；
+  - 恢复后务必核对公钥与靓号地址一致，再向该地址转入资产。";
+
+/// 把 32 字节种子编码为 24 个 BIP39 英文单词（种子即 BIP39 的 entropy）
+pub fn seed_to_words(seed: &[u8; 32]) -> String {
+    Mnemonic::from_entropy(seed)
+        .expect("32 字节是合法的 BIP39 entropy 长度")
+        .to_string()
+}
+
+/// 从 24 个单词还原 64 字节的私钥（`种子 || 公钥`）；单词或校验和不对时报错
+pub fn words_to_secret(words: &str) -> Result<[u8; 64], GrindError> {
+    let mnemonic = Mnemonic::parse_normalized(&words.split_whitespace().collect::<Vec<_>>().join(" "))
+        .map_err(|e| GrindError::Secret(format!("无法解析助记词: {}", e)))?;
+    let entropy = mnemonic.to_entropy();
+    let seed: [u8; 32] = entropy.as_slice().try_into()
+        .map_err(|_| GrindError::Secret(format!("助记词对应 {} 字节，应为 32 字节（24 个词）", entropy.len())))?;
+    Ok(Keypair::new_from_array(seed).to_bytes())
+}
+
+/// 在结果存储（或 solana-keygen 密钥文件）中找到公钥对应的私钥，必要时用口令解密，返回 32 字节种子
+pub fn find_seed(input: &Path, public_key: Option<&str>, passphrase: Option<&str>) -> Result<(String, [u8; 32]), GrindError> {
+    let entries = audit::collect_entries(input)?;
+    let entry = entries.iter()
+        .find(|e| public_key.is_none_or(|p| e.resolved_public_key().as_deref() == Some(p)))
+        .ok_or_else(|| match public_key {
+            Some(p) => GrindError::Config(format!("在 {} 中没有找到公钥 {} 的私钥", input.display(), p)),
+            None => GrindError::Config(format!("{} 中没有密钥对", input.display())),
+        })?;
+    let secret = if entry.secret_key.starts_with("enc1:") {
+        let passphrase = passphrase.ok_or_else(|| {
+            GrindError::Secret(format!("私钥已加密，需要通过环境变量 {} 提供口令", secrets::PASSPHRASE_ENV))
+        })?;
+        secrets::decrypt_secret(passphrase, &entry.secret_key)?
+    } else {
+        entry.secret_key.clone()
+    };
+    let bytes = bs58::decode(&secret).into_vec()
+        .map_err(|e| GrindError::Secret(format!("私钥不是有效的 base58: {}", e)))?;
+    let seed: [u8; 32] = bytes.get(..32).and_then(|s| s.try_into().ok())
+        .ok_or_else(|| GrindError::Secret(format!("私钥为 {} 字节，应为 64 字节", bytes.len())))?;
+    // 不信任记录中的公钥：以种子推导出的公钥为准，与要求的公钥不一致时拒绝
+    let derived = Keypair::new_from_array(seed).pubkey().to_string();
+    if public_key.is_some_and(|p| p != derived) {
+        return Err(GrindError::Secret(format!("{} 中记录的私钥推导出的公钥是 {}，与要求的不一致", entry.source, derived)));
+    }
+    Ok((derived, seed))
+}
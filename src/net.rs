@@ -0,0 +1,289 @@
+use crate::error::GrindError;
+use std::io;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+
+// ureq 不能解析 IPv6 字面地址的代理，交给它时换成这个占位主机名，连接时由 ProxyResolver 换回真实地址
+const IPV6_PROXY_HOST: &str = "ipv6-proxy.invalid";
+// 代理地址未给出端口时的默认端口，与 ureq 相同
+const DEFAULT_HTTP_PROXY_PORT: u16 = 80;
+const DEFAULT_SOCKS_PROXY_PORT: u16 = 1080;
+
+/// 出站 HTTP 请求（webhook 通知、RPC 查询）使用的代理
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ProxySetting {
+    /// 按请求的协议读取 `HTTPS_PROXY` 或 `HTTP_PROXY`，都没有时读取 `ALL_PROXY`（也认小写），
+    /// `NO_PROXY` 中的主机直接连接
+    #[default]
+    Env,
+    /// 直接连接，忽略环境变量
+    Direct,
+    /// 所有请求都经过这个代理
+    Url(String),
+}
+
+impl ProxySetting {
+    /// `env`、`none` 或代理地址（`http://[用户:密码@]主机:端口`，或 `socks4://`、`socks4a://`、`socks5://`、`socks5h://`，
+    /// IPv6 地址写在方括号中）
+    pub fn parse(value: &str) -> Result<Self, GrindError> {
+        match value {
+            "env" => Ok(ProxySetting::Env),
+            "none" => Ok(ProxySetting::Direct),
+            url => {
+                parse_proxy(url, "--proxy")?;
+                Ok(ProxySetting::Url(url.to_string()))
+            }
+        }
+    }
+}
+
+/// 按代理设置发出 HTTP 请求。目标地址可以是主机名、IPv4 或方括号中的 IPv6 地址，
+/// 主机名解析出多个地址时依次尝试，IPv6 不通时回退到 IPv4
+#[derive(Clone)]
+pub struct HttpClient {
+    direct: ureq::Agent,
+    http: Option<ureq::Agent>,
+    https: Option<ureq::Agent>,
+    no_proxy: Vec<String>,
+}
+
+impl HttpClient {
+    /// 代理地址无效或协议不支持时返回错误，而不是在第一次发送时才失败
+    pub fn new(setting: &ProxySetting) -> Result<Self, GrindError> {
+        let (http, https, no_proxy) = match setting {
+            ProxySetting::Env => {
+                let all = env_var(&["ALL_PROXY", "all_proxy"]);
+                let http = env_var(&["HTTP_PROXY", "http_proxy"]).or_else(|| all.clone());
+                let https = env_var(&["HTTPS_PROXY", "https_proxy"]).or(all);
+                let no_proxy = env_var(&["NO_PROXY", "no_proxy"]).map(|v| parse_no_proxy(&v.1)).unwrap_or_default();
+                (http, https, no_proxy)
+            }
+            ProxySetting::Direct => (None, None, Vec::new()),
+            ProxySetting::Url(url) => {
+                let proxy = Some(("--proxy", url.clone()));
+                (proxy.clone(), proxy, Vec::new())
+            }
+        };
+        let proxied = |proxy: Option<(&str, String)>| -> Result<Option<ureq::Agent>, GrindError> {
+            proxy.map(|(source, url)| parse_proxy(&url, source).map(|(proxy, resolver)| {
+                let builder = ureq::AgentBuilder::new().proxy(proxy);
+                match resolver {
+                    Some(resolver) => builder.resolver(resolver).build(),
+                    None => builder.build(),
+                }
+            })).transpose()
+        };
+        Ok(HttpClient {
+            direct: ureq::AgentBuilder::new().build(),
+            http: proxied(http)?,
+            https: proxied(https)?,
+            no_proxy,
+        })
+    }
+
+    pub fn post(&self, url: &str) -> ureq::Request {
+        let proxied = if url.starts_with("https://") { &self.https } else { &self.http };
+        match proxied {
+            Some(agent) if !self.bypasses(url) => agent.post(url),
+            _ => self.direct.post(url),
+        }
+    }
+
+    // NO_PROXY 的写法与 curl 相同：`*` 表示所有主机，其余按域名后缀匹配（`example.com` 和 `.example.com` 都匹配子域名）
+    fn bypasses(&self, url: &str) -> bool {
+        let host = url_host(url).to_ascii_lowercase();
+        self.no_proxy.iter().any(|entry| {
+            entry == "*" || host == *entry || host.strip_suffix(entry.as_str()).is_some_and(|rest| rest.ends_with('.'))
+        })
+    }
+}
+
+// 返回第一个非空的环境变量及其名称
+fn env_var(names: &[&'static str]) -> Option<(&'static str, String)> {
+    names.iter().find_map(|&name| std::env::var(name).ok().filter(|v| !v.trim().is_empty()).map(|v| (name, v)))
+}
+
+fn parse_no_proxy(value: &str) -> Vec<String> {
+    value.split(',')
+        .map(|entry| entry.trim().trim_start_matches('.').trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase())
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+// URL 中的主机部分，IPv6 地址去掉方括号
+fn url_host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    match host.strip_prefix('[') {
+        Some(v6) => v6.split_once(']').map_or(v6, |(addr, _)| addr),
+        None => host.split_once(':').map_or(host, |(name, _)| name),
+    }
+}
+
+// 解析代理地址；IPv6 字面地址换成占位主机名，并返回把它换回真实地址的解析器
+fn parse_proxy(url: &str, source: &str) -> Result<(ureq::Proxy, Option<ProxyResolver>), GrindError> {
+    let (proxy_url, resolver) = proxy_url(url, source)?;
+    let proxy = ureq::Proxy::new(proxy_url)
+        .map_err(|e| GrindError::Config(format!("{} 中的代理地址 '{}' 无效: {}", source, url, e)))?;
+    Ok((proxy, resolver.map(ProxyResolver)))
+}
+
+// 把代理地址改写成 ureq 接受的形式，IPv6 字面地址另外返回
+fn proxy_url(url: &str, source: &str) -> Result<(String, Option<SocketAddr>), GrindError> {
+    let invalid = |reason: &str| GrindError::Config(format!("{} 中的代理地址 '{}' 无效: {}", source, url, reason));
+    let (scheme, rest) = url.split_once("://").unwrap_or(("http", url));
+    let (scheme, default_port) = match scheme {
+        "http" => ("http", DEFAULT_HTTP_PROXY_PORT),
+        "socks4" | "socks4a" => (scheme, DEFAULT_SOCKS_PROXY_PORT),
+        // ureq 的 SOCKS5 总是把主机名交给代理解析，socks5 与 socks5h 相同
+        "socks" | "socks5" | "socks5h" => ("socks5", DEFAULT_SOCKS_PROXY_PORT),
+        "https" => return Err(invalid("不支持以 TLS 连接代理本身，请写作 http://（HTTPS 请求仍通过 CONNECT 加密）")),
+        _ => return Err(invalid("只支持 http://、socks4://、socks4a://、socks5:// 和 socks5h:// 代理")),
+    };
+    let rest = rest.trim_end_matches('/');
+    let (credentials, host) = match rest.rsplit_once('@') {
+        Some((credentials, host)) => (Some(credentials), host),
+        None => (None, rest),
+    };
+    let credentials = credentials.map_or_else(String::new, |c| format!("{}@", c));
+    let (host, resolver) = match host.strip_prefix('[') {
+        Some(v6) => {
+            let (addr, port) = v6.split_once(']').ok_or_else(|| invalid("IPv6 地址缺少 ]"))?;
+            let port = match port.strip_prefix(':') {
+                Some(port) => port.parse::<u16>().map_err(|_| invalid("端口无效"))?,
+                None if port.is_empty() => default_port,
+                None => return Err(invalid("IPv6 地址后只能跟 :端口")),
+            };
+            let addr = addr.parse().map_err(|_| invalid("IPv6 地址无效"))?;
+            (format!("{}:{}", IPV6_PROXY_HOST, port), Some(SocketAddr::new(addr, port)))
+        }
+        None if host.matches(':').count() > 1 => return Err(invalid("IPv6 地址需要写在方括号中，例如 http://[::1]:3128")),
+        None if host.is_empty() => return Err(invalid("缺少主机")),
+        None => (host.to_string(), None),
+    };
+    Ok((format!("{}://{}{}", scheme, credentials, host), resolver))
+}
+
+struct ProxyResolver(SocketAddr);
+
+impl ureq::Resolver for ProxyResolver {
+    fn resolve(&self, netloc: &str) -> io::Result<Vec<SocketAddr>> {
+        if netloc.rsplit_once(':').is_some_and(|(host, _)| host == IPV6_PROXY_HOST) {
+            return Ok(vec![self.0]);
+        }
+        netloc.to_socket_addrs().map(Iterator::collect)
+    }
+}
+
+/// 在 `addr` 上监听：`127.0.0.1:7878`、`[::1]:7878` 或 `主机名:端口`。
+/// `[::]:7878` 在 Linux 等默认双栈的系统上同时接受 IPv4 连接。
+/// 端口以明文 TCP 传输，没有 TLS，`allow_remote` 为假时只允许本机回环地址，否则提示用 `opt_in` 参数明确开启
+pub fn listen(addr: &str, allow_remote: bool, opt_in: &str) -> Result<TcpListener, GrindError> {
+    check_listen_address(addr, allow_remote, opt_in)?;
+    Ok(TcpListener::bind(addr)?)
+}
+
+/// 只检查 [`listen`] 是否接受这个地址，不监听，供开始搜索前校验配置
+pub fn check_listen_address(addr: &str, allow_remote: bool, opt_in: &str) -> Result<(), GrindError> {
+    check_address(addr)?;
+    if !allow_remote {
+        if let Some(remote) = addr.to_socket_addrs()?.find(|a| !a.ip().is_loopback()) {
+            return Err(GrindError::Config(format!(
+                "监听地址 '{}'（{}）不是本机回环地址：该端口以明文传输、不加密，默认只监听 127.0.0.1 或 [::1]；\
+                 跨机器查看建议通过 SSH 端口转发，确需直接对外监听时加上 {}",
+                addr, remote.ip(), opt_in
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// 连接 `addr`，写法与 [`listen`] 相同；主机名解析出多个地址时依次尝试
+pub fn connect(addr: &str) -> Result<TcpStream, GrindError> {
+    check_address(addr)?;
+    Ok(TcpStream::connect(addr)?)
+}
+
+// 不带方括号的 IPv6 地址无法与端口区分，给出明确的提示而不是解析错误
+fn check_address(addr: &str) -> Result<(), GrindError> {
+    if !addr.starts_with('[') && addr.matches(':').count() > 1 {
+        return Err(GrindError::Config(format!("IPv6 地址 '{}' 需要写在方括号中并带端口，例如 [::1]:7878", addr)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rewritten(url: &str) -> (String, Option<SocketAddr>) {
+        proxy_url(url, "--proxy").unwrap()
+    }
+
+    #[test]
+    fn proxy_urls() {
+        assert_eq!(rewritten("http://proxy.example:3128"), ("http://proxy.example:3128".to_string(), None));
+        assert_eq!(rewritten("proxy.example:3128/"), ("http://proxy.example:3128".to_string(), None));
+        assert_eq!(rewritten("http://user:p@ss@10.0.0.1:8080").0, "http://user:p@ss@10.0.0.1:8080");
+        assert_eq!(rewritten("socks4://10.0.0.1").0, "socks4://10.0.0.1");
+        assert_eq!(rewritten("socks4a://10.0.0.1:1081").0, "socks4a://10.0.0.1:1081");
+        assert_eq!(rewritten("socks5h://user:pw@proxy.example:1080").0, "socks5://user:pw@proxy.example:1080");
+        assert_eq!(rewritten("socks://proxy.example").0, "socks5://proxy.example");
+        for url in ["https://proxy.example:3128", "ftp://proxy.example", "http://", "http://::1:3128", "http://[::1", "http://[::1]x"] {
+            assert!(proxy_url(url, "--proxy").is_err(), "{}", url);
+        }
+    }
+
+    #[test]
+    fn ipv6_proxy_urls() {
+        let (url, addr) = rewritten("http://[2001:db8::1]:3128");
+        assert_eq!(url, format!("http://{}:3128", IPV6_PROXY_HOST));
+        assert_eq!(addr, Some("[2001:db8::1]:3128".parse().unwrap()));
+        // 没有端口时按协议取默认端口
+        assert_eq!(rewritten("http://[::1]").1, Some("[::1]:80".parse().unwrap()));
+        let (url, addr) = rewritten("socks5://user:pw@[::1]");
+        assert_eq!(url, format!("socks5://user:pw@{}:1080", IPV6_PROXY_HOST));
+        assert_eq!(addr, Some("[::1]:1080".parse().unwrap()));
+        assert!(parse_proxy("socks5h://[::1]:1080", "ALL_PROXY").is_ok());
+    }
+
+    #[test]
+    fn listen_only_on_loopback_by_default() {
+        assert!(listen("127.0.0.1:0", false, "--allow-remote").is_ok());
+        assert!(listen("localhost:0", false, "--allow-remote").is_ok());
+        for addr in ["0.0.0.0:0", "[::]:0"] {
+            let err = listen(addr, false, "--allow-remote").unwrap_err().to_string();
+            assert!(err.contains("--allow-remote"), "{}", err);
+        }
+        assert!(listen("0.0.0.0:0", true, "--allow-remote").is_ok());
+    }
+
+    #[test]
+    fn url_hosts() {
+        assert_eq!(url_host("https://api.example.com/hook?x=1"), "api.example.com");
+        assert_eq!(url_host("http://user:pw@example.com:8080/"), "example.com");
+        assert_eq!(url_host("http://[2001:db8::1]:8080/rpc"), "2001:db8::1");
+        assert_eq!(url_host("http://[::1]/"), "::1");
+        assert_eq!(url_host("example.com#frag"), "example.com");
+    }
+
+    #[test]
+    fn no_proxy_entries() {
+        let client = |no_proxy: &str| HttpClient {
+            no_proxy: parse_no_proxy(no_proxy),
+            ..HttpClient::new(&ProxySetting::Direct).unwrap()
+        };
+        let listed = client("example.com, .internal.test,[::1],10.0.0.5");
+        assert!(listed.bypasses("https://example.com/hook"));
+        assert!(listed.bypasses("https://api.EXAMPLE.com/hook"));
+        assert!(listed.bypasses("http://rpc.internal.test:8899"));
+        assert!(listed.bypasses("http://[::1]:8899/"));
+        assert!(listed.bypasses("http://10.0.0.5/"));
+        assert!(!listed.bypasses("https://notexample.com/"));
+        assert!(!listed.bypasses("https://example.com.evil.test/"));
+        assert!(!listed.bypasses("http://[::2]:8899/"));
+        let all = client("*");
+        assert!(all.bypasses("https://anything.test/"));
+        assert!(!client("").bypasses("https://example.com/"));
+    }
+}
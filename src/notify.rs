@@ -0,0 +1,57 @@
+use crate::event::RunEvent;
+use crate::grinder::EventCallback;
+use crate::net::HttpClient;
+use std::sync::Arc;
+use std::time::Duration;
+
+// 单个 webhook 请求的超时
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 按事件种类分别配置的 webhook 地址，方便把不同事件发到不同的频道；未配置的种类不发送
+#[derive(Debug, Clone, Default)]
+pub struct Webhooks {
+    /// 找到匹配的地址
+    pub found: Option<String>,
+    /// 所有目标都已找够
+    pub complete: Option<String>,
+    /// 生成数量越过里程碑
+    pub milestone: Option<String>,
+    /// 运行出错中止、速度停滞、目标可能赶不上截止时间、速度低于基线或钩子失败
+    pub error: Option<String>,
+}
+
+impl Webhooks {
+    pub fn is_empty(&self) -> bool {
+        self.found.is_none() && self.complete.is_none() && self.milestone.is_none() && self.error.is_none()
+    }
+
+    fn url_for(&self, event: &RunEvent) -> Option<&str> {
+        match event {
+            RunEvent::Found(_) => self.found.as_deref(),
+            RunEvent::Complete { .. } => self.complete.as_deref(),
+            RunEvent::Milestone { .. } => self.milestone.as_deref(),
+            RunEvent::Stalled { .. }
+            | RunEvent::DeadlineAtRisk { .. }
+            | RunEvent::BelowBaseline { .. }
+            | RunEvent::HookFailed { .. }
+            | RunEvent::Error { .. } => self.error.as_deref(),
+        }
+    }
+
+    /// 转换为交给 `GrindConfig::on_event` 的回调。
+    ///
+    /// 事件以 JSON 请求体经 `client`（按代理设置）POST 到对应的地址。发送在产生事件的线程中同步进行（每个请求最多等待 10 秒），
+    /// 因此运行结束时"全部完成"和"出错"通知一定已经发出；发送失败只输出警告，不影响搜索。
+    pub fn into_callback(self, client: HttpClient) -> EventCallback {
+        Arc::new(move |event: &RunEvent| {
+            let Some(url) = self.url_for(event) else { return };
+            let result = client.post(url)
+                .timeout(WEBHOOK_TIMEOUT)
+                .set("Content-Type", "application/json")
+                .send_string(&event.to_json());
+            if let Err(e) = result {
+                eprintln!("警告: {} 通知发送到 {} 失败: {}", event.kind(), url, e);
+            }
+        })
+    }
+}
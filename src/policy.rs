@@ -0,0 +1,120 @@
+use crate::error::GrindError;
+use crate::grinder::{GrindConfig, LOG_STEM};
+use crate::secrets;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// 私钥可以在控制台上出现几次
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConsolePolicy {
+    /// 不显示私钥（找到结果的提示中私钥被隐去）
+    Never,
+    /// 找到结果时显示一次，不允许再写到标准输出等其他控制台输出
+    Once,
+    /// 不限制
+    Always,
+}
+
+/// 私钥允许流向哪些地方，由团队部署时的 `--policy` 文件声明。四项都必须写明，例如
+/// `{"disk_plaintext": false, "webhook": false, "encrypted_file": true, "console": "once"}`；
+/// 启动时逐个检查会收到私钥的输出，违反策略的配置直接拒绝运行
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SecretPolicy {
+    /// 明文写入磁盘：常规日志、含私钥的结果文件、未加密的私钥文件、`<公钥>.json` 密钥文件
+    pub disk_plaintext: bool,
+    /// 交给进程外部：webhook 通知和库调用方的结果回调
+    pub webhook: bool,
+    /// 加密后写入私钥文件（`--encrypt-secrets`）
+    pub encrypted_file: bool,
+    pub console: ConsolePolicy,
+}
+
+/// 会收到私钥的一类输出
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretSink {
+    DiskPlaintext,
+    Webhook,
+    EncryptedFile,
+    Console,
+}
+
+impl fmt::Display for SecretSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SecretSink::DiskPlaintext => "disk_plaintext",
+            SecretSink::Webhook => "webhook",
+            SecretSink::EncryptedFile => "encrypted_file",
+            SecretSink::Console => "console",
+        })
+    }
+}
+
+impl SecretPolicy {
+    pub fn load(path: &Path) -> Result<Self, GrindError> {
+        let text = std::fs::read_to_string(path)?;
+        serde_json::from_str(&text)
+            .map_err(|e| GrindError::Config(format!("无法解析私钥策略文件 {}: {}", path.display(), e)))
+    }
+
+    /// 找到结果时的控制台提示是否显示私钥
+    pub fn show_on_console(&self) -> bool {
+        self.console != ConsolePolicy::Never
+    }
+
+    /// 检查 `config` 会把私钥交给的每个输出；有违反策略的输出时返回错误，列出所有违反项
+    pub fn enforce(&self, config: &GrindConfig) -> Result<(), GrindError> {
+        let sinks = secret_sinks(config, self.show_on_console() && config.console_shows_secret());
+        let console_copies = sinks.iter().filter(|(sink, _)| *sink == SecretSink::Console).count();
+        let violations: Vec<String> = sinks.iter()
+            .filter(|(sink, _)| match sink {
+                SecretSink::DiskPlaintext => !self.disk_plaintext,
+                SecretSink::Webhook => !self.webhook,
+                SecretSink::EncryptedFile => !self.encrypted_file,
+                SecretSink::Console => match self.console {
+                    ConsolePolicy::Never => true,
+                    ConsolePolicy::Once => console_copies > 1,
+                    ConsolePolicy::Always => false,
+                },
+            })
+            .map(|(sink, what)| format!("{}（{}）", what, sink))
+            .collect();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(GrindError::Config(format!("配置违反私钥策略，私钥会流向: {}", violations.join("; "))))
+        }
+    }
+}
+
+// `config` 运行时会收到私钥的所有输出及其说明；`console_status` 为找到结果的控制台提示是否显示私钥
+fn secret_sinks(config: &GrindConfig, console_status: bool) -> Vec<(SecretSink, String)> {
+    let mut sinks = Vec::new();
+    if !config.raw_speed && !config.low_memory {
+        let file = if config.shard_logs { format!("{}_tNN_XXXX.log", LOG_STEM) } else { format!("{}_XXXX.log", LOG_STEM) };
+        sinks.push((SecretSink::DiskPlaintext, format!("常规日志 {}", file)));
+    }
+    if !config.split_output {
+        sinks.push((SecretSink::DiskPlaintext, format!("结果文件 {}", config.result_format.file_name())));
+    } else if config.secret_passphrase.is_some() {
+        sinks.push((SecretSink::EncryptedFile, format!("加密的私钥文件 {}", secrets::SECRETS_FILE)));
+    } else {
+        sinks.push((SecretSink::DiskPlaintext, format!("私钥文件 {}", secrets::SECRETS_FILE)));
+    }
+    if config.write_keypair_files {
+        sinks.push((SecretSink::DiskPlaintext, "密钥文件 <公钥>.json".to_string()));
+    }
+    // webhook 通知中的结果事件不含私钥，只有库调用方的结果回调会收到私钥
+    if config.on_found.is_some() {
+        sinks.push((SecretSink::Webhook, "结果回调 on_found".to_string()));
+    }
+    if console_status {
+        sinks.push((SecretSink::Console, "找到结果时的控制台提示".to_string()));
+    }
+    if config.results_to_stdout && !config.split_output {
+        sinks.push((SecretSink::Console, "标准输出中的结果行（--results-to-stdout）".to_string()));
+    }
+    sinks
+}
@@ -0,0 +1,119 @@
+use crate::error::GrindError;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// nice 模式下工作线程的 nice 值：足以让写入线程抢到 CPU，又不至于让工作线程在空闲机器上慢下来
+#[cfg(target_os = "linux")]
+const WORKER_NICE: libc::c_int = 10;
+
+/// 写入线程（常规日志、结果）和钩子、通知线程相对工作线程的调度策略。
+/// 生成速度很高时工作线程占满所有核心，写入线程分不到时间片，队列积压忽高忽低；
+/// 这里的策略让写入一侧优先得到调度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IoPriority {
+    /// 不调整，所有线程平等竞争
+    #[default]
+    Normal,
+    /// 工作线程降低调度优先级（nice +10），写入线程保持默认优先级，CPU 繁忙时先得到调度。
+    /// 只调低、不调高，不需要特权
+    Nice,
+    /// 留出一个核心给写入线程：写入线程绑定到可用核心中的最后一个，工作线程绑定到其余核心。
+    /// 只有一个可用核心时不绑定
+    Dedicated,
+}
+
+impl IoPriority {
+    pub fn parse(value: &str) -> Result<Self, GrindError> {
+        match value {
+            "normal" => Ok(IoPriority::Normal),
+            "nice" => Ok(IoPriority::Nice),
+            "dedicated" => Ok(IoPriority::Dedicated),
+            _ => Err(GrindError::Config(format!("不支持的写入线程调度策略 '{}'（可选: normal, nice, dedicated）", value))),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            IoPriority::Normal => "normal",
+            IoPriority::Nice => "nice",
+            IoPriority::Dedicated => "dedicated",
+        }
+    }
+}
+
+// 每个线程都会调用，失败时只提示一次
+static FAILURE_REPORTED: AtomicBool = AtomicBool::new(false);
+
+fn report_failure(reason: &str) {
+    if !FAILURE_REPORTED.swap(true, Ordering::Relaxed) {
+        eprintln!("提示: 无法设置写入线程的调度策略（{}），按 normal 运行", reason);
+    }
+}
+
+/// 在工作线程开始生成前调用
+pub fn enter_worker(policy: IoPriority) {
+    match policy {
+        IoPriority::Normal => {}
+        IoPriority::Nice => lower_priority(),
+        IoPriority::Dedicated => pin_current(false),
+    }
+}
+
+/// 在写入线程、钩子线程开始工作前调用
+pub fn enter_io(policy: IoPriority) {
+    if policy == IoPriority::Dedicated {
+        pin_current(true);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn lower_priority() {
+    // Linux 的 nice 值按线程生效，PRIO_PROCESS 配合线程 ID 只影响当前线程
+    // SAFETY: gettid 和 setpriority 只读写当前线程的调度属性
+    let result = unsafe {
+        let tid = libc::syscall(libc::SYS_gettid) as libc::id_t;
+        libc::setpriority(libc::PRIO_PROCESS, tid, WORKER_NICE)
+    };
+    if result != 0 {
+        report_failure(&std::io::Error::last_os_error().to_string());
+    }
+}
+
+// 写入线程绑定到可用核心中的最后一个，工作线程绑定到其余核心。
+// 新线程继承创建者的亲和性，主线程不绑定，因此每个线程读到的都是进程可用的全部核心
+#[cfg(target_os = "linux")]
+fn pin_current(io: bool) {
+    // SAFETY: cpu_set_t 是普通的位图，全零即空集合；两个调用都只作用于当前线程（pid 0）
+    unsafe {
+        let mut allowed: libc::cpu_set_t = std::mem::zeroed();
+        if libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut allowed) != 0 {
+            report_failure(&std::io::Error::last_os_error().to_string());
+            return;
+        }
+        let cpus: Vec<usize> = (0..libc::CPU_SETSIZE as usize).filter(|&cpu| libc::CPU_ISSET(cpu, &allowed)).collect();
+        let Some((&io_cpu, others)) = cpus.split_last() else { return };
+        if others.is_empty() {
+            return;
+        }
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        if io {
+            libc::CPU_SET(io_cpu, &mut set);
+        } else {
+            for &cpu in others {
+                libc::CPU_SET(cpu, &mut set);
+            }
+        }
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            report_failure(&std::io::Error::last_os_error().to_string());
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn lower_priority() {
+    report_failure("只支持 Linux");
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pin_current(_io: bool) {
+    report_failure("只支持 Linux");
+}
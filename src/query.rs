@@ -0,0 +1,197 @@
+use crate::error::GrindError;
+use crate::event::FoundEvent;
+use crate::records;
+use crate::target::Target;
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// `query` 的过滤条件，未设置的条件不参与过滤
+#[derive(Debug, Clone, Default)]
+pub struct QueryFilter {
+    pub pubkey: Option<String>,
+    /// 目标的显示形式（[`crate::target::Target`] 的 `Display`），用 [`crate::target::parse_label`] 还原
+    pub pattern: Option<Target>,
+    pub since: Option<DateTime<FixedOffset>>,
+    /// 只查找由该后端产生的结果（按结果中记录的来源）；常规日志和没有来源的结果不会命中
+    pub backend: Option<String>,
+}
+
+/// 解析 `--since`：支持 RFC 3339、`YYYY-MM-DD HH:MM:SS` 和 `YYYY-MM-DD`（本地时间）
+pub fn parse_since(value: &str) -> Result<DateTime<FixedOffset>, GrindError> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time);
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok().and_then(|d| d.and_hms_opt(0, 0, 0)));
+    naive
+        .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+        .map(|time| time.fixed_offset())
+        .ok_or_else(|| GrindError::Config(format!("无法解析时间 '{}'", value)))
+}
+
+/// 一条命中的记录
+#[derive(Debug, Clone)]
+pub struct QueryHit {
+    pub file: PathBuf,
+    pub line_number: u64,
+    pub line: String,
+}
+
+// 从任意格式的一行中提取出的公共字段
+struct Entry {
+    timestamp: String,
+    public_key: String,
+    pattern: Option<String>,
+    backend: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FileKind {
+    Keypairs,
+    Results,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FileFormat {
+    Text,
+    Csv,
+    Jsonl,
+}
+
+fn classify(path: &Path) -> Option<(FileKind, FileFormat)> {
+    let name = path.file_name()?.to_str()?;
+    let (stem, ext) = name.rsplit_once('.')?;
+    let kind = if stem.starts_with("keypairs_") {
+        FileKind::Keypairs
+    } else if stem == "result" {
+        FileKind::Results
+    } else {
+        return None;
+    };
+    let format = match ext {
+        "log" => FileFormat::Text,
+        "csv" => FileFormat::Csv,
+        "jsonl" => FileFormat::Jsonl,
+        _ => return None,
+    };
+    Some((kind, format))
+}
+
+fn parse_entry(kind: FileKind, format: FileFormat, line: &str) -> Option<Entry> {
+    match (kind, format) {
+        (FileKind::Keypairs, FileFormat::Text) => records::parse_keypair_line(line).map(|r| Entry {
+            timestamp: r.timestamp,
+            public_key: r.public_key,
+            pattern: None,
+            backend: None,
+        }),
+        (FileKind::Results, FileFormat::Text) => records::parse_result_line(line).map(|e| Entry {
+            timestamp: e.timestamp,
+            public_key: e.public_key,
+            pattern: Some(e.pattern),
+            backend: e.provenance.map(|p| p.backend),
+        }),
+        (FileKind::Keypairs, FileFormat::Jsonl) => {
+            let value: serde_json::Value = serde_json::from_str(line).ok()?;
+            Some(Entry {
+                timestamp: value.get("timestamp")?.as_str()?.to_string(),
+                public_key: value.get("public_key")?.as_str()?.to_string(),
+                pattern: None,
+                backend: None,
+            })
+        }
+        (FileKind::Results, FileFormat::Jsonl) => serde_json::from_str::<FoundEvent>(line).ok().map(|e| Entry {
+            timestamp: e.timestamp,
+            public_key: e.public_key,
+            pattern: Some(e.pattern),
+            backend: e.provenance.map(|p| p.backend),
+        }),
+        // CSV 列顺序见 migrate 模块
+        (FileKind::Keypairs, FileFormat::Csv) => {
+            let cols: Vec<&str> = line.split(',').collect();
+            (cols.len() == 4 && cols[0] != "timestamp").then(|| Entry {
+                timestamp: cols[0].to_string(),
+                public_key: cols[2].to_string(),
+                pattern: None,
+                backend: None,
+            })
+        }
+        (FileKind::Results, FileFormat::Csv) => {
+            let cols: Vec<&str> = line.split(',').collect();
+            (cols.len() == 8 && cols[0] != "timestamp").then(|| Entry {
+                timestamp: cols[0].to_string(),
+                public_key: cols[6].to_string(),
+                pattern: Some(cols[2].to_string()),
+                backend: None,
+            })
+        }
+    }
+}
+
+fn entry_matches(filter: &QueryFilter, entry: &Entry) -> bool {
+    if let Some(pubkey) = &filter.pubkey {
+        if &entry.public_key != pubkey {
+            return false;
+        }
+    }
+    if let Some(pattern) = &filter.pattern {
+        let label = pattern.to_string();
+        if entry.pattern.as_deref() != Some(label.as_str()) && !pattern.matches(&entry.public_key) {
+            return false;
+        }
+    }
+    if filter.backend.is_some() && entry.backend != filter.backend {
+        return false;
+    }
+    if let Some(since) = &filter.since {
+        match DateTime::parse_from_rfc3339(&entry.timestamp) {
+            Ok(time) if time >= *since => {}
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// 列出目录中可查询的日志和结果文件（按文件名排序）
+pub fn queryable_files(dir: &Path) -> Result<Vec<PathBuf>, GrindError> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| classify(path).is_some())
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// 在目录下所有轮转的日志和结果文件中查找满足条件的记录，每找到一条调用一次 `on_hit`
+pub fn query_dir<F>(dir: &Path, filter: &QueryFilter, mut on_hit: F) -> Result<u64, GrindError>
+where
+    F: FnMut(&QueryHit),
+{
+    let mut hits = 0;
+    for path in queryable_files(dir)? {
+        let Some((kind, format)) = classify(&path) else { continue };
+        let reader = BufReader::new(File::open(&path)?);
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            // 按公钥查询时先做子串检查，绝大多数行无需解析
+            if let Some(pubkey) = &filter.pubkey {
+                if !line.contains(pubkey.as_str()) {
+                    continue;
+                }
+            }
+            let Some(entry) = parse_entry(kind, format, &line) else { continue };
+            if entry_matches(filter, &entry) {
+                hits += 1;
+                on_hit(&QueryHit {
+                    file: path.clone(),
+                    line_number: index as u64 + 1,
+                    line,
+                });
+            }
+        }
+    }
+    Ok(hits)
+}
@@ -0,0 +1,96 @@
+use crate::event::{FoundEvent, Provenance, FOUND_EVENT_SCHEMA_VERSION};
+use chrono::{Local, NaiveDateTime, SecondsFormat, TimeZone};
+use serde::Serialize;
+
+/// keypairs_XXXX.log 中的一条记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KeypairRecord {
+    /// RFC 3339 格式的时间
+    pub timestamp: String,
+    pub counter: u64,
+    pub public_key: String,
+    #[serde(rename = "secret_key")]
+    pub private_key: String,
+}
+
+/// 将日志中的 `20240101120000-123` 形式时间转为 RFC 3339；无法解析时原样返回
+pub fn legacy_time_to_rfc3339(time_str: &str) -> String {
+    let Some((secs, millis)) = time_str.split_once('-') else {
+        return time_str.to_string();
+    };
+    let (Ok(naive), Ok(millis)) = (NaiveDateTime::parse_from_str(secs, "%Y%m%d%H%M%S"), millis.parse::<i64>()) else {
+        return time_str.to_string();
+    };
+    match Local.from_local_datetime(&naive).earliest() {
+        Some(time) => (time + chrono::Duration::milliseconds(millis)).to_rfc3339_opts(SecondsFormat::Millis, false),
+        None => time_str.to_string(),
+    }
+}
+
+// 拆出 `[时间] 其余部分`
+fn split_time(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix('[')?;
+    let (time, rest) = rest.split_once("] ")?;
+    Some((time, rest))
+}
+
+// 解析 `键: 值 | 键: 值` 形式的字段
+fn field<'a>(fields: &[&'a str], key: &str) -> Option<&'a str> {
+    fields.iter().find_map(|f| f.strip_prefix(key).and_then(|v| v.strip_prefix(": ")))
+}
+
+/// 解析文本格式的常规日志行：`[时间] 序号: n | 公钥: xxx | 私钥: xxx`
+pub fn parse_keypair_line(line: &str) -> Option<KeypairRecord> {
+    let (time, rest) = split_time(line)?;
+    let fields: Vec<&str> = rest.split(" | ").collect();
+    Some(KeypairRecord {
+        timestamp: legacy_time_to_rfc3339(time),
+        counter: field(&fields, "序号")?.parse().ok()?,
+        public_key: field(&fields, "公钥")?.to_string(),
+        private_key: field(&fields, "私钥")?.to_string(),
+    })
+}
+
+/// 解析文本格式的结果行：`[时间] [FOUND] 匹配前缀: abc | 序号: n | 公钥: xxx | 私钥: xxx`
+/// 忽略大小写匹配时目标之后有 `实际匹配: AbC`，给出公钥中实际的大小写；正则目标（`匹配正则: /^So1/`）总有这一字段。
+/// 包含和正则目标之后还有 `位置: n`，即匹配在公钥中的字符位置。
+/// 公私钥分离模式下的结果行没有私钥字段；之后是 `来源: 后端#线程@主机 | 复核: keypair, pattern`（旧的结果没有），
+/// 一个公钥满足多个目标时末尾还有 `同时满足: a, ...b`。
+pub fn parse_result_line(line: &str) -> Option<FoundEvent> {
+    let (time, rest) = split_time(line)?;
+    let rest = rest.strip_prefix("[FOUND] 匹配")?;
+    let fields: Vec<&str> = rest.split(" | ").collect();
+    let (kind, pattern) = fields.first()?.split_once(": ")?;
+    // leet 目标的显示形式前面有 `leet:`
+    let bare = pattern.strip_prefix("leet:").unwrap_or(pattern);
+    let (match_type, prefix, suffix) = match kind {
+        "前缀" => ("prefix", bare, ""),
+        "后缀" => ("suffix", "", bare.strip_prefix("...")?),
+        "前后缀" => {
+            let (prefix, suffix) = bare.split_once("...")?;
+            ("prefix_suffix", prefix, suffix)
+        }
+        "包含" if bare.starts_with("...") && bare.ends_with("...") => ("contains", "", ""),
+        "正则" => ("regex", "", ""),
+        _ => return None,
+    };
+    Some(FoundEvent {
+        schema_version: FOUND_EVENT_SCHEMA_VERSION,
+        timestamp: legacy_time_to_rfc3339(time),
+        counter: field(&fields, "序号")?.parse().ok()?,
+        pattern: pattern.to_string(),
+        match_type: match_type.to_string(),
+        prefix: prefix.to_string(),
+        suffix: suffix.to_string(),
+        public_key: field(&fields, "公钥")?.to_string(),
+        matched_text: field(&fields, "实际匹配").map(str::to_string),
+        position: field(&fields, "位置").and_then(|v| v.parse().ok()),
+        secret_key: field(&fields, "私钥").map(str::to_string),
+        run_id: None,
+        matched_patterns: field(&fields, "同时满足")
+            .map(|v| v.split(", ").map(str::to_string).collect())
+            .unwrap_or_default(),
+        announcement: None,
+        provenance: field(&fields, "来源").and_then(|source| Provenance::parse(source, field(&fields, "复核"))),
+    })
+}
@@ -0,0 +1,181 @@
+//! 启动时的输出目录恢复检查：上次运行崩溃时可能留下没改名的临时文件、末尾有预分配空白或半行的日志和结果文件、
+//! 以及已记录结果数据库未提交的日志。删除无用的临时文件，截掉半行并把截下的内容移到 `quarantine/` 中保留，
+//! 在开始生成前报告做了什么。同一目录中可能还有其他进程在写，只处理一段时间内没有修改过的文件
+
+use crate::event;
+use crate::grinder::{self, ResultFormat};
+use crate::secrets;
+use crate::session;
+use chrono::Local;
+use serde::Serialize;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// 无法修复、但可能含有有用内容（例如截下的半行结果）的文件移到输出目录中的这个子目录
+pub const QUARANTINE_DIR: &str = "quarantine";
+
+// 最近这么久内修改过的文件可能正被其他进程写入，不处理
+const STALE_AFTER: Duration = Duration::from_secs(10);
+// 从文件末尾向前查找的块大小
+const TAIL_CHUNK: u64 = 64 * 1024;
+
+/// 恢复检查对一个文件做的处理
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum RecoveryAction {
+    /// 删除了空的临时文件（创建后还没来得及改名）
+    RemovedTemp { file: String },
+    /// 非空的临时文件可能比正式文件更新，原样移入隔离目录
+    QuarantinedTemp { file: String, moved_to: String },
+    /// 截掉了内存映射写入预分配、没有写到的空白（零字节）
+    TrimmedPadding { file: String, bytes: u64 },
+    /// 截掉了末尾没写完的一行，截下的内容保存在隔离目录
+    CutPartialLine { file: String, bytes: u64, saved_to: String },
+    /// 已记录结果数据库有未提交的回滚日志或预写日志，SQLite 打开时会自动回滚或合并
+    PendingJournal { file: String },
+}
+
+impl fmt::Display for RecoveryAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecoveryAction::RemovedTemp { file } => write!(f, "删除了遗留的空临时文件 {}", file),
+            RecoveryAction::QuarantinedTemp { file, moved_to } => write!(
+                f, "遗留的临时文件 {} 没有改名为正式文件，已移到 {}，请确认其中是否有需要的内容", file, moved_to
+            ),
+            RecoveryAction::TrimmedPadding { file, bytes } => write!(f, "截掉了 {} 末尾 {} 字节预分配的空白", file, bytes),
+            RecoveryAction::CutPartialLine { file, bytes, saved_to } => write!(
+                f, "{} 末尾有一行没写完（{} 字节），已截掉并保存到 {}", file, bytes, saved_to
+            ),
+            RecoveryAction::PendingJournal { file } => write!(
+                f, "{} 有未完成的事务日志，打开数据库时将回滚未提交的登记", file
+            ),
+        }
+    }
+}
+
+/// 检查并修复 `dir` 中上次运行留下的文件；`dedup_db` 为已记录结果数据库的路径
+pub fn scan(dir: &Path, dedup_db: Option<&Path>) -> io::Result<Vec<RecoveryAction>> {
+    let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    let mut actions = Vec::new();
+    if !dir.is_dir() {
+        return Ok(actions);
+    }
+    let mut names: Vec<String> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    names.sort();
+
+    for name in &names {
+        let path = dir.join(name);
+        if !is_stale(&path) {
+            continue;
+        }
+        if name.ends_with(".tmp") {
+            if fs::metadata(&path)?.len() == 0 {
+                fs::remove_file(&path)?;
+                actions.push(RecoveryAction::RemovedTemp { file: name.clone() });
+            } else {
+                let moved_to = quarantine_path(dir, name)?;
+                fs::rename(&path, &moved_to)?;
+                actions.push(RecoveryAction::QuarantinedTemp { file: name.clone(), moved_to: moved_to.display().to_string() });
+            }
+        } else if is_line_file(name) {
+            actions.extend(repair_tail(dir, name)?);
+        }
+    }
+
+    if let Some(db) = dedup_db {
+        for suffix in ["-journal", "-wal"] {
+            let mut journal = db.as_os_str().to_owned();
+            journal.push(suffix);
+            let journal = PathBuf::from(journal);
+            if journal.exists() && is_stale(&journal) && fs::metadata(&journal)?.len() > 0 {
+                actions.push(RecoveryAction::PendingJournal { file: journal.display().to_string() });
+            }
+        }
+    }
+    Ok(actions)
+}
+
+// 按行追加写入的日志和结果文件；已完成轮转的 .done.log 在改名前已经同步过，不必检查
+fn is_line_file(name: &str) -> bool {
+    let fixed = [
+        ResultFormat::Text.file_name(),
+        ResultFormat::Jsonl.file_name(),
+        secrets::SECRETS_FILE,
+        event::PUBLIC_FEED_FILE,
+        session::SESSION_LOG,
+    ];
+    fixed.contains(&name)
+        || (name.starts_with(grinder::LOG_STEM) && name.ends_with(".log") && !name.ends_with(".done.log"))
+}
+
+fn is_stale(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .is_ok_and(|modified| SystemTime::now().duration_since(modified).is_ok_and(|age| age >= STALE_AFTER))
+}
+
+// 隔离目录中的目标路径：原文件名后加上时间，多次恢复不会互相覆盖
+fn quarantine_path(dir: &Path, name: &str) -> io::Result<PathBuf> {
+    let quarantine = dir.join(QUARANTINE_DIR);
+    fs::create_dir_all(&quarantine)?;
+    Ok(quarantine.join(format!("{}.{}", name, Local::now().format("%Y%m%dT%H%M%S"))))
+}
+
+// 截掉文件末尾的零字节和没写完的一行
+fn repair_tail(dir: &Path, name: &str) -> io::Result<Vec<RecoveryAction>> {
+    let path = dir.join(name);
+    let mut file = OpenOptions::new().read(true).write(true).open(&path)?;
+    let len = file.metadata()?.len();
+    let mut actions = Vec::new();
+
+    // 最后一个非零字节之后都是预分配的空白
+    let content_end = find_back(&mut file, len, |b| b != 0)?.map_or(0, |pos| pos + 1);
+    if content_end < len {
+        actions.push(RecoveryAction::TrimmedPadding { file: name.to_string(), bytes: len - content_end });
+    }
+    // 最后一个换行之后是没写完的一行
+    let line_end = find_back(&mut file, content_end, |b| b == b'\n')?.map_or(0, |pos| pos + 1);
+    if line_end < content_end {
+        let mut partial = vec![0u8; (content_end - line_end) as usize];
+        file.seek(SeekFrom::Start(line_end))?;
+        file.read_exact(&mut partial)?;
+        let saved_to = quarantine_path(dir, &format!("{}.partial", name))?;
+        let mut saved = File::create(&saved_to)?;
+        saved.write_all(&partial)?;
+        saved.sync_all()?;
+        actions.push(RecoveryAction::CutPartialLine {
+            file: name.to_string(),
+            bytes: partial.len() as u64,
+            saved_to: saved_to.display().to_string(),
+        });
+    }
+    if line_end < len {
+        file.set_len(line_end)?;
+        file.sync_all()?;
+    }
+    Ok(actions)
+}
+
+// 在 [0, end) 中从后向前查找第一个满足条件的字节的位置
+fn find_back(file: &mut File, end: u64, pred: impl Fn(u8) -> bool) -> io::Result<Option<u64>> {
+    let mut chunk_end = end;
+    let mut buf = vec![0u8; TAIL_CHUNK as usize];
+    while chunk_end > 0 {
+        let chunk_start = chunk_end.saturating_sub(TAIL_CHUNK);
+        let chunk = &mut buf[..(chunk_end - chunk_start) as usize];
+        file.seek(SeekFrom::Start(chunk_start))?;
+        file.read_exact(chunk)?;
+        if let Some(offset) = chunk.iter().rposition(|&b| pred(b)) {
+            return Ok(Some(chunk_start + offset as u64));
+        }
+        chunk_end = chunk_start;
+    }
+    Ok(None)
+}
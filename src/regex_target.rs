@@ -0,0 +1,129 @@
+//! `--regex` 目标：用正则表达式描述对公钥的要求，例如 `^So1[1-9]{2}` 或 `^(Sol|Moon)`。
+//! 正则在整个 base58 公钥中搜索，需要固定在开头或结尾时写 `^` 或 `$`。
+//! 命中概率按每个字符 1/58 的粗略模型，在正则对应的 DFA 上逐个字符累加得到
+
+use crate::base58;
+use crate::error::GrindError;
+use regex::{Regex, RegexBuilder};
+use regex_automata::dfa::{dense, Automaton};
+use regex_automata::util::syntax;
+use regex_automata::Input;
+use std::collections::HashMap;
+use std::fmt;
+
+// 32 字节公钥编码后通常为 44 个字符，小于 58^43 的（约 5.8%）为 43 个字符，更短的可以忽略
+const KEY_LEN: usize = 44;
+// 估算概率时 DFA 的大小上限；超过时说明正则太复杂（例如 `X.{30}`），拒绝使用
+const DFA_SIZE_LIMIT: usize = 64 << 20;
+
+/// 编译后的正则目标。区分大小写和忽略大小写两种形式都预先编译并估算好概率，
+/// `--ignore-case` 只是切换使用哪一种
+#[derive(Clone)]
+pub struct TargetRegex {
+    source: String,
+    ignore_case: bool,
+    exact: Regex,
+    folded: Regex,
+    // 下标为是否忽略大小写
+    probability: [f64; 2],
+}
+
+impl TargetRegex {
+    pub fn new(source: &str) -> Result<Self, GrindError> {
+        if source.is_empty() {
+            return Err(GrindError::Pattern("正则不能为空".to_string()));
+        }
+        let compile = |ignore_case: bool| {
+            RegexBuilder::new(source).case_insensitive(ignore_case).build()
+                .map_err(|e| GrindError::Pattern(format!("无效的正则 '{}': {}", source, e)))
+        };
+        Ok(TargetRegex {
+            source: source.to_string(),
+            ignore_case: false,
+            exact: compile(false)?,
+            folded: compile(true)?,
+            probability: [probability(source, false)?, probability(source, true)?],
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// 忽略大小写的形式，用于 --ignore-case
+    pub fn folded(&self) -> Self {
+        TargetRegex { ignore_case: true, ..self.clone() }
+    }
+
+    fn regex(&self) -> &Regex {
+        if self.ignore_case { &self.folded } else { &self.exact }
+    }
+
+    pub fn is_match(&self, public_key: &str) -> bool {
+        self.regex().is_match(public_key)
+    }
+
+    /// 公钥中第一个匹配的部分
+    pub fn find<'a>(&self, public_key: &'a str) -> Option<regex::Match<'a>> {
+        self.regex().find(public_key)
+    }
+
+    /// 一个随机公钥命中的概率（按每个字符 1/58 估算）；为 0 时不可能命中
+    pub fn probability(&self, ignore_case: bool) -> f64 {
+        self.probability[(ignore_case || self.ignore_case) as usize]
+    }
+}
+
+impl PartialEq for TargetRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source && self.ignore_case == other.ignore_case
+    }
+}
+
+impl Eq for TargetRegex {}
+
+impl fmt::Debug for TargetRegex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TargetRegex").field("source", &self.source).field("ignore_case", &self.ignore_case).finish()
+    }
+}
+
+// 把正则编译为只认 ASCII 的 DFA，从开头起每读一个 base58 字符（各 1/58）推进一次状态分布：
+// 进入匹配状态的部分已经命中，进入死状态的部分不可能再命中；最后按 43、44 个字符两种长度收尾
+fn probability(source: &str, ignore_case: bool) -> Result<f64, GrindError> {
+    let error = |e: &dyn fmt::Display| GrindError::Pattern(format!("无法估算正则 '{}' 的命中概率: {}", source, e));
+    let dfa = dense::Builder::new()
+        .configure(dense::Config::new().dfa_size_limit(Some(DFA_SIZE_LIMIT)).determinize_size_limit(Some(DFA_SIZE_LIMIT)))
+        .syntax(syntax::Config::new().unicode(false).utf8(false).case_insensitive(ignore_case))
+        .build(source)
+        .map_err(|e| error(&e))?;
+    let start = dfa.start_state_forward(&Input::new("")).map_err(|e| error(&e))?;
+
+    let short_share = (43.0 * 58f64.ln() - 256.0 * 2f64.ln()).exp();
+    let mut matched = 0.0;
+    let mut result = 0.0;
+    let mut states = HashMap::from([(start, 1.0)]);
+    for len in 1..=KEY_LEN {
+        let mut next = HashMap::new();
+        for (&state, &p) in &states {
+            for &c in base58::ALPHABET {
+                let state = dfa.next_state(state, c);
+                if dfa.is_match_state(state) {
+                    matched += p / 58.0;
+                } else if !dfa.is_dead_state(state) && !dfa.is_quit_state(state) {
+                    *next.entry(state).or_insert(0.0) += p / 58.0;
+                }
+            }
+        }
+        states = next;
+        if len >= KEY_LEN - 1 {
+            let at_end: f64 = states.iter()
+                .filter(|(&state, _)| dfa.is_match_state(dfa.next_eoi_state(state)))
+                .map(|(_, &p)| p)
+                .sum();
+            let share = if len == KEY_LEN { 1.0 - short_share } else { short_share };
+            result += share * (matched + at_end);
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,174 @@
+use crate::backend::RngPolicy;
+use crate::error::GrindError;
+use crate::grinder::{GrindConfig, GrindSummary};
+use chrono::{Local, SecondsFormat, TimeDelta};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 每次搜索结束时写入输出目录的运行报告
+pub const RUN_REPORT_FILE: &str = "run_report.json";
+
+/// 运行所在机器的硬件信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hardware {
+    /// CPU 型号；无法读取时为 `unknown`
+    pub cpu_model: String,
+    pub logical_cores: usize,
+    pub os: String,
+    pub arch: String,
+}
+
+impl Hardware {
+    pub fn detect() -> Self {
+        Hardware {
+            cpu_model: cpu_model().unwrap_or_else(|| "unknown".to_string()),
+            logical_cores: num_cpus::get(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+        }
+    }
+}
+
+/// 本机的主机名；无法读取时为 `unknown`
+pub fn hostname() -> String {
+    #[cfg(unix)]
+    {
+        let mut buf = [0u8; 256];
+        // SAFETY: 缓冲区有效且长度正确；gethostname 最多写入 buf.len() 字节
+        if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } == 0 {
+            let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+            if let Ok(name) = std::str::from_utf8(&buf[..len]) {
+                if !name.is_empty() {
+                    return name.to_string();
+                }
+            }
+        }
+    }
+    std::env::var("COMPUTERNAME").or_else(|_| std::env::var("HOSTNAME")).unwrap_or_else(|_| "unknown".to_string())
+}
+
+// Linux 上从 /proc/cpuinfo 读取 CPU 型号
+fn cpu_model() -> Option<String> {
+    let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+    cpuinfo.lines()
+        .find_map(|line| line.strip_prefix("model name").and_then(|rest| rest.split_once(':')))
+        .map(|(_, model)| model.trim().to_string())
+}
+
+/// 一个目标在本次运行中的情况
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PatternReport {
+    pub pattern: String,
+    pub count: u64,
+    pub found: u64,
+    /// 找够数量时那个公钥的序号
+    pub attempts_to_find: Option<u64>,
+    /// 从本次运行开始到找够数量的秒数；未找够或继续前已找够时为空
+    pub secs_to_find: Option<f64>,
+}
+
+/// 一次运行的报告：速度、硬件、后端和每个目标的找到时间，`compare-runs` 据此比较两次运行。
+/// 继续暂停的搜索时报告被覆盖，速度和时间只统计继续之后的部分
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunReport {
+    pub run_id: String,
+    /// RFC 3339 格式的开始和结束时间
+    pub started_at: String,
+    pub finished_at: String,
+    pub backend: String,
+    /// 私钥种子的随机数来源及重新播种策略；旧的报告没有该字段
+    #[serde(default)]
+    pub rng: Option<RngPolicy>,
+    pub threads: usize,
+    pub ignore_case: bool,
+    pub hardware: Hardware,
+    /// 共生成的密钥对数量（包括继续前的部分）
+    pub attempts: u64,
+    pub elapsed_secs: f64,
+    pub keys_per_sec: f64,
+    pub suspended: bool,
+    pub patterns: Vec<PatternReport>,
+}
+
+impl RunReport {
+    pub fn new(config: &GrindConfig, summary: &GrindSummary, backend: &str, rng: RngPolicy) -> Self {
+        let finished = Local::now();
+        let started = TimeDelta::from_std(summary.elapsed).ok().and_then(|d| finished.checked_sub_signed(d)).unwrap_or(finished);
+        RunReport {
+            run_id: summary.run_id.clone(),
+            started_at: started.to_rfc3339_opts(SecondsFormat::Secs, false),
+            finished_at: finished.to_rfc3339_opts(SecondsFormat::Secs, false),
+            backend: backend.to_string(),
+            rng: Some(rng),
+            threads: config.num_threads,
+            ignore_case: config.ignore_case,
+            hardware: Hardware::detect(),
+            attempts: summary.attempts,
+            elapsed_secs: summary.elapsed.as_secs_f64(),
+            keys_per_sec: summary.keys_per_sec,
+            suspended: summary.suspended,
+            patterns: summary.found.iter().zip(&summary.satisfied_at).zip(&summary.satisfied_after)
+                .map(|(((target, found), satisfied_at), satisfied_after)| PatternReport {
+                    pattern: target.to_string(),
+                    count: target.count,
+                    found: *found,
+                    attempts_to_find: *satisfied_at,
+                    secs_to_find: satisfied_after.map(|d| d.as_secs_f64()),
+                })
+                .collect(),
+        }
+    }
+
+    /// 原子地写入 `dir` 中的 `run_report.json`
+    pub fn save(&self, dir: &Path) -> Result<PathBuf, GrindError> {
+        let path = dir.join(RUN_REPORT_FILE);
+        let tmp = path.with_extension("json.tmp");
+        let json = serde_json::to_string_pretty(self).expect("RunReport 序列化不会失败");
+        fs::write(&tmp, json + "\n")?;
+        fs::rename(&tmp, &path)?;
+        Ok(path)
+    }
+
+    /// 读取运行报告；`path` 可以是报告文件，也可以是包含它的运行目录
+    pub fn load(path: &Path) -> Result<Self, GrindError> {
+        let path = if path.is_dir() { path.join(RUN_REPORT_FILE) } else { path.to_path_buf() };
+        let text = fs::read_to_string(&path)
+            .map_err(|e| GrindError::Config(format!("无法读取运行报告 {}: {}", path.display(), e)))?;
+        serde_json::from_str(&text)
+            .map_err(|e| GrindError::Config(format!("无法解析运行报告 {}: {}", path.display(), e)))
+    }
+}
+
+/// 两次运行中同一个目标的对比
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternComparison {
+    pub pattern: String,
+    /// 该目标在两次运行中的情况；只出现在其中一次运行中时另一边为空
+    pub a: Option<PatternReport>,
+    pub b: Option<PatternReport>,
+}
+
+impl PatternComparison {
+    /// 找够数量的时间之比（a / b），大于 1 表示 b 更快；任一边没有时间时为空
+    pub fn speedup(&self) -> Option<f64> {
+        let a = self.a.as_ref()?.secs_to_find?;
+        let b = self.b.as_ref()?.secs_to_find?;
+        (b > 0.0).then(|| a / b)
+    }
+}
+
+/// 按目标对齐两次运行：先是 `a` 中的目标（按原顺序），再是只在 `b` 中出现的目标
+pub fn compare_patterns(a: &RunReport, b: &RunReport) -> Vec<PatternComparison> {
+    let mut rows: Vec<PatternComparison> = a.patterns.iter()
+        .map(|p| PatternComparison {
+            pattern: p.pattern.clone(),
+            a: Some(p.clone()),
+            b: b.patterns.iter().find(|q| q.pattern == p.pattern).cloned(),
+        })
+        .collect();
+    rows.extend(b.patterns.iter()
+        .filter(|q| !a.patterns.iter().any(|p| p.pattern == q.pattern))
+        .map(|q| PatternComparison { pattern: q.pattern.clone(), a: None, b: Some(q.clone()) }));
+    rows
+}
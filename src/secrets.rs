@@ -0,0 +1,157 @@
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use crate::error::GrindError;
+use crate::status;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+pub const SECRETS_FILE: &str = "result_secrets.log";
+pub const PASSPHRASE_ENV: &str = "VANITY_SECRETS_PASSPHRASE";
+
+const ENCRYPTED_TAG: &str = "enc1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const PBKDF2_ROUNDS: u32 = 200_000;
+
+/// 以 0600 权限打开（或创建）只追加的文件，用于存放私钥
+pub fn open_private_file(path: &Path) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let file = options.open(path)?;
+
+    // 文件可能早已存在且权限更宽，这里强制收紧
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(file)
+}
+
+/// 以 solana-keygen 兼容的 JSON 数组格式写出密钥对文件（权限 0600）
+pub fn write_keypair_file(path: &Path, private_key: &str) -> std::io::Result<()> {
+    let bytes = bs58::decode(private_key)
+        .into_vec()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    let json = format!(
+        "[{}]",
+        bytes.iter().map(u8::to_string).collect::<Vec<_>>().join(",")
+    );
+    let mut options = OpenOptions::new();
+    options.create(true).write(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(json.as_bytes())?;
+    Ok(())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Key {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<sha2::Sha256>(passphrase.as_bytes(), salt, PBKDF2_ROUNDS, &mut key);
+    Key::from(key)
+}
+
+/// 加密私钥，输出格式: enc1:<salt>:<nonce>:<密文>（均为 base58）
+pub fn encrypt_secret(passphrase: &str, private_key: &str) -> String {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt));
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, private_key.as_bytes())
+        .expect("ChaCha20Poly1305 加密不会失败");
+    format!(
+        "{}:{}:{}:{}",
+        ENCRYPTED_TAG,
+        bs58::encode(salt).into_string(),
+        bs58::encode(nonce).into_string(),
+        bs58::encode(ciphertext).into_string()
+    )
+}
+
+/// 解密 `encrypt_secret` 生成的字符串
+pub fn decrypt_secret(passphrase: &str, encoded: &str) -> Result<String, GrindError> {
+    let parts: Vec<&str> = encoded.split(':').collect();
+    if parts.len() != 4 || parts[0] != ENCRYPTED_TAG {
+        return Err(GrindError::Secret(format!("无法识别的加密格式: {}", encoded)));
+    }
+    let decode = |s: &str| {
+        bs58::decode(s).into_vec().map_err(|e| GrindError::Secret(format!("base58 解码失败: {}", e)))
+    };
+    let salt = decode(parts[1])?;
+    let nonce = decode(parts[2])?;
+    let ciphertext = decode(parts[3])?;
+    let nonce: [u8; NONCE_LEN] = match nonce.try_into() {
+        Ok(nonce) if salt.len() == SALT_LEN => nonce,
+        _ => return Err(GrindError::Secret("加密数据长度不正确".to_string())),
+    };
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt));
+    let plaintext = cipher
+        .decrypt(&Nonce::from(nonce), ciphertext.as_ref())
+        .map_err(|_| GrindError::Secret("解密失败：口令错误或数据已损坏".to_string()))?;
+    String::from_utf8(plaintext).map_err(|_| GrindError::Secret("解密结果不是有效的 UTF-8".to_string()))
+}
+
+/// 私钥文件写入器：每行以公钥为键，私钥可选加密
+pub struct SecretWriter {
+    writer: BufWriter<File>,
+    passphrase: Option<String>,
+}
+
+impl SecretWriter {
+    /// 在 `dir` 下打开（或创建）私钥文件 `SECRETS_FILE`
+    pub fn new(dir: &Path, passphrase: Option<String>) -> std::io::Result<Self> {
+        let path = dir.join(SECRETS_FILE);
+        let file = open_private_file(&path)?;
+        status!(
+            "创建私钥文件: {} (权限 0600{})",
+            path.display(),
+            if passphrase.is_some() { "，已加密" } else { "" }
+        );
+        Ok(SecretWriter {
+            writer: BufWriter::new(file),
+            passphrase,
+        })
+    }
+
+    pub fn write_secret(&mut self, time_str: &str, public_key: &str, private_key: &str) -> std::io::Result<()> {
+        let secret = match &self.passphrase {
+            Some(passphrase) => encrypt_secret(passphrase, private_key),
+            None => private_key.to_string(),
+        };
+        writeln!(self.writer, "[{}] 公钥: {} | 私钥: {}", time_str, public_key, secret)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    pub fn finalize(&mut self) -> std::io::Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// `decrypt-secrets` 子命令：读取私钥文件并输出解密后的内容
+pub fn decrypt_file(path: &str, passphrase: &str) -> Result<(), GrindError> {
+    let file = File::open(path)?;
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        match line.rsplit_once("私钥: ") {
+            Some((head, secret)) if secret.starts_with(ENCRYPTED_TAG) => {
+                println!("{}私钥: {}", head, decrypt_secret(passphrase, secret)?);
+            }
+            _ => println!("{}", line),
+        }
+    }
+    Ok(())
+}
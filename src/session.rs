@@ -0,0 +1,116 @@
+use crate::backend::RngPolicy;
+use crate::event::{self, RunEvent};
+use crate::grinder::EventCallback;
+use crate::recovery::RecoveryAction;
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// 输出目录中的会话日志：每行一个 JSON 对象，记录运行的生命周期事件，不含任何私钥
+pub const SESSION_LOG: &str = "session.log";
+
+/// 只记录在会话日志中的生命周期事件；运行事件（[`RunEvent`]）也原样记录在同一个文件中。
+/// 写入时补上 `timestamp` 和 `run_id`，`event` 字段给出种类
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SessionEvent {
+    /// 搜索开始
+    Start {
+        version: String,
+        threads: usize,
+        backend: String,
+        /// 私钥种子的随机数来源及重新播种策略
+        rng: RngPolicy,
+        targets: Vec<String>,
+        ignore_case: bool,
+    },
+    /// 从暂停时保存的进度继续
+    Resumed {
+        attempts: u64,
+        /// 与 `Start` 中的目标一一对应的已找到数量
+        found: Vec<u64>,
+    },
+    /// 开始前的恢复检查处理了上次运行崩溃留下的文件
+    Recovered { actions: Vec<RecoveryAction> },
+    /// 停滞检测的第一个完整采样窗口得出的速度，之后的停滞以它（及更高的窗口速度）为基准
+    Calibrated { keys_per_sec: f64 },
+    /// 常规日志达到行数上限，换用新文件
+    Rotated { file: String },
+    /// 收到暂停请求，状态已写入 `state_file`
+    Suspended { attempts: u64, state_file: String },
+    /// 运行结束
+    Shutdown {
+        /// `complete`（目标都已找够）、`limit`（达到 --keep-counting 的数量）、`suspended` 或 `error`
+        reason: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        attempts: Option<u64>,
+        elapsed_secs: f64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        found: Option<u64>,
+        /// 停止时宽限期内没能写入、被丢弃的常规日志条数
+        #[serde(skip_serializing_if = "Option::is_none")]
+        dropped_logs: Option<u64>,
+    },
+}
+
+// 会话日志中的一行：时间、运行 ID 和事件本身的字段
+#[derive(Serialize)]
+struct Entry<'a> {
+    timestamp: String,
+    run_id: &'a str,
+    #[serde(flatten)]
+    event: &'a SessionEvent,
+}
+
+/// 追加写入 `session.log` 的句柄，可在各线程间复制共享；每条记录立即写入文件，
+/// 进程意外退出时也能保留到最后一条
+#[derive(Clone)]
+pub(crate) struct SessionLog {
+    file: Arc<Mutex<File>>,
+    run_id: String,
+    // 写入失败只警告一次，不影响搜索
+    failed: Arc<AtomicBool>,
+}
+
+impl SessionLog {
+    pub(crate) fn open(dir: &Path, run_id: &str) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(dir.join(SESSION_LOG))?;
+        Ok(SessionLog {
+            file: Arc::new(Mutex::new(file)),
+            run_id: run_id.to_string(),
+            failed: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    pub(crate) fn record(&self, event: &SessionEvent) {
+        let entry = Entry { timestamp: event::now_timestamp(), run_id: &self.run_id, event };
+        self.write_line(&serde_json::to_string(&entry).expect("SessionEvent 序列化不会失败"));
+    }
+
+    pub(crate) fn record_run_event(&self, event: &RunEvent) {
+        self.write_line(&event.to_json());
+    }
+
+    fn write_line(&self, line: &str) {
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writeln!(file, "{}", line) {
+            if !self.failed.swap(true, Ordering::Relaxed) {
+                eprintln!("警告: 写入 {} 失败，之后的会话事件可能丢失: {}", SESSION_LOG, e);
+            }
+        }
+    }
+
+    /// 把运行事件同时记入会话日志的回调；`inner` 为调用方原有的回调
+    pub(crate) fn wrap(&self, inner: Option<EventCallback>) -> EventCallback {
+        let session = self.clone();
+        Arc::new(move |event: &RunEvent| {
+            session.record_run_event(event);
+            if let Some(callback) = &inner {
+                callback(event);
+            }
+        })
+    }
+}
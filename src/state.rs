@@ -0,0 +1,145 @@
+use crate::backend::RngPolicy;
+use crate::dedup;
+use crate::error::GrindError;
+use crate::grinder::{Attribution, OnComplete, ResultFormat};
+use crate::hooks;
+use crate::policy::SecretPolicy;
+use crate::target::{self, Target};
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 暂停时写出的状态文件名（位于输出目录）
+pub const STATE_FILE: &str = "state.json";
+/// 请求暂停的标记文件名（位于输出目录）；运行中的搜索发现它后保存状态并退出
+pub const SUSPEND_REQUEST: &str = "suspend.request";
+/// 当前状态文件的格式版本
+pub const STATE_VERSION: u32 = 1;
+
+/// 一个目标暂停时的进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetState {
+    /// 目标的显示形式（[`crate::target::Target`] 的 `Display`），用 [`crate::target::parse_label`] 还原
+    pub pattern: String,
+    pub count: u64,
+    /// RFC 3339 格式的截止时间；旧的状态文件没有该字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<String>,
+    pub found: u64,
+    /// 找够数量时那个公钥的序号
+    pub satisfied_at: Option<u64>,
+}
+
+/// 暂停的搜索的完整状态，可以复制到另一台机器上用 `resume-from` 继续
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunState {
+    pub version: u32,
+    pub run_id: String,
+    /// 暂停时已生成的密钥对数量，继续时计数从这里接着累加
+    pub attempts: u64,
+    pub targets: Vec<TargetState>,
+    pub num_threads: usize,
+    pub ignore_case: bool,
+    pub split_output: bool,
+    /// 私钥是否加密；继续时需要重新通过环境变量提供口令
+    pub encrypt_secrets: bool,
+    pub write_keypair_files: bool,
+    pub raw_speed: bool,
+    pub low_memory: bool,
+    /// 是否按线程分片写常规日志；旧的状态文件没有该字段
+    #[serde(default)]
+    pub shard_logs: bool,
+    pub result_format: ResultFormat,
+    /// 一个公钥满足多个目标时的归属策略；旧的状态文件没有该字段，按 `all` 处理
+    #[serde(default)]
+    pub attribution: Attribution,
+    /// 运行时的私钥策略，继续时同样执行；旧的状态文件没有该字段
+    #[serde(default)]
+    pub secret_policy: Option<SecretPolicy>,
+    /// 找到结果后执行的钩子命令（库回调不保存）；旧的状态文件没有这两个字段
+    #[serde(default)]
+    pub hooks: Vec<String>,
+    #[serde(default = "default_hook_timeout_secs")]
+    pub hook_timeout_secs: u64,
+    pub keep_counting: Option<u64>,
+    /// 所有目标都找够之后的行为；旧的状态文件没有该字段，按 `stop` 处理
+    #[serde(default)]
+    pub on_complete: OnComplete,
+    pub output_dir: PathBuf,
+    /// 已记录结果的数据库；旧的状态文件没有该字段，按默认的输出目录中的数据库处理
+    #[serde(default = "default_dedup_db")]
+    pub dedup_db: Option<PathBuf>,
+    /// 私钥种子的随机数来源，继续时沿用；旧的状态文件没有该字段，当时只有操作系统熵源
+    #[serde(default)]
+    pub rng: RngPolicy,
+    /// 常规日志当前写到的文件序号和该文件的行数，继续时接着轮转
+    pub log_file_index: u32,
+    pub log_line_count: u64,
+}
+
+fn default_hook_timeout_secs() -> u64 {
+    hooks::DEFAULT_HOOK_TIMEOUT.as_secs()
+}
+
+fn default_dedup_db() -> Option<PathBuf> {
+    Some(PathBuf::from(dedup::DEDUP_DB))
+}
+
+impl RunState {
+    /// 原子地写出状态文件（先写临时文件再重命名）
+    pub fn save(&self, path: &Path) -> Result<(), GrindError> {
+        let tmp = path.with_extension("json.tmp");
+        let json = serde_json::to_string_pretty(self).expect("RunState 序列化不会失败");
+        fs::write(&tmp, json + "\n")?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, GrindError> {
+        let text = fs::read_to_string(path)?;
+        let state: RunState = serde_json::from_str(&text)
+            .map_err(|e| GrindError::Config(format!("无法解析状态文件 {}: {}", path.display(), e)))?;
+        if state.version != STATE_VERSION {
+            return Err(GrindError::Config(format!(
+                "状态文件 {} 的版本为 {}，当前只支持版本 {}",
+                path.display(), state.version, STATE_VERSION
+            )));
+        }
+        Ok(state)
+    }
+
+    /// 还原目标列表（数量为原始要求的数量）
+    pub fn targets(&self) -> Result<Vec<Target>, GrindError> {
+        self.targets.iter()
+            .map(|t| {
+                let deadline = t.deadline.as_deref().map(DateTime::parse_from_rfc3339).transpose()
+                    .map_err(|e| GrindError::Config(format!("目标 '{}' 的截止时间无效: {}", t.pattern, e)))?;
+                Ok(Target { count: t.count, deadline, ..target::parse_label(&t.pattern)? })
+            })
+            .collect()
+    }
+}
+
+/// 继续一次暂停的搜索时的起点
+#[derive(Debug, Clone, Default)]
+pub struct ResumePoint {
+    pub attempts: u64,
+    /// 与目标一一对应的已找到数量
+    pub found: Vec<u64>,
+    pub satisfied_at: Vec<Option<u64>>,
+    pub log_file_index: u32,
+    pub log_line_count: u64,
+}
+
+impl From<&RunState> for ResumePoint {
+    fn from(state: &RunState) -> Self {
+        ResumePoint {
+            attempts: state.attempts,
+            found: state.targets.iter().map(|t| t.found).collect(),
+            satisfied_at: state.targets.iter().map(|t| t.satisfied_at).collect(),
+            log_file_index: state.log_file_index,
+            log_line_count: state.log_line_count,
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use crate::clock::SharedClock;
+use crate::error::GrindError;
+use crate::event::PublicEntry;
+use crate::net;
+use crate::status;
+use crate::target::Target;
+use chrono::SecondsFormat;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 向每个监控端推送统计快照的间隔
+const PUSH_INTERVAL: Duration = Duration::from_secs(1);
+// 进度文件的刷新间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+// 快照中保留的最近找到的结果数
+const RECENT_FINDS: usize = 100;
+
+/// `--progress` 写出的进度文件
+pub const PROGRESS_FILE: &str = "progress.json";
+/// 允许统计端口对外监听的命令行参数，出现在拒绝监听的错误提示中
+pub const STATS_ALLOW_REMOTE_FLAG: &str = "--stats-allow-remote";
+
+/// 统计端口的访问级别。统计端口从不接受任何命令，两种级别都不含私钥
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsAccess {
+    /// 完整快照：进度、找到的公钥、线程数和配置摘要
+    #[default]
+    Full,
+    /// 旁观：只有进度和找到的公钥，线程数和配置在发送前去掉，适合投到团队看板上
+    Spectate,
+}
+
+impl StatsAccess {
+    pub fn parse(value: &str) -> Result<Self, GrindError> {
+        match value {
+            "full" => Ok(StatsAccess::Full),
+            "spectate" => Ok(StatsAccess::Spectate),
+            other => Err(GrindError::Config(format!("未知的统计端口访问级别 '{}'（可用: full、spectate）", other))),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StatsAccess::Full => "full",
+            StatsAccess::Spectate => "spectate",
+        }
+    }
+}
+
+/// 运行中搜索的统计快照，以每行一个 JSON 对象的形式推送给 `monitor`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsSnapshot {
+    #[serde(default)]
+    pub access: StatsAccess,
+    pub elapsed_secs: f64,
+    pub attempts: u64,
+    /// 整个运行期间的平均速度（个/秒）
+    pub keys_per_sec: f64,
+    /// 工作线程数；旁观快照中为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<usize>,
+    pub targets: Vec<TargetProgress>,
+    /// 最近找到的结果（只有目标、公钥和时间，最多 100 个，先找到的在前）
+    #[serde(default)]
+    pub finds: Vec<PublicEntry>,
+    /// 配置摘要（与 `--debug-dump` 转储中的 `config` 相同）；旁观快照中为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<serde_json::Value>,
+    /// 按当前速度估算的剩余秒数（所有目标都找到为止）；速度为 0 时为空
+    pub eta_secs: Option<f64>,
+    /// 快照生成时间（RFC 3339）
+    pub updated_at: String,
+    /// 所有目标都已找到，搜索即将结束
+    pub finished: bool,
+    /// 所有目标都已找到，正按 `--on-complete idle` 等待追加目标；旧版本的快照没有该字段
+    #[serde(default)]
+    pub idle: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetProgress {
+    pub pattern: String,
+    pub found: u64,
+    pub count: u64,
+    /// 是否仍在参与匹配（已找够数量的目标不再检查）
+    pub active: bool,
+    /// 该目标占剩余预期工作量的比例（0~1）
+    pub effort_share: f64,
+    /// 按当前速度估算该目标找够数量还需的秒数；已完成时为 0
+    pub eta_secs: Option<f64>,
+}
+
+/// 最近找到的结果，由结果写入线程记录，只保留最近的若干个
+#[derive(Debug, Default)]
+pub struct RecentFinds(Mutex<VecDeque<PublicEntry>>);
+
+impl RecentFinds {
+    pub fn push(&self, entry: PublicEntry) {
+        let mut finds = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if finds.len() == RECENT_FINDS {
+            finds.pop_front();
+        }
+        finds.push_back(entry);
+    }
+
+    pub fn list(&self) -> Vec<PublicEntry> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect()
+    }
+}
+
+/// 搜索线程共享的计数器，用于生成快照
+pub struct StatsSource {
+    pub started: Instant,
+    /// 计算已用时间和快照时间的时间来源
+    pub clock: SharedClock,
+    pub threads: usize,
+    pub targets: Vec<Target>,
+    pub ignore_case: bool,
+    pub counter: Arc<AtomicU64>,
+    pub found_counts: Arc<Mutex<Vec<u64>>>,
+    pub finds: Arc<RecentFinds>,
+    /// 完整快照中附带的配置摘要
+    pub config: Option<serde_json::Value>,
+    pub finished: Arc<AtomicBool>,
+    pub idle: Arc<AtomicBool>,
+}
+
+impl StatsSource {
+    /// 按访问级别生成快照：旁观快照在这里去掉线程数和配置，不依赖客户端自觉
+    pub fn snapshot_for(&self, access: StatsAccess) -> StatsSnapshot {
+        let snapshot = self.snapshot();
+        match access {
+            StatsAccess::Full => snapshot,
+            StatsAccess::Spectate => StatsSnapshot { access, threads: None, config: None, ..snapshot },
+        }
+    }
+
+    /// 完整快照
+    pub fn snapshot(&self) -> StatsSnapshot {
+        let elapsed_secs = self.clock.now().duration_since(self.started).as_secs_f64();
+        let attempts = self.counter.load(Ordering::Relaxed);
+        let found = self.found_counts.lock().unwrap_or_else(|e| e.into_inner());
+        let remaining_work: Vec<f64> = self.targets.iter().zip(found.iter())
+            .map(|(t, &n)| t.count.saturating_sub(n) as f64 * t.expected_attempts(self.ignore_case))
+            .collect();
+        let total_work: f64 = remaining_work.iter().sum();
+        let keys_per_sec = if elapsed_secs > 0.0 { attempts as f64 / elapsed_secs } else { 0.0 };
+        // 每个公钥同时检查所有目标，全部完成的时间取决于剩余工作量最大的目标
+        let eta = |work: f64| (keys_per_sec > 0.0).then(|| work / keys_per_sec);
+        StatsSnapshot {
+            access: StatsAccess::Full,
+            elapsed_secs,
+            attempts,
+            keys_per_sec,
+            threads: Some(self.threads),
+            targets: self.targets.iter().zip(found.iter()).zip(remaining_work.iter())
+                .map(|((t, &n), &work)| TargetProgress {
+                    pattern: t.to_string(),
+                    found: n,
+                    count: t.count,
+                    active: n < t.count,
+                    effort_share: if total_work > 0.0 { work / total_work } else { 0.0 },
+                    eta_secs: eta(work),
+                })
+                .collect(),
+            finds: self.finds.list(),
+            config: self.config.clone(),
+            eta_secs: eta(remaining_work.iter().copied().fold(0.0, f64::max)),
+            updated_at: self.clock.local().to_rfc3339_opts(SecondsFormat::Secs, false),
+            finished: self.finished.load(Ordering::Relaxed),
+            idle: self.idle.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 在 `addr` 上监听监控连接，搜索结束后停止接受新连接。
+/// 每个连接每秒收到一行按 `access` 生成的快照，直到连接断开或搜索结束；连接发来的任何数据都被忽略。
+pub fn serve(addr: &str, allow_remote: bool, source: Arc<StatsSource>, access: StatsAccess) -> Result<thread::JoinHandle<()>, GrindError> {
+    let listener = net::listen(addr, allow_remote, STATS_ALLOW_REMOTE_FLAG)?;
+    listener.set_nonblocking(true)?;
+    match access {
+        StatsAccess::Full => status!("统计端口: {}（可用 monitor 子命令连接）", listener.local_addr()?),
+        StatsAccess::Spectate => status!("统计端口: {}（旁观模式：只有进度和找到的公钥，可用 monitor 子命令连接）", listener.local_addr()?),
+    }
+
+    Ok(thread::spawn(move || {
+        while !source.finished.load(Ordering::Relaxed) {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    let source = Arc::clone(&source);
+                    thread::spawn(move || push_snapshots(stream, &source, access));
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(Duration::from_millis(100)),
+                Err(e) => {
+                    eprintln!("警告: 统计端口接受连接失败: {}", e);
+                    thread::sleep(Duration::from_millis(100));
+                }
+            }
+        }
+    }))
+}
+
+/// 每隔几秒将快照原子地写入 `path`（先写临时文件再重命名），搜索结束时写入最终状态后返回
+pub fn write_progress_file(path: PathBuf, source: Arc<StatsSource>) -> thread::JoinHandle<()> {
+    thread::spawn(move || loop {
+        let snapshot = source.snapshot();
+        if let Err(e) = save_snapshot(&path, &snapshot) {
+            eprintln!("警告: 写入进度文件 {} 失败: {}", path.display(), e);
+        }
+        if snapshot.finished {
+            break;
+        }
+        // 分段休眠，搜索结束后尽快写出最终状态
+        let started = Instant::now();
+        while started.elapsed() < PROGRESS_INTERVAL && !source.finished.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(100));
+        }
+    })
+}
+
+fn save_snapshot(path: &Path, snapshot: &StatsSnapshot) -> std::io::Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    let json = serde_json::to_string_pretty(snapshot).expect("StatsSnapshot 序列化不会失败");
+    fs::write(&tmp, json + "\n")?;
+    fs::rename(&tmp, path)
+}
+
+fn push_snapshots(mut stream: TcpStream, source: &StatsSource, access: StatsAccess) {
+    // 监听套接字为非阻塞模式，部分平台上接受的连接会继承该设置
+    let _ = stream.set_nonblocking(false);
+    loop {
+        let snapshot = source.snapshot_for(access);
+        let line = serde_json::to_string(&snapshot).expect("StatsSnapshot 序列化不会失败");
+        if writeln!(stream, "{}", line).is_err() || snapshot.finished {
+            break;
+        }
+        thread::sleep(PUSH_INTERVAL);
+    }
+}
+
+/// 连接到运行中搜索的统计端口，每收到一个快照调用一次 `on_snapshot`，搜索结束或连接断开时返回
+pub fn monitor<F>(addr: &str, mut on_snapshot: F) -> Result<(), GrindError>
+where
+    F: FnMut(&StatsSnapshot),
+{
+    let stream = net::connect(addr)?;
+    for line in BufReader::new(stream).lines() {
+        let snapshot: StatsSnapshot = serde_json::from_str(&line?)
+            .map_err(|e| GrindError::Backend(format!("无法解析统计数据: {}", e)))?;
+        on_snapshot(&snapshot);
+        if snapshot.finished {
+            break;
+        }
+    }
+    Ok(())
+}
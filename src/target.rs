@@ -0,0 +1,508 @@
+use crate::base58;
+use crate::clock::Clock;
+use crate::error::GrindError;
+use crate::query;
+use crate::regex_target::TargetRegex;
+use chrono::{DateTime, Duration, FixedOffset};
+use std::collections::HashMap;
+use std::fmt;
+
+/// 一个搜索目标：公钥需同时满足前缀、后缀和包含的字符串（为空表示不限制），或者满足正则，共需找到 `count` 个
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    pub prefix: String,
+    pub suffix: String,
+    /// `--contains` 目标：出现在公钥中任意位置的字符串；此时前缀和后缀为空
+    pub contains: String,
+    /// `--regex` 目标；此时前缀和后缀为空
+    pub regex: Option<TargetRegex>,
+    /// `--leet` 目标：前缀、后缀和包含的字符串中的字母也可以是对应的 leet 数字（`seek` 也匹配 `5eek`、`s33k`），
+    /// 显示形式为 `leet:seek`
+    pub leet: bool,
+    pub count: u64,
+    /// 希望找够数量的截止时间；按当前速度预计赶不上时发出警告
+    pub deadline: Option<DateTime<FixedOffset>>,
+}
+
+impl Target {
+    pub fn with_prefix(prefix: &str) -> Self {
+        Target {
+            prefix: prefix.to_string(),
+            suffix: String::new(),
+            contains: String::new(),
+            regex: None,
+            leet: false,
+            count: 1,
+            deadline: None,
+        }
+    }
+
+    pub fn with_suffix(suffix: &str) -> Self {
+        Target {
+            prefix: String::new(),
+            suffix: suffix.to_string(),
+            contains: String::new(),
+            regex: None,
+            leet: false,
+            count: 1,
+            deadline: None,
+        }
+    }
+
+    /// 检查前缀、后缀和包含的字符串都在 base58 字母表中（leet 目标中有对应数字的字母也可以，例如 `seal` 的 `l`），
+    /// 不在时给出把 0、O、I、l 换成最接近字符的建议。
+    /// `with_prefix` 等构造函数不做检查，开始搜索前由 [`crate::difficulty::ensure_reachable`] 统一检查
+    pub fn check_alphabet(&self, ignore_case: bool) -> Result<(), GrindError> {
+        let Some(c) = [&self.prefix, &self.suffix, &self.contains].into_iter().find_map(|part| invalid_char(part, ignore_case, self.leet)) else {
+            return Ok(());
+        };
+        let suggestion = match (substitute(&self.prefix), substitute(&self.suffix), substitute(&self.contains)) {
+            (Some(prefix), Some(suffix), Some(contains)) => Some(Target { prefix, suffix, contains, ..self.clone() }.to_string()),
+            _ => None,
+        };
+        Err(alphabet_error(&self.to_string(), c, suggestion))
+    }
+
+    /// 出现在公钥中任意位置的字符串；超过公钥长度的不可能匹配，直接拒绝
+    pub fn with_contains(text: &str) -> Result<Self, GrindError> {
+        contains_target(text, false)
+    }
+
+    pub fn with_regex(source: &str) -> Result<Self, GrindError> {
+        Ok(Target {
+            prefix: String::new(),
+            suffix: String::new(),
+            contains: String::new(),
+            regex: Some(TargetRegex::new(source)?),
+            leet: false,
+            count: 1,
+            deadline: None,
+        })
+    }
+
+    /// 检查候选公钥是否满足该目标（忽略大小写时调用方传入已转为小写的公钥）
+    pub fn matches(&self, public_key: &str) -> bool {
+        if let Some(regex) = &self.regex {
+            return regex.is_match(public_key);
+        }
+        if self.leet {
+            let key = public_key.as_bytes();
+            return key.get(..self.prefix.len()).is_some_and(|head| leet_eq(self.prefix.as_bytes(), head))
+                && key.len().checked_sub(self.suffix.len()).is_some_and(|start| leet_eq(self.suffix.as_bytes(), &key[start..]))
+                && (self.contains.is_empty() || find_leet(key, self.contains.as_bytes()).is_some());
+        }
+        public_key.starts_with(&self.prefix) && public_key.ends_with(&self.suffix) && public_key.contains(&self.contains)
+    }
+
+    /// 位置不固定的目标（包含、正则）在公钥中匹配的字符位置（从 0 计）；前缀、后缀目标为空。
+    /// 忽略大小写时目标已转为小写，先按原样查找，找不到再在转为小写的公钥中查找
+    pub fn match_position(&self, public_key: &str) -> Option<usize> {
+        if let Some(regex) = &self.regex {
+            return regex.find(public_key).map(|m| m.start());
+        }
+        if self.contains.is_empty() {
+            return None;
+        }
+        if self.leet {
+            let pattern = self.contains.as_bytes();
+            return find_leet(public_key.as_bytes(), pattern).or_else(|| find_leet(public_key.to_lowercase().as_bytes(), pattern));
+        }
+        public_key.find(&self.contains).or_else(|| public_key.to_lowercase().find(&self.contains))
+    }
+
+    /// 公钥中与目标对应的部分，保留公钥实际的大小写，写法与目标的显示形式相同（例如 `SeekR`、`...PuMp`）；
+    /// 与目标写法完全相同（没有忽略大小写，或大小写恰好一致）时为空。正则目标总是给出公钥中第一个匹配的部分，
+    /// leet 目标总是给出实际匹配的写法（例如 `leet:seek` 的 `5eek`）
+    pub fn matched_text(&self, public_key: &str) -> Option<String> {
+        if let Some(regex) = &self.regex {
+            return regex.find(public_key).map(|m| m.as_str().to_string());
+        }
+        let prefix = public_key.get(..self.prefix.len())?;
+        let suffix = public_key.get(public_key.len().checked_sub(self.suffix.len())?..)?;
+        let contains = match self.match_position(public_key) {
+            Some(position) => public_key.get(position..position + self.contains.len())?,
+            None => "",
+        };
+        let actual = Target {
+            prefix: prefix.to_string(),
+            suffix: suffix.to_string(),
+            contains: contains.to_string(),
+            leet: false,
+            ..self.clone()
+        };
+        (actual != *self).then(|| actual.to_string())
+    }
+
+    /// 结果日志中使用的匹配类型
+    pub fn kind(&self) -> &'static str {
+        if self.regex.is_some() {
+            return "正则";
+        }
+        if !self.contains.is_empty() {
+            return "包含";
+        }
+        match (self.prefix.is_empty(), self.suffix.is_empty()) {
+            (false, true) => "前缀",
+            (true, false) => "后缀",
+            _ => "前后缀",
+        }
+    }
+
+    /// 结构化输出中使用的匹配类型标识
+    pub fn match_type(&self) -> &'static str {
+        if self.regex.is_some() {
+            return "regex";
+        }
+        if !self.contains.is_empty() {
+            return "contains";
+        }
+        match (self.prefix.is_empty(), self.suffix.is_empty()) {
+            (false, true) => "prefix",
+            (true, false) => "suffix",
+            _ => "prefix_suffix",
+        }
+    }
+
+    /// 平均需要生成多少个密钥对才能命中一次（按每个字符 1/58 的概率粗略估算）。
+    /// 包含的字符串在 44 个字符的公钥中有 `44 - n + 1` 个可能的位置，按位置数折算（忽略重叠）
+    pub fn expected_attempts(&self, ignore_case: bool) -> f64 {
+        if let Some(regex) = &self.regex {
+            return 1.0 / regex.probability(ignore_case);
+        }
+        let positions = if self.contains.is_empty() {
+            1.0
+        } else {
+            (base58::MAX_ENCODED_LEN + 1).saturating_sub(self.contains.len()) as f64
+        };
+        // 忽略大小写时大小写两种形式都在字母表中的字母、leet 目标中有对应数字的字母，命中概率按形式数加倍
+        self.prefix.bytes().chain(self.suffix.bytes()).chain(self.contains.bytes())
+            .map(|c| 58.0 / char_forms(c, ignore_case, self.leet).len() as f64)
+            .product::<f64>() / positions
+    }
+
+    /// 转为小写，用于 --ignore-case
+    pub fn to_lowercase(&self) -> Self {
+        Target {
+            prefix: self.prefix.to_lowercase(),
+            suffix: self.suffix.to_lowercase(),
+            contains: self.contains.to_lowercase(),
+            regex: self.regex.as_ref().map(TargetRegex::folded),
+            leet: self.leet,
+            count: self.count,
+            deadline: self.deadline,
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(regex) = &self.regex {
+            return write!(f, "/{}/", regex.source());
+        }
+        if self.leet {
+            f.write_str("leet:")?;
+        }
+        if !self.contains.is_empty() {
+            return write!(f, "...{}...", self.contains);
+        }
+        match (self.prefix.is_empty(), self.suffix.is_empty()) {
+            (false, true) => write!(f, "{}", self.prefix),
+            (true, false) => write!(f, "...{}", self.suffix),
+            _ => write!(f, "{}...{}", self.prefix, self.suffix),
+        }
+    }
+}
+
+/// 从显示形式（`abc`、`...xyz`、`abc...xyz`、`...mid...`、`/正则/`，以及前面加 `leet:` 的 leet 目标）还原目标，数量为 1
+pub fn parse_label(label: &str) -> Result<Target, GrindError> {
+    match label.strip_prefix("leet:") {
+        Some(rest) if rest.starts_with('/') || rest.starts_with("leet:") => {
+            Err(GrindError::Pattern(format!("无效的目标 '{}'：leet 只适用于前缀、后缀和包含的字符串", label)))
+        }
+        Some(rest) => parse_plain_label(rest, true),
+        None => parse_plain_label(label, false),
+    }
+}
+
+fn parse_plain_label(label: &str, leet: bool) -> Result<Target, GrindError> {
+    if let Some(text) = label.strip_prefix("...").and_then(|rest| rest.strip_suffix("...")).filter(|text| !text.is_empty()) {
+        return contains_target(text, leet);
+    }
+    if let Some(source) = label.strip_prefix('/').and_then(|rest| rest.strip_suffix('/')) {
+        return Target::with_regex(source);
+    }
+    let (prefix, suffix) = label.split_once("...").unwrap_or((label, ""));
+    if prefix.is_empty() && suffix.is_empty() {
+        return Err(GrindError::Pattern(format!("无效的目标 '{}'", label)));
+    }
+    Ok(Target {
+        prefix: prefix.to_string(),
+        suffix: suffix.to_string(),
+        contains: String::new(),
+        regex: None,
+        leet,
+        count: 1,
+        deadline: None,
+    })
+}
+
+fn contains_target(text: &str, leet: bool) -> Result<Target, GrindError> {
+    if text.is_empty() || text.len() > base58::MAX_ENCODED_LEN {
+        return Err(GrindError::Pattern(format!("包含的字符串 '{}' 长度必须在 1 到 {} 之间", text, base58::MAX_ENCODED_LEN)));
+    }
+    Ok(Target {
+        prefix: String::new(),
+        suffix: String::new(),
+        contains: text.to_string(),
+        regex: None,
+        leet,
+        count: 1,
+        deadline: None,
+    })
+}
+
+/// 解析 `--prefix` 和位置参数：`abc` 为前缀；`abc...xyz` 为必须同时满足前缀和后缀的一个目标，
+/// 与 `--starts-and-ends-with abc:xyz:1` 相同
+pub fn parse_prefix_arg(value: &str) -> Result<Target, GrindError> {
+    match value.split_once("...") {
+        Some((prefix, suffix)) if !prefix.is_empty() && !suffix.is_empty() => parse_label(value),
+        _ => Ok(Target::with_prefix(value)),
+    }
+}
+
+/// `--leet` 时字母可以换成的数字，与词表的 `leet` 变换相同；只取 base58 中存在的数字，所以 `o` 没有对应的数字
+pub fn leet_digit(c: char) -> Option<char> {
+    match c.to_ascii_lowercase() {
+        'a' => Some('4'),
+        'b' => Some('8'),
+        'e' => Some('3'),
+        'g' => Some('9'),
+        'i' | 'l' => Some('1'),
+        's' => Some('5'),
+        't' => Some('7'),
+        _ => None,
+    }
+}
+
+/// 目标中的一个字符在匹配时可以对应的所有 base58 字符：忽略大小写时包括另一种大小写，leet 目标还包括对应的数字
+pub fn char_forms(c: u8, ignore_case: bool, leet: bool) -> Vec<u8> {
+    let mut forms = if ignore_case { vec![c.to_ascii_lowercase(), c.to_ascii_uppercase()] } else { vec![c] };
+    forms.dedup();
+    if let Some(digit) = leet.then(|| leet_digit(c as char)).flatten() {
+        forms.push(digit as u8);
+    }
+    forms.retain(|&f| base58::digit_value(f).is_some());
+    forms
+}
+
+/// 目标中的一个字符在匹配索引中的写法。忽略大小写时目标和候选公钥都已转为小写，只保留小写形式，
+/// 按折叠后的字母表判断：`l` 本身不是 base58 字符，但公钥中的 `L` 转为小写后就是它
+pub fn match_forms(c: u8, ignore_case: bool, leet: bool) -> Vec<u8> {
+    if !ignore_case {
+        return char_forms(c, false, leet);
+    }
+    let lower = c.to_ascii_lowercase();
+    let mut forms = Vec::new();
+    if base58::digit_value(lower).is_some() || base58::digit_value(lower.to_ascii_uppercase()).is_some() {
+        forms.push(lower);
+    }
+    if let Some(digit) = leet.then(|| leet_digit(c as char)).flatten() {
+        forms.push(digit as u8);
+    }
+    forms
+}
+
+// leet 目标逐个字符比较：相同，或者公钥中是目标字母对应的数字
+fn leet_eq(pattern: &[u8], text: &[u8]) -> bool {
+    pattern.len() == text.len()
+        && pattern.iter().zip(text).all(|(&p, &t)| p == t || leet_digit(p as char) == Some(t as char))
+}
+
+fn find_leet(text: &[u8], pattern: &[u8]) -> Option<usize> {
+    text.windows(pattern.len()).position(|window| leet_eq(pattern, window))
+}
+
+fn validate_base58(pattern: &str, leet: bool) -> Result<(), GrindError> {
+    match invalid_char(pattern, false, leet) {
+        Some(c) => Err(alphabet_error(pattern, c, substitute(pattern))),
+        None => Ok(()),
+    }
+}
+
+fn is_base58(c: char) -> bool {
+    c.is_ascii() && base58::digit_value(c as u8).is_some()
+}
+
+// 第一个不在字母表中的字符；忽略大小写时另一种大小写在字母表中也算有效（例如 `sOl` 可以匹配 `soL`），
+// leet 目标中有对应数字的字母也算有效（`seal` 的 `l` 可以匹配 `1`）
+fn invalid_char(pattern: &str, ignore_case: bool, leet: bool) -> Option<char> {
+    pattern.chars().find(|&c| {
+        !is_base58(c)
+            && !(ignore_case && (is_base58(c.to_ascii_lowercase()) || is_base58(c.to_ascii_uppercase())))
+            && !(leet && leet_digit(c).is_some())
+    })
+}
+
+// 把无效字符换成最接近的有效字符；有无法替代的字符时没有建议
+fn substitute(pattern: &str) -> Option<String> {
+    pattern.chars().map(|c| if is_base58(c) { Some(c) } else { base58::substitute(c) }).collect()
+}
+
+fn alphabet_error(pattern: &str, c: char, suggestion: Option<String>) -> GrindError {
+    let hint = suggestion.map_or_else(String::new, |s| format!("，可以改用 '{}'", s));
+    GrindError::Pattern(format!(
+        "'{}' 包含非 base58 字符 '{}'（Solana 地址中不会出现 0、O、I、l），搜索永远不会结束{}", pattern, c, hint
+    ))
+}
+
+fn parse_count(value: &str, arg: &str) -> Result<u64, GrindError> {
+    match value.parse::<u64>() {
+        Ok(0) | Err(_) => Err(GrindError::Pattern(format!("{} 中的数量 '{}' 必须是正整数", arg, value))),
+        Ok(count) => Ok(count),
+    }
+}
+
+/// 拆出位置参数和 `--prefix`、`--suffix`、`--contains` 末尾的 `:数量`（例如 `abc:3`）；冒号不在 base58 中，不会与目标本身混淆
+pub fn split_count(value: &str) -> Result<(&str, Option<u64>), GrindError> {
+    match value.rsplit_once(':') {
+        Some((pattern, count)) if !pattern.is_empty() => Ok((pattern, Some(parse_count(count, value)?))),
+        _ => Ok((value, None)),
+    }
+}
+
+/// 解析 solana-keygen grind 风格的参数值:
+/// --starts-with PREFIX:COUNT / --ends-with SUFFIX:COUNT / --starts-and-ends-with PREFIX:SUFFIX:COUNT
+pub fn parse_grind_arg(flag: &str, value: &str) -> Result<Target, GrindError> {
+    let parts: Vec<&str> = value.split(':').collect();
+    if parts.iter().any(|part| part.is_empty()) {
+        return Err(GrindError::Pattern(format!("{} 的参数 '{}' 中存在空字段", flag, value)));
+    }
+    let target = match (flag, parts.as_slice()) {
+        ("--starts-with", [prefix, count]) => Target {
+            prefix: prefix.to_string(),
+            suffix: String::new(),
+            contains: String::new(),
+            regex: None,
+            leet: false,
+            count: parse_count(count, value)?,
+            deadline: None,
+        },
+        ("--ends-with", [suffix, count]) => Target {
+            prefix: String::new(),
+            suffix: suffix.to_string(),
+            contains: String::new(),
+            regex: None,
+            leet: false,
+            count: parse_count(count, value)?,
+            deadline: None,
+        },
+        ("--starts-and-ends-with", [prefix, suffix, count]) => Target {
+            prefix: prefix.to_string(),
+            suffix: suffix.to_string(),
+            contains: String::new(),
+            regex: None,
+            leet: false,
+            count: parse_count(count, value)?,
+            deadline: None,
+        },
+        ("--starts-and-ends-with", _) => {
+            return Err(GrindError::Pattern(format!("{} 的格式应为 PREFIX:SUFFIX:COUNT，实际为 '{}'", flag, value)));
+        }
+        _ => {
+            return Err(GrindError::Pattern(format!("{} 的格式应为 PATTERN:COUNT，实际为 '{}'", flag, value)));
+        }
+    };
+    validate_base58(&target.prefix, false)?;
+    validate_base58(&target.suffix, false)?;
+    Ok(target)
+}
+
+/// 合并了的重复目标
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergedTarget {
+    pub label: String,
+    /// 给出的次数
+    pub sources: usize,
+    /// 合并后要求的数量
+    pub count: u64,
+}
+
+/// 合并重复的目标（忽略大小写时按小写形式比较），保留第一次出现的写法和顺序。
+/// `counted[i]` 为真表示第 i 个目标的数量是明确给出的（例如 `--starts-with abc:3`）：合并后的数量为明确给出的数量之和，
+/// 都没有给出时为 1；截止时间取最早的一个。返回合并后的目标和发生了合并的目标
+pub fn merge_duplicates(targets: Vec<Target>, counted: &[bool], ignore_case: bool) -> (Vec<Target>, Vec<MergedTarget>) {
+    let mut merged: Vec<(Target, usize, Option<u64>)> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for (i, target) in targets.into_iter().enumerate() {
+        let key = if ignore_case { target.to_lowercase().to_string() } else { target.to_string() };
+        let count = counted.get(i).copied().unwrap_or(false).then_some(target.count);
+        match index.get(&key) {
+            Some(&idx) => {
+                let (first, sources, total) = &mut merged[idx];
+                *sources += 1;
+                if let Some(count) = count {
+                    *total = Some(total.unwrap_or(0) + count);
+                }
+                first.deadline = match (first.deadline, target.deadline) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+            }
+            None => {
+                index.insert(key, merged.len());
+                merged.push((target, 1, count));
+            }
+        }
+    }
+    let mut report = Vec::new();
+    let targets = merged.into_iter()
+        .map(|(target, sources, total)| {
+            let target = Target { count: total.unwrap_or(target.count), ..target };
+            if sources > 1 {
+                report.push(MergedTarget { label: target.to_string(), sources, count: target.count });
+            }
+            target
+        })
+        .collect();
+    (targets, report)
+}
+
+/// 解析截止时间：相对于 `clock` 当前时间的时长（`90m`、`24h`、`7d`），或 `query --since` 接受的绝对时间
+pub fn parse_deadline(value: &str, clock: &dyn Clock) -> Result<DateTime<FixedOffset>, GrindError> {
+    let relative = value.char_indices().last().and_then(|(split, _)| {
+        let amount: i64 = value[..split].parse().ok().filter(|&n| n > 0)?;
+        match &value[split..] {
+            "m" => Duration::try_minutes(amount),
+            "h" => Duration::try_hours(amount),
+            "d" => Duration::try_days(amount),
+            _ => None,
+        }
+    });
+    match relative {
+        Some(duration) => Ok((clock.local() + duration).fixed_offset()),
+        None => query::parse_since(value)
+            .map_err(|_| GrindError::Config(format!("无法解析截止时间 '{}'（可用 90m、24h、7d 或 YYYY-MM-DD HH:MM:SS 等）", value))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alphabet_checked_with_ignore_case() {
+        for label in ["...sol...", "So...l", "sol"] {
+            let target = parse_label(label).unwrap();
+            assert!(target.check_alphabet(true).is_ok(), "{}", label);
+            assert!(target.check_alphabet(false).is_err(), "{}", label);
+        }
+        assert!(parse_label("leet:seal").unwrap().check_alphabet(false).is_ok());
+        assert!(parse_label("S0L").unwrap().check_alphabet(true).is_err());
+    }
+
+    #[test]
+    fn grind_args_stay_strict() {
+        assert!(parse_grind_arg("--starts-with", "sol:1").is_err());
+        assert!(parse_grind_arg("--starts-with", "So1:1").is_ok());
+    }
+}
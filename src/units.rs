@@ -0,0 +1,153 @@
+use crate::error::GrindError;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// 可选的速度平滑窗口及其名称
+pub const RATE_WINDOWS: [(&str, Duration); 3] = [
+    ("1s", Duration::from_secs(1)),
+    ("10s", Duration::from_secs(10)),
+    ("1m", Duration::from_secs(60)),
+];
+
+/// 进度和汇总中数量、速度的显示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CountFormat {
+    /// 原样输出整数，例如 `12345678`
+    #[default]
+    Raw,
+    /// 带 K/M/B/T 单位，例如 `12.35M`
+    Human,
+    /// 科学计数法，例如 `1.23e7`
+    Scientific,
+}
+
+impl CountFormat {
+    pub fn parse(value: &str) -> Result<Self, GrindError> {
+        match value {
+            "raw" => Ok(CountFormat::Raw),
+            "human" => Ok(CountFormat::Human),
+            "scientific" => Ok(CountFormat::Scientific),
+            _ => Err(GrindError::Config(format!("不支持的数量显示方式 '{}'（可选: raw, human, scientific）", value))),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CountFormat::Raw => "raw",
+            CountFormat::Human => "human",
+            CountFormat::Scientific => "scientific",
+        }
+    }
+
+    /// 格式化一个数量（例如已生成的密钥对数）
+    pub fn count(self, n: u64) -> String {
+        match self {
+            CountFormat::Raw => n.to_string(),
+            _ => self.rate(n as f64),
+        }
+    }
+
+    /// 格式化一个速度或其他非整数的数量
+    pub fn rate(self, value: f64) -> String {
+        match self {
+            CountFormat::Raw => format!("{:.0}", value),
+            CountFormat::Scientific if value < 1000.0 => format!("{:.0}", value),
+            CountFormat::Scientific => format!("{:.2e}", value),
+            CountFormat::Human => {
+                const UNITS: [(f64, &str); 4] = [(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "K")];
+                match UNITS.iter().find(|(scale, _)| value >= *scale) {
+                    Some((scale, unit)) => format!("{:.2}{}", value / scale, unit),
+                    None => format!("{:.0}", value),
+                }
+            }
+        }
+    }
+}
+
+/// 把秒数写成预计耗时和汇总中使用的形式，只保留最大的两个单位，例如 `3天4小时`、`12分5秒`；
+/// 超过一年时按年计（很难的目标可能要几百万年），无穷大时为 `无限`
+pub fn duration(secs: f64) -> String {
+    const YEAR: f64 = 365.0 * 86400.0;
+    if !secs.is_finite() {
+        return "无限".to_string();
+    }
+    if secs >= 1e4 * YEAR {
+        return format!("{:.2e}年", secs / YEAR);
+    }
+    if secs >= YEAR {
+        return format!("{:.1}年", secs / YEAR);
+    }
+    if secs < 1.0 {
+        return "不到1秒".to_string();
+    }
+    let secs = secs.round() as u64;
+    let (days, hours, minutes, seconds) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    if days > 0 {
+        format!("{}天{}小时", days, hours)
+    } else if hours > 0 {
+        format!("{}小时{}分", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}分{}秒", minutes, seconds)
+    } else {
+        format!("{}秒", seconds)
+    }
+}
+
+/// 解析时间长度：`90s`、`30m`、`2h`、`7d`，不带单位时按秒计
+pub fn parse_duration(value: &str) -> Result<Duration, GrindError> {
+    let invalid = || GrindError::Config(format!("无效的时间 '{}'（例如 90s、30m、2h、7d）", value));
+    let (number, scale) = match value.char_indices().last() {
+        Some((split, 's')) => (&value[..split], 1),
+        Some((split, 'm')) => (&value[..split], 60),
+        Some((split, 'h')) => (&value[..split], 3600),
+        Some((split, 'd')) => (&value[..split], 86400),
+        _ => (value, 1),
+    };
+    number.parse::<u64>().ok()
+        .and_then(|n| n.checked_mul(scale))
+        .filter(|&secs| secs > 0)
+        .map(Duration::from_secs)
+        .ok_or_else(invalid)
+}
+
+/// 解析速度平滑窗口（`1s`、`10s` 或 `1m`）
+pub fn parse_rate_window(value: &str) -> Result<Duration, GrindError> {
+    RATE_WINDOWS.iter()
+        .find(|(name, _)| *name == value)
+        .map(|(_, window)| *window)
+        .ok_or_else(|| GrindError::Config(format!("不支持的速度平滑窗口 '{}'（可选: 1s, 10s, 1m）", value)))
+}
+
+/// 窗口的名称；不是预设窗口时按秒数显示
+pub fn rate_window_name(window: Duration) -> String {
+    RATE_WINDOWS.iter()
+        .find(|(_, w)| *w == window)
+        .map_or_else(|| format!("{}s", window.as_secs()), |(name, _)| name.to_string())
+}
+
+/// 按最近一个窗口内的计数器采样计算平均速度
+#[derive(Debug, Clone)]
+pub struct RateMeter {
+    window: Duration,
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl RateMeter {
+    pub fn new(window: Duration, now: Instant, count: u64) -> Self {
+        RateMeter { window, samples: VecDeque::from([(now, count)]) }
+    }
+
+    /// 记录一次采样，返回最近一个窗口（运行时间不足一个窗口时为全部时间）内的平均速度
+    pub fn record(&mut self, now: Instant, count: u64) -> f64 {
+        self.samples.push_back((now, count));
+        // 保留一个不晚于窗口起点的采样，使计算跨度正好覆盖整个窗口
+        while self.samples.len() > 2 && now.duration_since(self.samples[1].0) >= self.window {
+            self.samples.pop_front();
+        }
+        let (start, first) = self.samples[0];
+        let secs = now.duration_since(start).as_secs_f64();
+        if secs > 0.0 { count.saturating_sub(first) as f64 / secs } else { 0.0 }
+    }
+}
@@ -0,0 +1,63 @@
+use crate::error::GrindError;
+use crate::event::FoundEvent;
+use crate::records;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+/// 将结果文件中的一行解析为 FoundEvent（支持文本格式和 JSONL）
+pub fn parse_result_entry(line: &str) -> Option<FoundEvent> {
+    if line.starts_with('{') {
+        serde_json::from_str(line).ok()
+    } else {
+        records::parse_result_line(line)
+    }
+}
+
+/// 持续跟踪结果文件，每出现一条新结果调用一次 `on_event`。
+/// 采用定时轮询文件大小的方式，因此同样适用于 NFS/SSHFS 等挂载路径；
+/// 文件被截断或替换时从头重新读取。该函数只在出错时返回。
+pub fn watch_file<F>(path: &Path, from_start: bool, interval: Duration, mut on_event: F) -> Result<(), GrindError>
+where
+    F: FnMut(&FoundEvent),
+{
+    let mut offset = match (from_start, std::fs::metadata(path)) {
+        (false, Ok(meta)) => meta.len(),
+        _ => 0,
+    };
+    let mut pending = String::new();
+
+    loop {
+        let len = match std::fs::metadata(path) {
+            Ok(meta) => meta.len(),
+            // 文件尚未创建（搜索还没开始），继续等待
+            Err(_) => {
+                thread::sleep(interval);
+                continue;
+            }
+        };
+        if len < offset {
+            offset = 0;
+            pending.clear();
+        }
+        if len > offset {
+            let mut file = File::open(path)?;
+            file.seek(SeekFrom::Start(offset))?;
+            let mut chunk = Vec::with_capacity((len - offset) as usize);
+            file.take(len - offset).read_to_end(&mut chunk)?;
+            offset += chunk.len() as u64;
+            pending.push_str(&String::from_utf8_lossy(&chunk));
+
+            // 只处理完整的行，不完整的尾部留到下一轮
+            while let Some(pos) = pending.find('\n') {
+                let line: String = pending.drain(..=pos).collect();
+                if let Some(event) = parse_result_entry(line.trim_end()) {
+                    on_event(&event);
+                }
+            }
+        }
+        thread::sleep(interval);
+    }
+}
@@ -0,0 +1,193 @@
+use crate::base58;
+use crate::error::GrindError;
+use crate::target::{self, Target};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+// 词表参数中表示内置词表的前缀，例如 `builtin:en`
+const BUILTIN_PREFIX: &str = "builtin:";
+
+/// 内置词表：`en` 为常见的英文短单词，`zh` 为吉祥话和常用词的拼音，
+/// `profanity` 为英文脏话和常见的拼音缩写，供 `--exclude-words` 排除不雅的目标
+pub const BUILTIN_NAMES: &[&str] = &["en", "zh", "profanity"];
+
+#[cfg(feature = "builtin-wordlists")]
+fn builtin(name: &str) -> Option<&'static str> {
+    match name {
+        "en" => Some(include_str!("../wordlists/en.txt")),
+        "zh" => Some(include_str!("../wordlists/zh.txt")),
+        "profanity" => Some(include_str!("../wordlists/profanity.txt")),
+        _ => None,
+    }
+}
+
+#[cfg(not(feature = "builtin-wordlists"))]
+fn builtin(_name: &str) -> Option<&'static str> {
+    None
+}
+
+/// 读取词表：`builtin:<名称>` 为编译进程序的内置词表（见 [`BUILTIN_NAMES`]，需要默认启用的
+/// `builtin-wordlists` 功能），其余按文件路径读取
+pub fn read_source(source: &str) -> Result<Cow<'static, str>, GrindError> {
+    let Some(name) = source.strip_prefix(BUILTIN_PREFIX) else {
+        return Ok(Cow::Owned(fs::read_to_string(source)?));
+    };
+    if !BUILTIN_NAMES.contains(&name) {
+        let names: Vec<String> = BUILTIN_NAMES.iter().map(|n| format!("{}{}", BUILTIN_PREFIX, n)).collect();
+        return Err(GrindError::Config(format!("没有内置词表 '{}'（可用: {}）", source, names.join(", "))));
+    }
+    builtin(name).map(Cow::Borrowed).ok_or_else(|| GrindError::Config(format!(
+        "编译时未启用 builtin-wordlists 功能，内置词表 {} 不可用，请改用词表文件", source
+    )))
+}
+
+/// 读取要排除的单词（每行一个，忽略空行和 `#` 开头的注释），统一为小写
+pub fn load_words(source: &str) -> Result<HashSet<String>, GrindError> {
+    let text = read_source(source)?;
+    Ok(words(&text).map(str::to_lowercase).collect())
+}
+
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().map(str::trim).filter(|word| !word.is_empty() && !word.starts_with('#'))
+}
+
+/// 对词表中单词的变换，每个单词生成原样及所有变换组合后的形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    /// 首字母大写：`moon` → `Moon`
+    Capitalize,
+    /// 全部大写：`moon` → `MOON`
+    Upper,
+    /// 常见的 leet 替换（只换成 base58 中存在的数字）：`seal` → `5341`
+    Leet,
+}
+
+impl Transform {
+    /// 解析逗号分隔的变换列表，例如 `capitalize,leet`
+    pub fn parse_list(value: &str) -> Result<Vec<Transform>, GrindError> {
+        value.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| match name {
+                "capitalize" => Ok(Transform::Capitalize),
+                "upper" => Ok(Transform::Upper),
+                "leet" => Ok(Transform::Leet),
+                other => Err(GrindError::Config(format!(
+                    "未知的变换 '{}'（可用: capitalize、upper、leet）", other
+                ))),
+            })
+            .collect()
+    }
+
+    fn apply(self, word: &str) -> String {
+        match self {
+            Transform::Capitalize => {
+                let mut chars = word.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect(),
+                    None => String::new(),
+                }
+            }
+            Transform::Upper => word.to_uppercase(),
+            // base58 不含 0，所以 o 保持不变；l 不在字母表中，换成 1 反而能用
+            Transform::Leet => word.chars().map(|c| target::leet_digit(c).unwrap_or(c)).collect(),
+        }
+    }
+}
+
+/// 词表展开的结果
+#[derive(Debug, Clone, Default)]
+pub struct Expansion {
+    /// 去重后的前缀目标，按词表顺序排列
+    pub targets: Vec<Target>,
+    /// 因含有非 base58 字符（如 0、O、I、l）而丢弃的候选数量
+    pub rejected: u64,
+    /// 在排除列表中而跳过的单词数量
+    pub excluded: u64,
+}
+
+/// 读取词表（文件或内置词表，见 [`read_source`]；每行一个单词，忽略空行和 `#` 开头的注释），
+/// 跳过短于 `min_len` 的单词和 `exclude` 中的单词（不区分大小写，在变换之前比较），
+/// 对每个单词应用 `transforms` 的所有组合，得到去重后的前缀目标
+pub fn expand(
+    source: &str,
+    min_len: usize,
+    transforms: &[Transform],
+    exclude: &HashSet<String>,
+) -> Result<Expansion, GrindError> {
+    let text = read_source(source)?;
+    let mut expansion = Expansion::default();
+    let mut seen = HashSet::new();
+    for word in words(&text) {
+        if word.chars().count() < min_len {
+            continue;
+        }
+        if exclude.contains(&word.to_lowercase()) {
+            expansion.excluded += 1;
+            continue;
+        }
+        let mut variants = vec![word.to_string()];
+        for &transform in transforms {
+            let transformed: Vec<String> = variants.iter().map(|v| transform.apply(v)).collect();
+            variants.extend(transformed);
+        }
+        for variant in variants {
+            if !seen.insert(variant.clone()) {
+                continue;
+            }
+            if variant.bytes().all(|c| base58::digit_value(c).is_some()) {
+                expansion.targets.push(Target::with_prefix(&variant));
+            } else {
+                expansion.rejected += 1;
+            }
+        }
+    }
+    if expansion.targets.is_empty() {
+        return Err(GrindError::Pattern(format!("词表 {} 没有展开出任何有效的目标", source)));
+    }
+    Ok(expansion)
+}
+
+/// 目标文件中无法解析的一行
+#[derive(Debug, Clone)]
+pub struct InvalidLine {
+    /// 行号（从 1 计）
+    pub line: usize,
+    pub text: String,
+    pub reason: String,
+}
+
+/// 目标文件的读取结果
+#[derive(Debug, Clone, Default)]
+pub struct PatternFile {
+    /// 去重后的目标，按文件顺序排列
+    pub targets: Vec<Target>,
+    /// 与前面的行重复而跳过的行数
+    pub duplicates: u64,
+    pub invalid: Vec<InvalidLine>,
+}
+
+/// 读取目标文件：每行一个目标，写法与结果中的目标相同（`abc`、`...xyz`、`abc...xyz`、`...mid...`、`/正则/`），
+/// 忽略空行和 `#` 开头的注释。逐行校验（字母表按 `ignore_case` 检查），含非 base58 字符等无效的行记入 `invalid` 后跳过
+pub fn load_patterns(path: &Path, ignore_case: bool) -> Result<PatternFile, GrindError> {
+    let text = fs::read_to_string(path)?;
+    let mut file = PatternFile::default();
+    let mut seen = HashSet::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match target::parse_label(line).and_then(|t| t.check_alphabet(ignore_case).map(|_| t)) {
+            Ok(target) if seen.insert(target.to_string()) => file.targets.push(target),
+            Ok(_) => file.duplicates += 1,
+            Err(e) => file.invalid.push(InvalidLine { line: idx + 1, text: line.to_string(), reason: e.to_string() }),
+        }
+    }
+    if file.targets.is_empty() {
+        return Err(GrindError::Pattern(format!("目标文件 {} 中没有任何有效的目标", path.display())));
+    }
+    Ok(file)
+}